@@ -0,0 +1,28 @@
+use serde_json::Error as SerdeError;
+use thiserror::Error;
+use tokio::sync::broadcast::error::SendError;
+use tokio_tungstenite::tungstenite::error::Error as TungsteniteError;
+
+use shared::{
+    payload::*,
+    pubsub::{Envelope, PublisherError},
+};
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Error, Debug)]
+pub enum PriceFeedError {
+    #[error("PriceFeedError - BinanceWsError: {0}")]
+    BinanceWsError(#[from] TungsteniteError),
+    #[error("PriceFeedError - EmptyBookSide: BinanceDepthUpdate had empty book side")]
+    EmptyBookSide,
+    #[error("PriceFeedError - SerdeError: {0}")]
+    SerializationError(#[from] SerdeError),
+    #[error("PriceFeedError - PublisherError: {0}")]
+    PublisherError(#[from] PublisherError),
+    #[error("PriceFeedError - PricePublish: {0}")]
+    PricePublish(#[from] SendError<Envelope<PriceStreamPayload>>),
+    #[error("PriceFeedError: StreamEnded - Stream ended unexpectedly")]
+    StreamEnded,
+    #[error("PriceFeedError: StreamStalled - No message received within the unhealthy interval")]
+    StreamStalled,
+}
@@ -0,0 +1,94 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![cfg_attr(feature = "fail-on-warnings", deny(clippy::all))]
+
+pub mod error;
+pub mod order_book;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use shared::{
+    health::{FeedHealthCheck, HealthCheckTrigger},
+    payload::*,
+    pubsub::*,
+};
+use tokio::time::timeout;
+use url::Url;
+
+pub use error::*;
+pub use order_book::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BinancePriceFeedConfig {
+    #[serde(default = "default_url")]
+    pub url: Url,
+}
+
+impl Default for BinancePriceFeedConfig {
+    fn default() -> Self {
+        Self { url: default_url() }
+    }
+}
+
+fn default_url() -> Url {
+    Url::parse(BINANCE_WS_URL).expect("invalid binance_ws_url")
+}
+
+pub async fn run(
+    price_stream_publisher: memory::Publisher<PriceStreamPayload>,
+    health_check_trigger: HealthCheckTrigger,
+    unhealthy_msg_interval: std::time::Duration,
+    config: BinancePriceFeedConfig,
+) -> Result<(), PriceFeedError> {
+    let health = FeedHealthCheck::new(
+        "binance order book",
+        chrono::Duration::from_std(unhealthy_msg_interval).expect("invalid unhealthy interval"),
+    );
+    health.respond_to(health_check_trigger);
+
+    loop {
+        if let Err(e) = order_book_subscription(
+            &price_stream_publisher,
+            &health,
+            unhealthy_msg_interval,
+            &config.url,
+        )
+        .await
+        {
+            tracing::warn!(error = %e, "binance_price.order_book.resubscribe");
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(5_u64)).await;
+    }
+}
+
+async fn order_book_subscription(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    health: &FeedHealthCheck,
+    unhealthy_msg_interval: std::time::Duration,
+    url: &Url,
+) -> Result<(), PriceFeedError> {
+    let mut stream = subscribe_btc_usdt_swap_order_book(url).await?;
+    loop {
+        match timeout(unhealthy_msg_interval, stream.next()).await {
+            Ok(Some(book)) => binance_order_book_received(publisher, health, book).await?,
+            Ok(None) => return Err(PriceFeedError::StreamEnded),
+            Err(_) => return Err(PriceFeedError::StreamStalled),
+        }
+    }
+}
+
+async fn binance_order_book_received(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    health: &FeedHealthCheck,
+    book: BinanceDepthUpdate,
+) -> Result<(), PriceFeedError> {
+    if let Ok(order_book) = OrderBookPayload::try_from(book) {
+        health.record_update().await;
+        publisher
+            .throttle_publish(
+                "BINANCE_ORDER_BOOK",
+                PriceStreamPayload::BinanceBtcUsdtSwapOrderBookPayload(order_book),
+            )
+            .await?;
+    }
+    Ok(())
+}
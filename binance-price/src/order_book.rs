@@ -0,0 +1,132 @@
+use futures::{Stream, StreamExt};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use shared::{payload::*, time::*};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use url::Url;
+
+use std::{collections::BTreeMap, pin::Pin};
+
+use crate::PriceFeedError;
+
+pub const BINANCE_WS_URL: &str = "wss://fstream.binance.com/ws";
+pub const BTC_USDT_SWAP_SYMBOL: &str = "BTCUSDT";
+/// Top 20 levels of the USDⓈ-M perpetual, pushed every 100ms
+const PARTIAL_DEPTH_STREAM: &str = "btcusdt@depth20@100ms";
+const CENTS_PER_USD: Decimal = dec!(100);
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Clone)]
+pub struct PriceQuantity(pub Decimal, pub Decimal);
+
+/// https://developers.binance.com/docs/derivatives/usds-margined-futures/websocket-market-streams/Partial-Book-Depth-Streams
+#[derive(Debug, Deserialize, Clone)]
+pub struct BinanceDepthUpdate {
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "u")]
+    pub last_update_id: u64,
+    #[serde(rename = "b")]
+    pub bids: Vec<PriceQuantity>,
+    #[serde(rename = "a")]
+    pub asks: Vec<PriceQuantity>,
+}
+
+/// Partial depth streams carry the complete top of the book in every message
+/// so no local book has to be maintained
+pub async fn subscribe_btc_usdt_swap_order_book(
+    url: &Url,
+) -> Result<Pin<Box<dyn Stream<Item = BinanceDepthUpdate> + Send>>, PriceFeedError> {
+    let request = format!(
+        "{}/{}",
+        url.as_str().trim_end_matches('/'),
+        PARTIAL_DEPTH_STREAM
+    )
+    .into_client_request()?;
+    let (ws_stream, _) = connect_async(request).await?;
+
+    Ok(Box::pin(ws_stream.filter_map(|message| async move {
+        if let Ok(msg) = message {
+            if let Ok(msg_str) = msg.into_text() {
+                if let Ok(book) = serde_json::from_str::<BinanceDepthUpdate>(&msg_str) {
+                    return Some(book);
+                }
+            }
+        }
+        None
+    })))
+}
+
+/// Binance quotes against USDT which is taken to be at par with USD
+impl TryFrom<BinanceDepthUpdate> for OrderBookPayload {
+    type Error = PriceFeedError;
+
+    fn try_from(book: BinanceDepthUpdate) -> Result<Self, Self::Error> {
+        let asks = side_to_payload(book.asks);
+        let bids = side_to_payload(book.bids);
+        if asks.is_empty() || bids.is_empty() {
+            return Err(PriceFeedError::EmptyBookSide);
+        }
+
+        Ok(Self {
+            asks,
+            bids,
+            timestamp: TimeStamp::from(book.event_time / 1000),
+            exchange: ExchangeIdRaw::from(BINANCE_EXCHANGE_ID),
+        })
+    }
+}
+
+fn side_to_payload(side: Vec<PriceQuantity>) -> BTreeMap<PriceRaw, VolumeInCentsRaw> {
+    side.into_iter()
+        .filter(|PriceQuantity(_, quantity)| *quantity > Decimal::ZERO)
+        .map(|PriceQuantity(price, quantity)| {
+            (
+                PriceRaw::from(PriceRatioRaw::from_one_btc_in_usd_price(price).numerator_amount()),
+                VolumeInCentsRaw::from(quantity * price * CENTS_PER_USD),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_partial_depth_update() -> anyhow::Result<()> {
+        let update = serde_json::from_str::<BinanceDepthUpdate>(include_str!(
+            "../tests/fixtures/depth-update.json"
+        ))?;
+        assert_eq!(update.symbol, BTC_USDT_SWAP_SYMBOL);
+
+        let payload = OrderBookPayload::try_from(update)?;
+        assert_eq!(payload.exchange, ExchangeIdRaw::from(BINANCE_EXCHANGE_ID));
+        assert_eq!(payload.timestamp, TimeStamp::from(1718000000));
+        assert_eq!(payload.bids.len(), 3);
+        assert_eq!(payload.asks.len(), 2);
+        assert_eq!(
+            payload.bids.get(&PriceRaw::from(dec!(0.064))),
+            Some(&VolumeInCentsRaw::from(dec!(1600000)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_empty_side() {
+        let update = BinanceDepthUpdate {
+            event_time: 1718000000000,
+            symbol: BTC_USDT_SWAP_SYMBOL.to_string(),
+            last_update_id: 1,
+            bids: vec![PriceQuantity(dec!(64000), dec!(0.25))],
+            asks: vec![PriceQuantity(dec!(64000.1), dec!(0))],
+        };
+        assert!(matches!(
+            OrderBookPayload::try_from(update),
+            Err(PriceFeedError::EmptyBookSide)
+        ));
+    }
+}
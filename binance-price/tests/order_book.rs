@@ -0,0 +1,43 @@
+use binance_price::*;
+use futures::StreamExt;
+
+use shared::{payload::*, pubsub::*};
+
+#[tokio::test]
+async fn subscribes_to_partial_depth_stream() -> anyhow::Result<()> {
+    let config = BinancePriceFeedConfig::default();
+    let mut stream = subscribe_btc_usdt_swap_order_book(&config.url)
+        .await
+        .expect("subscribe to partial depth stream");
+    let book = stream.next().await.expect("order book");
+
+    assert_eq!(book.symbol, BTC_USDT_SWAP_SYMBOL);
+    assert_eq!(book.bids.len(), 20);
+    assert_eq!(book.asks.len(), 20);
+    Ok(())
+}
+
+#[tokio::test]
+async fn publishes_to_price_stream() -> anyhow::Result<()> {
+    let (book_send, mut book_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (_, recv) = futures::channel::mpsc::unbounded();
+
+    tokio::spawn(async move {
+        let _res = binance_price::run(
+            book_send,
+            recv,
+            std::time::Duration::from_secs(20),
+            BinancePriceFeedConfig::default(),
+        )
+        .await;
+    });
+
+    let recv = book_recv.next().await.expect("expected order book");
+    if let PriceStreamPayload::BinanceBtcUsdtSwapOrderBookPayload(book) = recv.payload {
+        assert!(!book.asks.is_empty() && !book.bids.is_empty());
+    } else {
+        panic!("expected binance order book");
+    }
+    Ok(())
+}
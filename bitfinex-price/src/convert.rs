@@ -0,0 +1,52 @@
+use shared::{payload::*, time::*};
+
+use super::price_feed::{BitfinexPriceTick, PriceFeedError, BTC_USD_SWAP_SYMBOL};
+
+impl TryFrom<BitfinexPriceTick> for PriceStreamPayload {
+    type Error = PriceFeedError;
+
+    fn try_from(tick: BitfinexPriceTick) -> Result<Self, Self::Error> {
+        Ok(PriceStreamPayload::BitfinexBtcUsdSwapPricePayload(
+            PriceMessagePayload {
+                exchange: ExchangeIdRaw::from(BITFINEX_EXCHANGE_ID),
+                instrument_id: InstrumentIdRaw::from(BTC_USD_SWAP_SYMBOL),
+                timestamp: tick
+                    .mts
+                    .map(|mts| TimeStamp::from(mts / 1000))
+                    .unwrap_or_else(TimeStamp::now),
+                ask_price: PriceRatioRaw::from_one_btc_in_usd_price(tick.ask),
+                bid_price: PriceRatioRaw::from_one_btc_in_usd_price(tick.bid),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn convert_ticker() -> anyhow::Result<()> {
+        let tick = serde_json::from_str::<BitfinexPriceTick>(include_str!(
+            "../tests/fixtures/ticker.json"
+        ))?;
+        let PriceStreamPayload::BitfinexBtcUsdSwapPricePayload(payload) =
+            PriceStreamPayload::try_from(tick)?
+        else {
+            panic!("expected bitfinex price payload")
+        };
+        assert_eq!(payload.bid_price.numerator_amount(), dec!(0.0640005));
+        assert_eq!(payload.ask_price.numerator_amount(), dec!(0.064001));
+        assert_eq!(payload.timestamp, TimeStamp::from(1718000000));
+        Ok(())
+    }
+
+    #[test]
+    fn heartbeat_is_not_a_tick() {
+        assert!(
+            serde_json::from_str::<BitfinexPriceTick>(r#"[17469,"hb",1718000000000]"#).is_err()
+        );
+    }
+}
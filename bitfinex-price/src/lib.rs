@@ -0,0 +1,144 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![cfg_attr(feature = "fail-on-warnings", deny(clippy::all))]
+
+mod convert;
+pub mod error;
+pub mod order_book;
+pub mod price_feed;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use shared::{
+    health::{FeedHealthCheck, HealthCheckTrigger},
+    payload::*,
+    pubsub::*,
+};
+use tokio::{join, time::timeout};
+use url::Url;
+
+pub use order_book::*;
+pub use price_feed::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitfinexPriceFeedConfig {
+    #[serde(default = "default_url")]
+    pub url: Url,
+}
+
+impl Default for BitfinexPriceFeedConfig {
+    fn default() -> Self {
+        Self { url: default_url() }
+    }
+}
+
+fn default_url() -> Url {
+    Url::parse(BITFINEX_WS_URL).expect("invalid bitfinex_ws_url")
+}
+
+pub async fn run(
+    price_stream_publisher: memory::Publisher<PriceStreamPayload>,
+    health_check_trigger: HealthCheckTrigger,
+    unhealthy_msg_interval: std::time::Duration,
+    config: BitfinexPriceFeedConfig,
+) -> Result<(), PriceFeedError> {
+    let health = FeedHealthCheck::new(
+        "bitfinex order book",
+        chrono::Duration::from_std(unhealthy_msg_interval).expect("invalid unhealthy interval"),
+    );
+    health.respond_to(health_check_trigger);
+
+    let _ = tokio::spawn(async move {
+        loop {
+            let tick_publisher = price_stream_publisher.clone();
+            if let Ok(mut stream) = subscribe_btc_usd_swap_price_tick(&config.url).await {
+                let tick_task = tokio::spawn(async move {
+                    while let Some(tick) = stream.next().await {
+                        let _res = bitfinex_price_tick_received(&tick_publisher, tick).await;
+                    }
+                });
+                let order_book_publisher = price_stream_publisher.clone();
+                let order_book_url = config.url.clone();
+                let order_book_health = health.clone();
+                let order_book_task = tokio::spawn(async move {
+                    loop {
+                        if let Err(e) = order_book_subscription(
+                            order_book_publisher.clone(),
+                            &order_book_health,
+                            unhealthy_msg_interval,
+                            &order_book_url,
+                        )
+                        .await
+                        {
+                            tracing::warn!(error = %e, "bitfinex_price.order_book.resubscribe");
+                        }
+                        tokio::time::sleep(std::time::Duration::from_secs(5_u64)).await;
+                    }
+                });
+                let _ = join!(tick_task, order_book_task);
+            }
+        }
+    })
+    .await;
+
+    Ok(())
+}
+
+/// Bitfinex sends a fresh snapshot on every subscription so a checksum
+/// mismatch or a stalled stream is recovered from by resubscribing.
+async fn order_book_subscription(
+    publisher: memory::Publisher<PriceStreamPayload>,
+    health: &FeedHealthCheck,
+    unhealthy_msg_interval: std::time::Duration,
+    url: &Url,
+) -> Result<(), PriceFeedError> {
+    let mut stream = subscribe_btc_usd_swap_order_book(url).await?;
+    let full_load = stream.next().await.ok_or(PriceFeedError::InitialFullLoad)?;
+    let mut cache = OrderBookCache::new(CompleteOrderBook::try_from(full_load)?);
+
+    loop {
+        match timeout(unhealthy_msg_interval, stream.next()).await {
+            Ok(Some(book)) => {
+                bitfinex_order_book_received(&publisher, health, book, &mut cache).await?
+            }
+            Ok(None) => return Err(PriceFeedError::StreamEnded),
+            Err(_) => return Err(PriceFeedError::StreamStalled),
+        }
+    }
+}
+
+async fn bitfinex_price_tick_received(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    tick: BitfinexPriceTick,
+) -> Result<(), PriceFeedError> {
+    if let Ok(payload) = PriceStreamPayload::try_from(tick) {
+        publisher
+            .throttle_publish("BITFINEX_PRICE_TICK", payload)
+            .await?;
+    }
+    Ok(())
+}
+
+/// The book is only published once it has been verified against the
+/// checksum that follows each batch of updates
+async fn bitfinex_order_book_received(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    health: &FeedHealthCheck,
+    book: BitfinexOrderBook,
+    cache: &mut OrderBookCache,
+) -> Result<(), PriceFeedError> {
+    let verified = matches!(book, BitfinexOrderBook::Checksum { .. });
+    cache.update_order_book(book)?;
+    if verified {
+        health.record_update().await;
+        if let Ok(complete_order_book) = OrderBookPayload::try_from(cache.latest().clone()) {
+            publisher
+                .throttle_publish(
+                    "BITFINEX_ORDER_BOOK",
+                    PriceStreamPayload::BitfinexBtcUsdSwapOrderBookPayload(complete_order_book),
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
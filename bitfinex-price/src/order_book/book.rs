@@ -0,0 +1,352 @@
+use itertools::Itertools;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use serde_json::Value;
+use shared::{payload::*, time::*};
+use std::collections::BTreeMap;
+
+use crate::{
+    price_feed::{channel_id, decimal, message_mts},
+    PriceFeedError,
+};
+
+const CHECKSUM_DEPTH_LIMIT: usize = 25;
+const CENTS_PER_USD: Decimal = dec!(100);
+
+/// `[PRICE, COUNT, AMOUNT]` where a positive amount is a bid and a negative amount an ask
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub count: u64,
+    pub amount: Decimal,
+}
+
+impl TryFrom<&Value> for BookLevel {
+    type Error = PriceFeedError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.as_array().map(Vec::as_slice) {
+            Some([price, count, amount]) => Ok(Self {
+                price: decimal(price)?,
+                count: count.as_u64().ok_or_else(|| {
+                    PriceFeedError::UnexpectedMessage("invalid book level count".to_string())
+                })?,
+                amount: decimal(amount)?,
+            }),
+            _ => Err(PriceFeedError::UnexpectedMessage(
+                "not a book level".to_string(),
+            )),
+        }
+    }
+}
+
+/// Messages on the `book` channel. The snapshot is sent once after subscribing,
+/// followed by one update per changed level and a checksum of the resulting book.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Vec<Value>")]
+pub enum BitfinexOrderBook {
+    Snapshot {
+        channel_id: u64,
+        levels: Vec<BookLevel>,
+        mts: Option<u64>,
+    },
+    Update {
+        channel_id: u64,
+        level: BookLevel,
+        mts: Option<u64>,
+    },
+    Checksum {
+        channel_id: u64,
+        checksum: i32,
+        mts: Option<u64>,
+    },
+}
+
+impl TryFrom<Vec<Value>> for BitfinexOrderBook {
+    type Error = PriceFeedError;
+
+    fn try_from(message: Vec<Value>) -> Result<Self, Self::Error> {
+        let channel_id = channel_id(&message)?;
+        match message.get(1) {
+            Some(Value::String(event)) if event == "cs" => {
+                Ok(Self::Checksum {
+                    channel_id,
+                    checksum: message.get(2).and_then(Value::as_i64).ok_or_else(|| {
+                        PriceFeedError::UnexpectedMessage("invalid checksum".into())
+                    })? as i32,
+                    mts: message_mts(&message, 3),
+                })
+            }
+            Some(Value::Array(levels)) if levels.iter().all(Value::is_array) => {
+                Ok(Self::Snapshot {
+                    channel_id,
+                    levels: levels
+                        .iter()
+                        .map(BookLevel::try_from)
+                        .collect::<Result<_, _>>()?,
+                    mts: message_mts(&message, 2),
+                })
+            }
+            Some(level @ Value::Array(_)) => Ok(Self::Update {
+                channel_id,
+                level: BookLevel::try_from(level)?,
+                mts: message_mts(&message, 2),
+            }),
+            _ => Err(PriceFeedError::UnexpectedMessage(
+                "not a book message".to_string(),
+            )),
+        }
+    }
+}
+
+impl BitfinexOrderBook {
+    pub fn timestamp(&self) -> TimeStamp {
+        let mts = match self {
+            Self::Snapshot { mts, .. } | Self::Update { mts, .. } | Self::Checksum { mts, .. } => {
+                mts
+            }
+        };
+        mts.map(|mts| TimeStamp::from(mts / 1000))
+            .unwrap_or_else(TimeStamp::now)
+    }
+}
+
+#[derive(Debug, PartialOrd, PartialEq, Eq, Ord, Clone)]
+pub struct OrderPrice(Decimal);
+impl From<Decimal> for OrderPrice {
+    fn from(d: Decimal) -> Self {
+        Self(d)
+    }
+}
+
+/// Levels keep the signed amounts as sent by bitfinex since the checksum is
+/// computed over them
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompleteOrderBook {
+    asks: BTreeMap<OrderPrice, Decimal>,
+    bids: BTreeMap<OrderPrice, Decimal>,
+    timestamp: TimeStamp,
+}
+
+impl TryFrom<BitfinexOrderBook> for CompleteOrderBook {
+    type Error = PriceFeedError;
+
+    fn try_from(book: BitfinexOrderBook) -> Result<Self, Self::Error> {
+        let timestamp = book.timestamp();
+        match book {
+            BitfinexOrderBook::Snapshot { levels, .. } => {
+                Ok(Self::from_snapshot(levels, timestamp))
+            }
+            _ => Err(PriceFeedError::InitialFullLoad),
+        }
+    }
+}
+
+impl CompleteOrderBook {
+    pub fn from_snapshot(levels: Vec<BookLevel>, timestamp: TimeStamp) -> Self {
+        let mut book = Self {
+            asks: BTreeMap::new(),
+            bids: BTreeMap::new(),
+            timestamp,
+        };
+        for level in levels {
+            book.apply(level);
+        }
+        book
+    }
+
+    /// https://docs.bitfinex.com/reference/ws-public-books#algorithm-to-create-and-keep-a-book-instance-updated
+    fn apply(&mut self, level: BookLevel) {
+        let side = if level.amount > Decimal::ZERO {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        };
+        if level.count == 0 {
+            side.remove(&OrderPrice::from(level.price));
+        } else {
+            side.insert(OrderPrice::from(level.price), level.amount);
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn verify_checksum(&self, checksum: i32) -> Result<(), PriceFeedError> {
+        if self.calculate_checksum() != checksum {
+            return Err(PriceFeedError::CheckSumValidation);
+        }
+        Ok(())
+    }
+
+    /// https://docs.bitfinex.com/docs/ws-websocket-checksum
+    fn calculate_checksum(&self) -> i32 {
+        let bids_list = self
+            .bids
+            .iter()
+            .rev()
+            .take(CHECKSUM_DEPTH_LIMIT)
+            .map(|(price, amount)| format!("{}:{}", js_number(price.0), js_number(*amount)));
+        let asks_list = self
+            .asks
+            .iter()
+            .take(CHECKSUM_DEPTH_LIMIT)
+            .map(|(price, amount)| format!("{}:{}", js_number(price.0), js_number(*amount)));
+
+        let crc = Itertools::intersperse(bids_list.interleave(asks_list), ":".to_string())
+            .collect::<String>();
+
+        crc32fast::hash(crc.as_bytes()) as i32
+    }
+}
+
+/// The checksum is computed by bitfinex over javascript's number formatting,
+/// which switches to exponent notation below 1e-6
+fn js_number(value: Decimal) -> String {
+    let value = value.normalize();
+    match value.to_f64() {
+        Some(float) if float != 0.0 && float.abs() < 1e-6 => format!("{:e}", float),
+        _ => value.to_string(),
+    }
+}
+
+impl TryFrom<CompleteOrderBook> for OrderBookPayload {
+    type Error = PriceFeedError;
+
+    fn try_from(book: CompleteOrderBook) -> Result<Self, Self::Error> {
+        if book.asks.is_empty() || book.bids.is_empty() {
+            return Err(PriceFeedError::EmptyBookSide);
+        }
+        let mut asks_map = BTreeMap::new();
+        for (ask_price, ask_amount) in book.asks {
+            let price = PriceRatioRaw::from_one_btc_in_usd_price(ask_price.0).numerator_amount();
+            let _ = asks_map.insert(
+                PriceRaw::from(price),
+                VolumeInCentsRaw::from(ask_amount.abs() * ask_price.0 * CENTS_PER_USD),
+            );
+        }
+
+        let mut bids_map = BTreeMap::new();
+        for (bid_price, bid_amount) in book.bids {
+            let price = PriceRatioRaw::from_one_btc_in_usd_price(bid_price.0).numerator_amount();
+            let _ = bids_map.insert(
+                PriceRaw::from(price),
+                VolumeInCentsRaw::from(bid_amount.abs() * bid_price.0 * CENTS_PER_USD),
+            );
+        }
+
+        Ok(Self {
+            asks: asks_map,
+            bids: bids_map,
+            timestamp: book.timestamp,
+            exchange: ExchangeIdRaw::from(BITFINEX_EXCHANGE_ID),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct OrderBookCache {
+    current: CompleteOrderBook,
+}
+
+impl OrderBookCache {
+    pub fn new(book: CompleteOrderBook) -> Self {
+        Self { current: book }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn update_order_book(&mut self, book: BitfinexOrderBook) -> Result<(), PriceFeedError> {
+        let timestamp = book.timestamp();
+        match book {
+            BitfinexOrderBook::Snapshot { levels, .. } => {
+                self.current = CompleteOrderBook::from_snapshot(levels, timestamp);
+            }
+            BitfinexOrderBook::Update { level, .. } => {
+                self.current.apply(level);
+                self.current.timestamp = timestamp;
+            }
+            BitfinexOrderBook::Checksum { checksum, .. } => {
+                self.current.verify_checksum(checksum)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn latest(&self) -> &CompleteOrderBook {
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded_feed() -> Vec<BitfinexOrderBook> {
+        include_str!("../../tests/fixtures/book-feed.jsonl")
+            .lines()
+            .filter_map(|line| serde_json::from_str::<BitfinexOrderBook>(line).ok())
+            .collect()
+    }
+
+    #[test]
+    fn parses_book_messages() {
+        let feed = recorded_feed();
+        assert_eq!(feed.len(), 5);
+        assert!(
+            matches!(&feed[0], BitfinexOrderBook::Snapshot { levels, .. } if levels.len() == 50)
+        );
+        assert!(matches!(feed[2], BitfinexOrderBook::Update { .. }));
+        assert!(matches!(feed[4], BitfinexOrderBook::Checksum { .. }));
+    }
+
+    #[test]
+    fn applies_updates_and_verifies_checksums() -> anyhow::Result<()> {
+        let mut feed = recorded_feed().into_iter();
+        let mut cache =
+            OrderBookCache::new(CompleteOrderBook::try_from(feed.next().expect("snapshot"))?);
+        for book in feed {
+            cache.update_order_book(book)?;
+        }
+        assert_eq!(cache.latest().asks.len(), 24);
+        assert_eq!(
+            cache.latest().bids.get(&OrderPrice::from(dec!(64000))),
+            Some(&dec!(0.75))
+        );
+
+        let payload = OrderBookPayload::try_from(cache.latest().clone())?;
+        assert_eq!(payload.exchange, ExchangeIdRaw::from(BITFINEX_EXCHANGE_ID));
+        let top_bid_volume = payload
+            .bids
+            .get(&PriceRaw::from(dec!(0.064)))
+            .expect("top bid");
+        assert_eq!(*top_bid_volume, VolumeInCentsRaw::from(dec!(4800000)));
+        Ok(())
+    }
+
+    #[test]
+    fn detects_checksum_mismatch() -> anyhow::Result<()> {
+        let mut feed = recorded_feed().into_iter();
+        let mut cache =
+            OrderBookCache::new(CompleteOrderBook::try_from(feed.next().expect("snapshot"))?);
+        cache.update_order_book(BitfinexOrderBook::Update {
+            channel_id: 17470,
+            level: BookLevel {
+                price: dec!(64000.25),
+                count: 1,
+                amount: dec!(0.1),
+            },
+            mts: None,
+        })?;
+        assert!(matches!(
+            cache.update_order_book(feed.next().expect("checksum")),
+            Err(PriceFeedError::CheckSumValidation)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn formats_numbers_like_javascript() {
+        assert_eq!(js_number(dec!(64000.50)), "64000.5");
+        assert_eq!(js_number(dec!(-2)), "-2");
+        assert_eq!(js_number(dec!(0.00000015)), "1.5e-7");
+    }
+}
@@ -0,0 +1,29 @@
+mod book;
+
+use futures::Stream;
+use std::pin::Pin;
+use url::Url;
+
+use super::error::*;
+use crate::price_feed::{subscribe_channel, BTC_USD_SWAP_SYMBOL};
+pub use book::*;
+
+pub(crate) const BOOK_CHANNEL: &str = "book";
+const BOOK_DEPTH: &str = "25";
+
+pub async fn subscribe_btc_usd_swap_order_book(
+    url: &Url,
+) -> Result<Pin<Box<dyn Stream<Item = BitfinexOrderBook> + Send>>, PriceFeedError> {
+    subscribe_channel(
+        url,
+        serde_json::json!({
+            "event": "subscribe",
+            "channel": BOOK_CHANNEL,
+            "symbol": BTC_USD_SWAP_SYMBOL,
+            "prec": "P0",
+            "freq": "F0",
+            "len": BOOK_DEPTH
+        }),
+    )
+    .await
+}
@@ -0,0 +1,88 @@
+mod tick;
+
+use futures::{SinkExt, Stream, StreamExt};
+use rust_decimal::Decimal;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use url::Url;
+
+use std::pin::Pin;
+
+pub use crate::error::*;
+pub use tick::*;
+
+pub const BITFINEX_WS_URL: &str = "wss://api-pub.bitfinex.com/ws/2";
+pub const BTC_USD_SWAP_SYMBOL: &str = "tBTCF0:USTF0";
+pub(crate) const TICKER_CHANNEL: &str = "ticker";
+
+/// https://docs.bitfinex.com/docs/ws-general#configuration
+const TIMESTAMP_FLAG: u64 = 32768;
+const OB_CHECKSUM_FLAG: u64 = 131072;
+
+pub async fn subscribe_btc_usd_swap_price_tick(
+    url: &Url,
+) -> Result<Pin<Box<dyn Stream<Item = BitfinexPriceTick> + Send>>, PriceFeedError> {
+    subscribe_channel(
+        url,
+        serde_json::json!({
+            "event": "subscribe",
+            "channel": TICKER_CHANNEL,
+            "symbol": BTC_USD_SWAP_SYMBOL
+        }),
+    )
+    .await
+}
+
+/// Opens a connection carrying a single subscription. Event messages and
+/// heartbeats don't deserialize into `T` and are dropped.
+pub(crate) async fn subscribe_channel<T: DeserializeOwned + Send + 'static>(
+    url: &Url,
+    subscription: Value,
+) -> Result<Pin<Box<dyn Stream<Item = T> + Send>>, PriceFeedError> {
+    let request = url.as_str().into_client_request()?;
+    let (ws_stream, _) = connect_async(request).await?;
+    let (mut sender, receiver) = ws_stream.split();
+
+    let conf = serde_json::json!({
+        "event": "conf",
+        "flags": TIMESTAMP_FLAG | OB_CHECKSUM_FLAG
+    })
+    .to_string();
+    sender.send(Message::from(conf)).await?;
+    sender.send(Message::from(subscription.to_string())).await?;
+
+    Ok(Box::pin(receiver.filter_map(|message| async move {
+        if let Ok(msg) = message {
+            if let Ok(msg_str) = msg.into_text() {
+                if let Ok(item) = serde_json::from_str::<T>(&msg_str) {
+                    return Some(item);
+                }
+            }
+        }
+        None
+    })))
+}
+
+#[allow(clippy::result_large_err)]
+pub(crate) fn channel_id(message: &[Value]) -> Result<u64, PriceFeedError> {
+    message
+        .first()
+        .and_then(Value::as_u64)
+        .ok_or_else(|| PriceFeedError::UnexpectedMessage("missing channel id".to_string()))
+}
+
+/// With the timestamp flag set every channel message ends with the server time in millis
+pub(crate) fn message_mts(message: &[Value], expected_len: usize) -> Option<u64> {
+    if message.len() > expected_len {
+        message.last().and_then(Value::as_u64)
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::result_large_err)]
+pub(crate) fn decimal(value: &Value) -> Result<Decimal, PriceFeedError> {
+    Ok(<Decimal as Deserialize>::deserialize(value)?)
+}
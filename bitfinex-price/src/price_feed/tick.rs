@@ -0,0 +1,41 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{channel_id, decimal, message_mts};
+use crate::PriceFeedError;
+
+/// `[CHANNEL_ID, [BID, BID_SIZE, ASK, ASK_SIZE, DAILY_CHANGE, DAILY_CHANGE_RELATIVE, LAST_PRICE, VOLUME, HIGH, LOW], MTS]`
+#[derive(Clone, Deserialize, Debug)]
+#[serde(try_from = "Vec<Value>")]
+pub struct BitfinexPriceTick {
+    pub channel_id: u64,
+    pub bid: Decimal,
+    pub bid_size: Decimal,
+    pub ask: Decimal,
+    pub ask_size: Decimal,
+    pub last_price: Decimal,
+    pub mts: Option<u64>,
+}
+
+impl TryFrom<Vec<Value>> for BitfinexPriceTick {
+    type Error = PriceFeedError;
+
+    fn try_from(message: Vec<Value>) -> Result<Self, Self::Error> {
+        let data = message
+            .get(1)
+            .and_then(Value::as_array)
+            .filter(|data| data.len() >= 7)
+            .ok_or_else(|| PriceFeedError::UnexpectedMessage("not a ticker update".to_string()))?;
+
+        Ok(Self {
+            channel_id: channel_id(&message)?,
+            bid: decimal(&data[0])?,
+            bid_size: decimal(&data[1])?,
+            ask: decimal(&data[2])?,
+            ask_size: decimal(&data[3])?,
+            last_price: decimal(&data[6])?,
+            mts: message_mts(&message, 2),
+        })
+    }
+}
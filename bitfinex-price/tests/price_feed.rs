@@ -0,0 +1,71 @@
+use bitfinex_price::*;
+use chrono::Duration;
+use futures::StreamExt;
+
+use shared::{payload::*, pubsub::*, time::*};
+
+#[tokio::test]
+async fn subscribes_to_ticker_channel() -> anyhow::Result<()> {
+    let config = BitfinexPriceFeedConfig::default();
+    let mut received = subscribe_btc_usd_swap_price_tick(&config.url)
+        .await
+        .expect("subscribe_btc_usd_swap");
+    let price_tick = received.next().await.expect("expected price tick");
+
+    let duration_since =
+        TimeStamp::from(price_tick.mts.expect("timestamp flag") / 1000).duration_since();
+    assert!(duration_since < Duration::try_seconds(30).unwrap());
+    assert!(price_tick.ask >= price_tick.bid);
+    Ok(())
+}
+
+#[tokio::test]
+async fn subscribes_to_book_channel() -> anyhow::Result<()> {
+    let config = BitfinexPriceFeedConfig::default();
+    let mut order_book_stream = subscribe_btc_usd_swap_order_book(&config.url)
+        .await
+        .expect("subscribe to book channel");
+    let order_book = order_book_stream.next().await.expect("order book");
+
+    if let BitfinexOrderBook::Snapshot { levels, .. } = order_book {
+        assert_eq!(levels.len(), 50);
+    } else {
+        panic!("expected snapshot");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn publishes_to_price_stream() -> anyhow::Result<()> {
+    let (tick_send, mut tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (_, recv) = futures::channel::mpsc::unbounded();
+
+    tokio::spawn(async move {
+        let _res = bitfinex_price::run(
+            tick_send,
+            recv,
+            std::time::Duration::from_secs(20),
+            BitfinexPriceFeedConfig::default(),
+        )
+        .await;
+    });
+
+    let mut received_tick = false;
+    let mut received_book = false;
+    while !(received_tick && received_book) {
+        let recv = tick_recv
+            .next()
+            .await
+            .expect("expected price stream payload");
+        match recv.payload {
+            PriceStreamPayload::BitfinexBtcUsdSwapPricePayload(_) => received_tick = true,
+            PriceStreamPayload::BitfinexBtcUsdSwapOrderBookPayload(book) => {
+                assert!(!book.asks.is_empty() && !book.bids.is_empty());
+                received_book = true
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
@@ -44,6 +44,12 @@ enum Command {
         /// Okex passphrase
         #[clap(env = "OKEX_PASSPHRASE", default_value = "")]
         okex_passphrase: String,
+        /// Okex master account secret key
+        #[clap(env = "OKEX_MASTER_SECRET_KEY", default_value = "")]
+        okex_master_secret_key: String,
+        /// Okex master account passphrase
+        #[clap(env = "OKEX_MASTER_PASSPHRASE", default_value = "")]
+        okex_master_passphrase: String,
         /// Bria profile api key
         #[clap(env = "BRIA_PROFILE_API_KEY", default_value = "")]
         bria_profile_api_key: String,
@@ -93,6 +99,8 @@ pub async fn run() -> anyhow::Result<()> {
             galoy_phone_code,
             okex_passphrase,
             okex_secret_key,
+            okex_master_passphrase,
+            okex_master_secret_key,
             pg_con,
             bria_profile_api_key,
         } => {
@@ -102,6 +110,8 @@ pub async fn run() -> anyhow::Result<()> {
                     galoy_phone_code,
                     okex_passphrase,
                     okex_secret_key,
+                    okex_master_passphrase,
+                    okex_master_secret_key,
                     pg_con,
                     bria_profile_api_key,
                 },
@@ -152,6 +162,10 @@ async fn run_cmd(
         galoy,
         hedging,
         exchanges,
+        okex_price_feed,
+        bitfinex_price_feed,
+        binance_price_feed,
+        kraken_price_feed,
         bria,
         quotes_server,
     }: Config,
@@ -163,6 +177,7 @@ async fn run_cmd(
     let (send, mut receive) = tokio::sync::mpsc::channel(1);
     let mut handles = Vec::new();
     let mut checkers = HashMap::new();
+    let mut details = HashMap::new();
     let (price_send, price_recv) = memory::channel(price_stream_throttle_period());
 
     let unhealthy_msg_interval = price_server
@@ -180,36 +195,137 @@ async fn run_cmd(
 
         let okex_send = send.clone();
         let price_send = price_send.clone();
+        let (snd, recv) = futures::channel::mpsc::unbounded();
+        checkers.insert("okex_price", snd);
         handles.push(tokio::spawn(async move {
             let _ = okex_send.try_send(
-                okex_price::run(price_send, unhealthy_msg_interval / 2)
-                    .await
-                    .context("Okex Price Feed error"),
+                okex_price::run(
+                    price_send,
+                    recv,
+                    unhealthy_msg_interval / 2,
+                    okex_price_feed,
+                )
+                .await
+                .context("Okex Price Feed error"),
+            );
+        }));
+    }
+
+    if exchanges
+        .bitfinex
+        .as_ref()
+        .map(|bitfinex| bitfinex.weight > Decimal::ZERO)
+        .unwrap_or(false)
+    {
+        println!("Starting Bitfinex price feed");
+
+        let bitfinex_send = send.clone();
+        let price_send = price_send.clone();
+        let (snd, recv) = futures::channel::mpsc::unbounded();
+        checkers.insert("bitfinex_price", snd);
+        handles.push(tokio::spawn(async move {
+            let _ = bitfinex_send.try_send(
+                bitfinex_price::run(
+                    price_send,
+                    recv,
+                    unhealthy_msg_interval / 2,
+                    bitfinex_price_feed,
+                )
+                .await
+                .context("Bitfinex Price Feed error"),
+            );
+        }));
+    }
+
+    if exchanges
+        .binance
+        .as_ref()
+        .map(|binance| binance.weight > Decimal::ZERO)
+        .unwrap_or(false)
+    {
+        println!("Starting Binance price feed");
+
+        let binance_send = send.clone();
+        let price_send = price_send.clone();
+        let (snd, recv) = futures::channel::mpsc::unbounded();
+        checkers.insert("binance_price", snd);
+        handles.push(tokio::spawn(async move {
+            let _ = binance_send.try_send(
+                binance_price::run(
+                    price_send,
+                    recv,
+                    unhealthy_msg_interval / 2,
+                    binance_price_feed,
+                )
+                .await
+                .context("Binance Price Feed error"),
             );
         }));
     }
 
+    if exchanges
+        .kraken
+        .as_ref()
+        .map(|kraken| kraken.weight > Decimal::ZERO)
+        .unwrap_or(false)
+    {
+        println!("Starting Kraken price feed");
+
+        let kraken_send = send.clone();
+        let price_send = price_send.clone();
+        let (snd, recv) = futures::channel::mpsc::unbounded();
+        checkers.insert("kraken_price", snd);
+        handles.push(tokio::spawn(async move {
+            let _ = kraken_send.try_send(
+                kraken_price::run(
+                    price_send,
+                    recv,
+                    unhealthy_msg_interval / 2,
+                    kraken_price_feed,
+                )
+                .await
+                .context("Kraken Price Feed error"),
+            );
+        }));
+    }
+
+    let mut pool = None;
+    let mut ledger = None;
+
     if price_server.enabled {
         println!(
             "Starting price server on port {}",
             price_server.server.listen_port
         );
 
+        if price_server.history.enabled {
+            pool = Some(crate::db::init_pool(&db).await?);
+            ledger = Some(ledger::Ledger::init(pool.as_ref().unwrap()).await?);
+        }
+
         let price_send = send.clone();
         let (snd, recv) = futures::channel::mpsc::unbounded();
         checkers.insert("price", snd);
+        let (details_snd, details_recv) = futures::channel::mpsc::unbounded();
+        details.insert("price", details_snd);
         let price = price_recv.resubscribe();
         let weights = extract_weights(&exchanges);
+        let pool = pool.clone();
         handles.push(tokio::spawn(async move {
             let _ = price_send.try_send(
                 price_server::run(
                     recv,
+                    details_recv,
                     price_server.health,
                     price_server.server,
                     price_server.fees,
                     price,
                     price_server.price_cache,
+                    price_server.mixer,
                     weights,
+                    price_server.history,
+                    pool,
+                    price_server.fx,
                 )
                 .await
                 .context("Price Server error"),
@@ -217,9 +333,6 @@ async fn run_cmd(
         }));
     }
 
-    let mut pool = None;
-    let mut ledger = None;
-
     if hedging.enabled {
         println!("Starting hedging process");
 
@@ -231,8 +344,10 @@ async fn run_cmd(
         checkers.insert("hedging", snd);
 
         if let Some(okex_cfg) = exchanges.okex.as_ref() {
-            pool = Some(crate::db::init_pool(&db).await?);
-            ledger = Some(ledger::Ledger::init(pool.as_ref().unwrap()).await?);
+            if pool.is_none() {
+                pool = Some(crate::db::init_pool(&db).await?);
+                ledger = Some(ledger::Ledger::init(pool.as_ref().unwrap()).await?);
+            }
 
             let okex_config = okex_cfg.config.clone();
             let pool = pool.clone();
@@ -266,8 +381,10 @@ async fn run_cmd(
         let quotes_send = send.clone();
         let (snd, recv) = futures::channel::mpsc::unbounded();
         checkers.insert("quotes", snd);
+        let (details_snd, details_recv) = futures::channel::mpsc::unbounded();
+        details.insert("quotes", details_snd);
         let price = price_recv.resubscribe();
-        let weights = extract_weights_for_quotes_server(&exchanges);
+        let weights = extract_weights(&exchanges);
         let ledger = ledger.clone();
         let pool = pool.clone();
         handles.push(tokio::spawn(async move {
@@ -275,11 +392,13 @@ async fn run_cmd(
                 quotes_server::run(
                     pool.as_ref().unwrap().clone(),
                     recv,
+                    details_recv,
                     quotes_server.health,
                     quotes_server.server,
                     quotes_server.fees,
                     price,
                     quotes_server.price_cache,
+                    quotes_server.mixer,
                     weights,
                     quotes_server.config,
                     ledger.as_ref().unwrap().clone(),
@@ -308,7 +427,7 @@ async fn run_cmd(
     }
 
     handles.push(tokio::spawn(async move {
-        let _ = send.try_send(crate::health::run(checkers).await);
+        let _ = send.try_send(crate::health::run(checkers, details).await);
     }));
     let reason = receive.recv().await.expect("Didn't receive msg");
     for handle in handles {
@@ -338,16 +457,11 @@ fn price_stream_throttle_period() -> Duration {
     Duration::from_std(std::time::Duration::from_millis(500)).unwrap()
 }
 
-fn extract_weights(config: &hedging::ExchangesConfig) -> price_server::ExchangeWeights {
-    price_server::ExchangeWeights {
-        okex: config.okex.as_ref().map(|c| c.weight),
-    }
-}
-
-fn extract_weights_for_quotes_server(
-    config: &hedging::ExchangesConfig,
-) -> quotes_server::ExchangeWeights {
-    quotes_server::ExchangeWeights {
+fn extract_weights(config: &hedging::ExchangesConfig) -> pricing::ExchangeWeights {
+    pricing::ExchangeWeights {
         okex: config.okex.as_ref().map(|c| c.weight),
+        bitfinex: config.bitfinex.as_ref().map(|c| c.weight),
+        binance: config.binance.as_ref().map(|c| c.weight),
+        kraken: config.kraken.as_ref().map(|c| c.weight),
     }
 }
@@ -2,16 +2,16 @@ use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use binance_price::BinancePriceFeedConfig;
+use bitfinex_price::BitfinexPriceFeedConfig;
 use bria_client::BriaClientConfig;
 use galoy_client::GaloyClientConfig;
 use hedging::{ExchangesConfig, HedgingAppConfig};
-use price_server::{
-    ExchangePriceCacheConfig, FeeCalculatorConfig, PriceServerConfig, PriceServerHealthCheckConfig,
-};
-use quotes_server::{
-    QuotesConfig, QuotesExchangePriceCacheConfig, QuotesFeeCalculatorConfig, QuotesServerConfig,
-    QuotesServerHealthCheckConfig,
-};
+use kraken_price::KrakenPriceFeedConfig;
+use okex_price::OkexPriceFeedConfig;
+use price_server::{FxConfig, PriceHistoryConfig, PriceServerConfig, PriceServerHealthCheckConfig};
+use pricing::{ExchangePriceCacheConfig, FeeCalculatorConfig, PriceMixerConfig};
+use quotes_server::{QuotesConfig, QuotesServerConfig, QuotesServerHealthCheckConfig};
 use user_trades::UserTradesConfig;
 
 use super::{db::DbConfig, tracing::TracingConfig};
@@ -33,6 +33,14 @@ pub struct Config {
     #[serde(default)]
     pub exchanges: ExchangesConfig,
     #[serde(default)]
+    pub okex_price_feed: OkexPriceFeedConfig,
+    #[serde(default)]
+    pub bitfinex_price_feed: BitfinexPriceFeedConfig,
+    #[serde(default)]
+    pub binance_price_feed: BinancePriceFeedConfig,
+    #[serde(default)]
+    pub kraken_price_feed: KrakenPriceFeedConfig,
+    #[serde(default)]
     pub bria: BriaClientConfig,
     #[serde(default)]
     pub quotes_server: QuotesServerWrapper,
@@ -42,6 +50,8 @@ pub struct EnvOverride {
     pub pg_con: String,
     pub okex_secret_key: String,
     pub okex_passphrase: String,
+    pub okex_master_secret_key: String,
+    pub okex_master_passphrase: String,
     pub galoy_phone_code: String,
     pub bria_profile_api_key: String,
 }
@@ -53,6 +63,8 @@ impl Config {
             galoy_phone_code,
             okex_passphrase,
             okex_secret_key,
+            okex_master_passphrase,
+            okex_master_secret_key,
             pg_con: stablesats_pg_con,
             bria_profile_api_key,
         }: EnvOverride,
@@ -66,6 +78,10 @@ impl Config {
         if let Some(okex) = config.exchanges.okex.as_mut() {
             okex.config.client.secret_key = okex_secret_key;
             okex.config.client.passphrase = okex_passphrase;
+            if let Some(master) = okex.config.master.as_mut() {
+                master.client.secret_key = okex_master_secret_key;
+                master.client.passphrase = okex_master_passphrase;
+            }
         };
 
         config.db.pg_con = stablesats_pg_con;
@@ -90,6 +106,12 @@ pub struct PriceServerWrapper {
     pub fees: FeeCalculatorConfig,
     #[serde(default)]
     pub price_cache: ExchangePriceCacheConfig,
+    #[serde(default)]
+    pub mixer: PriceMixerConfig,
+    #[serde(default)]
+    pub history: PriceHistoryConfig,
+    #[serde(default)]
+    pub fx: FxConfig,
 }
 impl Default for PriceServerWrapper {
     fn default() -> Self {
@@ -99,6 +121,9 @@ impl Default for PriceServerWrapper {
             health: PriceServerHealthCheckConfig::default(),
             fees: FeeCalculatorConfig::default(),
             price_cache: ExchangePriceCacheConfig::default(),
+            mixer: PriceMixerConfig::default(),
+            history: PriceHistoryConfig::default(),
+            fx: FxConfig::default(),
         }
     }
 }
@@ -112,9 +137,11 @@ pub struct QuotesServerWrapper {
     #[serde(default)]
     pub server: QuotesServerConfig,
     #[serde(default)]
-    pub fees: QuotesFeeCalculatorConfig,
+    pub fees: FeeCalculatorConfig,
+    #[serde(default)]
+    pub price_cache: ExchangePriceCacheConfig,
     #[serde(default)]
-    pub price_cache: QuotesExchangePriceCacheConfig,
+    pub mixer: PriceMixerConfig,
     #[serde(default)]
     pub config: QuotesConfig,
 }
@@ -1,11 +1,17 @@
 use anyhow::Context;
-use axum::{http::StatusCode, routing::get, Router};
+use axum::{http::StatusCode, routing::get, Json, Router};
 use futures::SinkExt;
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+};
 use tokio::sync::RwLock;
 use tracing::{instrument, trace, warn};
 
-use shared::health::HealthChecker;
+use shared::health::{HealthChecker, HealthDetails, HealthDetailsRequester};
+
+type HealthResponse = (StatusCode, Json<BTreeMap<&'static str, HealthDetails>>);
 
 async fn health_check(
     checkers: Arc<HashMap<&'static str, HealthChecker>>,
@@ -41,6 +47,28 @@ async fn health_check(
     StatusCode::OK
 }
 
+/// Attaches the details each component reports to the health check status
+async fn with_details(
+    status: StatusCode,
+    requesters: Arc<HashMap<&'static str, HealthDetailsRequester>>,
+) -> HealthResponse {
+    let mut details = BTreeMap::new();
+    for (name, requester) in requesters.iter() {
+        let (snd, recv) = futures::channel::oneshot::channel();
+        if let Err(e) = requester.clone().send(snd).await {
+            warn!("Couldn't request '{name}' health details: {e}");
+            continue;
+        }
+        match tokio::time::timeout(std::time::Duration::from_millis(500), recv).await {
+            Ok(Ok(component)) => {
+                details.insert(*name, component);
+            }
+            _ => warn!("'{name}' health details unavailable"),
+        }
+    }
+    (status, Json(details))
+}
+
 #[instrument(name = "health.health_check_error", skip_all, fields(component_name, error = true, error.level, error.message, n_errors))]
 async fn health_check_error(
     name: &str,
@@ -68,32 +96,47 @@ async fn health_check_error(
     StatusCode::SERVICE_UNAVAILABLE
 }
 
-pub async fn run(checkers: HashMap<&'static str, HealthChecker>) -> anyhow::Result<()> {
+pub async fn run(
+    checkers: HashMap<&'static str, HealthChecker>,
+    details: HashMap<&'static str, HealthDetailsRequester>,
+) -> anyhow::Result<()> {
     let checkers = Arc::new(checkers);
+    let details = Arc::new(details);
     let app = Router::new()
         .route(
             "/health/live",
             get({
                 let checkers = checkers.clone();
+                let details = details.clone();
                 let n_errors = Arc::new(tokio::sync::RwLock::new(0));
-                move || health_check(Arc::clone(&checkers), Arc::clone(&n_errors))
+                move || {
+                    let status = health_check(Arc::clone(&checkers), Arc::clone(&n_errors));
+                    let details = Arc::clone(&details);
+                    async move { with_details(status.await, details).await }
+                }
             }),
         )
         .route(
             "/health/startup",
             get({
                 let checkers = checkers.clone();
-                move || health_check(Arc::clone(&checkers), Arc::new(RwLock::new(0)))
+                let details = details.clone();
+                move || {
+                    let status = health_check(Arc::clone(&checkers), Arc::new(RwLock::new(0)));
+                    let details = Arc::clone(&details);
+                    async move { with_details(status.await, details).await }
+                }
             }),
         )
         .route(
             "/health/ready",
             get({
                 let checkers = checkers.clone();
+                let details = details.clone();
                 let ever_ready = Arc::new(RwLock::new(false));
                 || async move {
                     let ever_ready = Arc::clone(&ever_ready);
-                    if *ever_ready.read().await {
+                    let status = if *ever_ready.read().await {
                         StatusCode::OK
                     } else {
                         let ret =
@@ -102,7 +145,8 @@ pub async fn run(checkers: HashMap<&'static str, HealthChecker>) -> anyhow::Resu
                             *ever_ready.write().await = true;
                         }
                         ret
-                    }
+                    };
+                    with_details(status, Arc::clone(&details)).await
                 }
             }),
         );
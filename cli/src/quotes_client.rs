@@ -67,6 +67,7 @@ impl QuotesClient {
                         proto::get_quote_to_sell_usd_request::QuoteFor::AmountToBuyInSats(amount),
                     ),
                     immediate_execution,
+                    ..Default::default()
                 });
                 let response = client.get_quote_to_sell_usd(request).await?;
                 output_json(response)?;
@@ -77,6 +78,7 @@ impl QuotesClient {
                         proto::get_quote_to_buy_usd_request::QuoteFor::AmountToSellInSats(amount),
                     ),
                     immediate_execution,
+                    ..Default::default()
                 });
                 let response = client.get_quote_to_buy_usd(request).await?;
                 output_json(response)?;
@@ -87,6 +89,7 @@ impl QuotesClient {
                         proto::get_quote_to_buy_usd_request::QuoteFor::AmountToBuyInCents(amount),
                     ),
                     immediate_execution,
+                    ..Default::default()
                 });
                 let response = client.get_quote_to_buy_usd(request).await?;
                 output_json(response)?;
@@ -97,6 +100,7 @@ impl QuotesClient {
                         proto::get_quote_to_sell_usd_request::QuoteFor::AmountToSellInCents(amount),
                     ),
                     immediate_execution,
+                    ..Default::default()
                 });
                 let response = client.get_quote_to_sell_usd(request).await?;
                 output_json(response)?;
@@ -109,7 +113,10 @@ impl QuotesClient {
     pub async fn accept_quote(&self, quote_id: String) -> anyhow::Result<()> {
         let mut client = self.connect().await?;
 
-        let request = tonic::Request::new(proto::AcceptQuoteRequest { quote_id });
+        let request = tonic::Request::new(proto::AcceptQuoteRequest {
+            quote_id,
+            ..Default::default()
+        });
         let _ = client.accept_quote(request).await?.into_inner();
         println!("Quote accepted!");
 
@@ -1,6 +1,7 @@
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::KeyValue;
 use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
 use opentelemetry_sdk::trace::{Sampler, TracerProvider};
 use opentelemetry_sdk::Resource;
 use serde::{Deserialize, Serialize};
@@ -28,19 +29,30 @@ pub fn init_tracer(config: TracingConfig) -> anyhow::Result<()> {
     let service_name = config.service_name;
     println!("Sending traces to {tracing_endpoint}");
 
+    let resource = Resource::new(vec![KeyValue::new("service.name", service_name.clone())]);
+
     let exporter = opentelemetry_otlp::SpanExporter::builder()
         .with_tonic()
-        .with_endpoint(tracing_endpoint)
+        .with_endpoint(tracing_endpoint.clone())
         .build()?;
 
     let provider = TracerProvider::builder()
         .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
         .with_sampler(Sampler::AlwaysOn)
-        .with_resource(Resource::new(vec![KeyValue::new(
-            "service.name",
-            service_name.clone(),
-        )]))
+        .with_resource(resource.clone())
+        .build();
+
+    let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+        .with_tonic()
+        .with_endpoint(tracing_endpoint)
+        .build()?;
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(
+            PeriodicReader::builder(metric_exporter, opentelemetry_sdk::runtime::Tokio).build(),
+        )
+        .with_resource(resource)
         .build();
+    opentelemetry::global::set_meter_provider(meter_provider);
     let tracer = provider.tracer(service_name);
 
     let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
@@ -7,6 +7,8 @@ use tracing::instrument;
 use galoy_client::*;
 use shared::{health::HealthCheckTrigger, payload::PriceStreamPayload, pubsub::memory};
 
+use std::sync::Arc;
+
 use crate::{config::*, error::*, okex::*};
 
 pub struct HedgingApp {
@@ -63,7 +65,13 @@ impl HedgingApp {
             .await?;
 
         let _ = Self::spawn_global_liability_listener(pool.clone(), ledger).await;
-        Self::spawn_health_checker(health_check_trigger, health_cfg, price_receiver).await;
+        Self::spawn_health_checker(
+            health_check_trigger,
+            health_cfg,
+            price_receiver,
+            okex_engine,
+        )
+        .await;
         let app = HedgingApp {
             _job_runner_handle: job_runner_handle,
         };
@@ -74,6 +82,7 @@ impl HedgingApp {
         mut health_check_trigger: HealthCheckTrigger,
         health_cfg: HedgingAppHealthConfig,
         price_sub: memory::Subscriber<PriceStreamPayload>,
+        okex_engine: Arc<OkexEngine>,
     ) {
         while let Some(check) = health_check_trigger.next().await {
             match price_sub
@@ -83,9 +92,14 @@ impl HedgingApp {
                 Err(e) => {
                     let _ = check.send(Err(e));
                 }
-                _ => {
-                    let _ = check.send(Ok(()));
-                }
+                _ => match okex_engine.check_rate_limit_health() {
+                    Err(e) => {
+                        let _ = check.send(Err(e.to_string()));
+                    }
+                    _ => {
+                        let _ = check.send(Ok(()));
+                    }
+                },
             }
         }
     }
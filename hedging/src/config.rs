@@ -7,8 +7,15 @@ use crate::okex::OkexConfig;
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ExchangesConfig {
     pub okex: Option<ExchangeConfig<OkexConfig>>,
+    pub bitfinex: Option<ExchangeConfig<PriceSourceConfig>>,
+    pub binance: Option<ExchangeConfig<PriceSourceConfig>>,
+    pub kraken: Option<ExchangeConfig<PriceSourceConfig>>,
 }
 
+/// Exchanges that are only used as price sources have nothing to configure beyond their weight
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceSourceConfig {}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ExchangeConfig<T: DeserializeOwned + Serialize + Default> {
     pub weight: Decimal,
@@ -0,0 +1,37 @@
+use rust_decimal::Decimal;
+
+use crate::error::HedgingError;
+
+/// Collateral and leverage bound the quotes server sizes new quotes against
+#[derive(Debug, Clone)]
+pub struct OkexHedgingCapacitySnapshot {
+    pub trading_btc_total_balance: Decimal,
+    pub funding_btc_total_balance: Decimal,
+    pub usd_cents_per_btc: Decimal,
+    pub high_bound_ratio_leverage: Decimal,
+}
+
+pub async fn record_hedging_capacity(
+    pool: &sqlx::PgPool,
+    snapshot: OkexHedgingCapacitySnapshot,
+) -> Result<(), HedgingError> {
+    sqlx::query!(
+        r#"INSERT INTO okex_hedging_capacity (
+             trading_btc_total_balance, funding_btc_total_balance,
+             usd_cents_per_btc, high_bound_ratio_leverage
+           ) VALUES ($1, $2, $3, $4)
+           ON CONFLICT (id) DO UPDATE SET
+             trading_btc_total_balance = EXCLUDED.trading_btc_total_balance,
+             funding_btc_total_balance = EXCLUDED.funding_btc_total_balance,
+             usd_cents_per_btc = EXCLUDED.usd_cents_per_btc,
+             high_bound_ratio_leverage = EXCLUDED.high_bound_ratio_leverage,
+             updated_at = NOW()"#,
+        snapshot.trading_btc_total_balance,
+        snapshot.funding_btc_total_balance,
+        snapshot.usd_cents_per_btc,
+        snapshot.high_bound_ratio_leverage
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use okex_client::OkexClientConfig;
+use okex_client::{OkexClientConfig, OkexMasterClientConfig};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
@@ -9,6 +9,9 @@ use std::time::Duration;
 pub struct OkexConfig {
     #[serde(default)]
     pub client: OkexClientConfig,
+    /// Master account owning the sub-account configured in `client`
+    #[serde(default)]
+    pub master: Option<OkexMasterClientConfig>,
     #[serde_as(as = "serde_with::DurationSeconds<u64>")]
     #[serde(default = "default_okex_poll_frequency")]
     pub poll_frequency: Duration,
@@ -87,6 +90,11 @@ pub struct OkexFundingConfig {
     #[serde_as(as = "serde_with::DurationSeconds<i64>")]
     #[serde(default = "default_deposit_lost_timeout_seconds")]
     pub deposit_lost_timeout_seconds: chrono::Duration,
+
+    /// Mark prices older than this fall back to the okex ticker endpoint
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_mark_price_stale_after")]
+    pub mark_price_stale_after: chrono::Duration,
 }
 impl Default for OkexFundingConfig {
     fn default() -> Self {
@@ -102,6 +110,8 @@ impl Default for OkexFundingConfig {
             high_bound_buffer_percentage: default_high_bound_buffer_percentage(),
 
             deposit_lost_timeout_seconds: default_deposit_lost_timeout_seconds(),
+
+            mark_price_stale_after: default_mark_price_stale_after(),
         }
     }
 }
@@ -132,3 +142,6 @@ fn default_high_bound_buffer_percentage() -> Decimal {
 fn default_deposit_lost_timeout_seconds() -> chrono::Duration {
     chrono::Duration::try_seconds(3600).expect("should always be able to create a timeout duration")
 }
+fn default_mark_price_stale_after() -> chrono::Duration {
+    chrono::Duration::try_seconds(30).expect("should always be able to create a stale duration")
+}
@@ -5,10 +5,13 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 use std::sync::Arc;
 
 use ledger::Ledger;
-use okex_client::OkexClient;
+use okex_client::{OkexClient, OkexMasterClient};
 use shared::{payload::*, pubsub::memory};
 
-use super::{config::*, funding_adjustment::*, hedge_adjustment::*, job, orders::*, transfers::*};
+use super::{
+    capacity::*, config::*, funding_adjustment::*, hedge_adjustment::*, job, mark_price::*,
+    orders::*, transfers::*,
+};
 use crate::error::HedgingError;
 
 pub struct OkexEngine {
@@ -17,9 +20,11 @@ pub struct OkexEngine {
     orders: OkexOrders,
     transfers: OkexTransfers,
     okex_client: OkexClient,
+    okex_master_client: Option<OkexMasterClient>,
     ledger: Ledger,
     funding_adjustment: FundingAdjustment,
     hedging_adjustment: HedgingAdjustment,
+    mark_price: OkexMarkPrice,
 }
 
 impl OkexEngine {
@@ -30,6 +35,10 @@ impl OkexEngine {
         price_receiver: memory::Subscriber<PriceStreamPayload>,
     ) -> Result<Arc<Self>, HedgingError> {
         let okex_client = OkexClient::new(config.client.clone()).await?;
+        let okex_master_client = match config.master.as_ref() {
+            Some(master) => Some(OkexMasterClient::new(master.clone()).await?),
+            None => None,
+        };
         let orders = OkexOrders::new(pool.clone()).await?;
         let transfers = OkexTransfers::new(pool.clone()).await?;
         okex_client
@@ -38,15 +47,18 @@ impl OkexEngine {
         let funding_adjustment =
             FundingAdjustment::new(config.funding.clone(), config.hedging.clone());
         let hedging_adjustment = HedgingAdjustment::new(config.hedging.clone());
+        let mark_price = OkexMarkPrice::new(config.funding.mark_price_stale_after);
         let ret = Arc::new(Self {
             config,
             pool,
             okex_client,
+            okex_master_client,
             orders,
             transfers,
             ledger,
             funding_adjustment,
             hedging_adjustment,
+            mark_price,
         });
 
         Arc::clone(&ret)
@@ -62,12 +74,22 @@ impl OkexEngine {
         Ok(ret)
     }
 
+    pub fn check_rate_limit_health(&self) -> Result<(), HedgingError> {
+        self.okex_client.check_rate_limit_health()?;
+        if let Some(master) = self.okex_master_client.as_ref() {
+            master.check_rate_limit_health()?;
+        }
+        Ok(())
+    }
+
     pub fn add_context_to_job_registry(&self, runner: &mut sqlxmq::JobRegistry) {
         runner.set_context(self.okex_client.clone());
+        runner.set_context(self.okex_master_client.clone());
         runner.set_context(self.orders.clone());
         runner.set_context(self.transfers.clone());
         runner.set_context(job::OkexPollDelay(self.config.poll_frequency));
         runner.set_context(self.funding_adjustment.clone());
+        runner.set_context(self.mark_price.clone());
         runner.set_context(self.hedging_adjustment.clone());
         runner.set_context(self.config.funding.clone());
         runner.set_context(self.ledger.clone());
@@ -86,6 +108,11 @@ impl OkexEngine {
     ) -> Result<(), HedgingError> {
         tokio::spawn(async move {
             while let Some(msg) = tick_recv.next().await {
+                if let PriceStreamPayload::OkexBtcUsdSwapMarkPricePayload(ref mark_price) =
+                    msg.payload
+                {
+                    self.mark_price.update(mark_price).await;
+                }
                 if let PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(_) = msg.payload {
                     let correlation_id = msg.meta.correlation_id;
                     let span = info_span!(
@@ -259,13 +286,30 @@ impl OkexEngine {
             .usd_liability_balances()
             .await?
             .okex_allocation;
-        let last_price_in_usd_cents = self
-            .okex_client
-            .get_last_price_in_usd_cents()
-            .await?
-            .usd_cents;
+        let last_price_in_usd_cents = match self.mark_price.usd_cents_per_btc().await {
+            Some(mark_price) => mark_price,
+            None => {
+                self.okex_client
+                    .get_last_price_in_usd_cents()
+                    .await?
+                    .usd_cents
+            }
+        };
         let trading_available_balance = self.okex_client.trading_account_balance().await?;
         let funding_available_balance = self.okex_client.funding_account_balance().await?;
+        if let Err(err) = record_hedging_capacity(
+            &self.pool,
+            OkexHedgingCapacitySnapshot {
+                trading_btc_total_balance: trading_available_balance.total_amt_in_btc,
+                funding_btc_total_balance: funding_available_balance.total_amt_in_btc,
+                usd_cents_per_btc: last_price_in_usd_cents,
+                high_bound_ratio_leverage: self.config.funding.high_bound_ratio_leverage,
+            },
+        )
+        .await
+        {
+            tracing::warn!(error = %err, "hedging.okex.record_hedging_capacity_failed");
+        }
 
         let action = self.funding_adjustment.determine_action(
             target_liability_in_cents,
@@ -14,6 +14,8 @@ pub enum OkexFundingAdjustment {
     TransferFundingToTrading(Decimal),
     OnchainDeposit(Decimal),
     OnchainWithdraw(Decimal),
+    TransferMasterToSubAccount(Decimal),
+    TransferSubAccountToMaster(Decimal),
 }
 impl std::fmt::Display for OkexFundingAdjustment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -31,6 +33,12 @@ impl std::fmt::Display for OkexFundingAdjustment {
             OkexFundingAdjustment::OnchainWithdraw(amount_in_btc) => {
                 write!(f, "OnchainWithdraw({amount_in_btc})")
             }
+            OkexFundingAdjustment::TransferMasterToSubAccount(amount_in_btc) => {
+                write!(f, "TransferMasterToSubAccount({amount_in_btc})")
+            }
+            OkexFundingAdjustment::TransferSubAccountToMaster(amount_in_btc) => {
+                write!(f, "TransferSubAccountToMaster({amount_in_btc})")
+            }
         }
     }
 }
@@ -46,6 +54,8 @@ impl OkexFundingAdjustment {
             Self::TransferFundingToTrading(_) => "transfer-funding-to-trading",
             Self::OnchainDeposit(_) => "deposit",
             Self::OnchainWithdraw(_) => "withdraw",
+            Self::TransferMasterToSubAccount(_) => "transfer-master-to-sub",
+            Self::TransferSubAccountToMaster(_) => "transfer-sub-to-master",
         }
     }
 
@@ -54,7 +64,9 @@ impl OkexFundingAdjustment {
             Self::TransferTradingToFunding(size)
             | Self::TransferFundingToTrading(size)
             | Self::OnchainDeposit(size)
-            | Self::OnchainWithdraw(size) => Some(size),
+            | Self::OnchainWithdraw(size)
+            | Self::TransferMasterToSubAccount(size)
+            | Self::TransferSubAccountToMaster(size) => Some(size),
             _ => None,
         }
     }
@@ -62,6 +74,22 @@ impl OkexFundingAdjustment {
     pub fn unit(&self) -> &'static str {
         "btc"
     }
+
+    /// When a master account is configured the sub-account is topped up from
+    /// (and swept into) the master funding account instead of going onchain.
+    /// Deposits still go onchain if the master cannot cover them.
+    /// A top-up lands in the sub-account's funding account, see
+    /// [`FundingAdjustment::trading_share_of_top_up`] for the part that is
+    /// moved on to trading.
+    pub fn via_master_account(self, master_free_balance_in_btc: Decimal) -> Self {
+        match self {
+            Self::OnchainDeposit(amount) if amount <= master_free_balance_in_btc => {
+                Self::TransferMasterToSubAccount(amount)
+            }
+            Self::OnchainWithdraw(amount) => Self::TransferSubAccountToMaster(amount),
+            action => action,
+        }
+    }
 }
 
 fn round_contract_in_cents(amount_in_cents: Decimal) -> Decimal {
@@ -93,6 +121,19 @@ impl FundingAdjustment {
         }
     }
 
+    /// Part of a top-up landed in the funding account that belongs in trading,
+    /// the rest refills the funding account to its minimum balance
+    pub fn trading_share_of_top_up(
+        &self,
+        funding_btc_total_balance: Decimal,
+        top_up_in_btc: Decimal,
+    ) -> Decimal {
+        std::cmp::max(
+            Decimal::ZERO,
+            funding_btc_total_balance + top_up_in_btc - self.config.minimum_funding_balance_btc,
+        )
+    }
+
     pub fn determine_action(
         &self,
         abs_liability_in_cents: SyntheticCentLiability,
@@ -870,6 +911,106 @@ mod tests {
         assert_eq!(external, expected_external);
     }
 
+    #[test]
+    fn deposit_via_master_account() {
+        let action = OkexFundingAdjustment::OnchainDeposit(dec!(0.5));
+        assert_eq!(
+            action.clone().via_master_account(dec!(1)),
+            OkexFundingAdjustment::TransferMasterToSubAccount(dec!(0.5))
+        );
+        assert_eq!(
+            action.clone().via_master_account(dec!(0.1)),
+            OkexFundingAdjustment::OnchainDeposit(dec!(0.5))
+        );
+    }
+
+    #[test]
+    fn master_top_up_moves_deposit_to_trading() {
+        let funding_adjustment = FundingAdjustment {
+            config: OkexFundingConfig::default(),
+            hedging_config: OkexHedgingConfig::default(),
+        };
+        let minimum_funding_balance_btc = funding_adjustment.config.minimum_funding_balance_btc;
+        let liability = SyntheticCentLiability::try_from(dec!(10_000)).unwrap();
+        let btc_price: Decimal = dec!(1);
+
+        let expected_total: Decimal =
+            round_btc(liability / funding_adjustment.config.high_safebound_ratio_leverage);
+        let (_, top_up) = split_deposit(Decimal::ZERO, expected_total, minimum_funding_balance_btc);
+        let action = funding_adjustment
+            .determine_action(
+                liability,
+                SyntheticCentExposure::from(dec!(0)),
+                Decimal::ZERO,
+                btc_price,
+                Decimal::ZERO,
+            )
+            .via_master_account(top_up);
+        assert_eq!(
+            action,
+            OkexFundingAdjustment::TransferMasterToSubAccount(top_up)
+        );
+        assert_eq!(
+            funding_adjustment.trading_share_of_top_up(Decimal::ZERO, top_up),
+            expected_total
+        );
+
+        let exposure = dec!(10_100);
+        let total_collateral: Decimal =
+            exposure / funding_adjustment.config.high_bound_ratio_leverage;
+        let expected_total: Decimal = round_btc(
+            exposure / funding_adjustment.config.high_safebound_ratio_leverage - total_collateral,
+        );
+        let (_, top_up) = split_deposit(Decimal::ZERO, expected_total, minimum_funding_balance_btc);
+        let action = funding_adjustment
+            .determine_action(
+                liability,
+                SyntheticCentExposure::from(-exposure),
+                total_collateral,
+                btc_price,
+                Decimal::ZERO,
+            )
+            .via_master_account(top_up);
+        assert_eq!(
+            action,
+            OkexFundingAdjustment::TransferMasterToSubAccount(top_up)
+        );
+        assert_eq!(
+            funding_adjustment.trading_share_of_top_up(Decimal::ZERO, top_up),
+            expected_total
+        );
+    }
+
+    #[test]
+    fn top_up_refilling_funding_stays_in_funding() {
+        let funding_adjustment = FundingAdjustment {
+            config: OkexFundingConfig::default(),
+            hedging_config: OkexHedgingConfig::default(),
+        };
+        let minimum_funding_balance_btc = funding_adjustment.config.minimum_funding_balance_btc;
+        let funding_btc_total_balance = minimum_funding_balance_btc / dec!(2);
+        let (_, top_up) = split_deposit(
+            funding_btc_total_balance,
+            Decimal::ZERO,
+            minimum_funding_balance_btc,
+        );
+        assert_eq!(
+            funding_adjustment.trading_share_of_top_up(funding_btc_total_balance, top_up),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn withdraw_via_master_account() {
+        let action = OkexFundingAdjustment::OnchainWithdraw(dec!(0.5));
+        assert_eq!(
+            action.via_master_account(Decimal::ZERO),
+            OkexFundingAdjustment::TransferSubAccountToMaster(dec!(0.5))
+        );
+        let action = OkexFundingAdjustment::TransferFundingToTrading(dec!(0.5));
+        assert_eq!(action.clone().via_master_account(dec!(1)), action);
+    }
+
     #[test]
     fn btc_round_down() {
         let expected_btc = dec!(100_000_000.0) / dec!(100_000_000);
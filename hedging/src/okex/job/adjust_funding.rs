@@ -13,7 +13,7 @@ const SATS_PER_BTC: Decimal = dec!(100_000_000);
 #[allow(clippy::too_many_arguments)]
 #[instrument(name = "hedging.okex.job.adjust_funding", skip_all, fields(correlation_id = %correlation_id,
         target_liability, current_position, last_price_in_usd_cents, funding_available_balance,
-        trading_available_balance, onchain_fees, action, client_transfer_id,
+        trading_available_balance, master_available_balance, onchain_fees, action, client_transfer_id,
         amount_with_jitter,
         transferred_funding, lag_ok), err)]
 pub(super) async fn execute(
@@ -21,9 +21,11 @@ pub(super) async fn execute(
     pool: &sqlx::PgPool,
     ledger: ledger::Ledger,
     okex: OkexClient,
+    okex_master: Option<OkexMasterClient>,
     okex_transfers: OkexTransfers,
     bria: &mut BriaClient,
     funding_adjustment: FundingAdjustment,
+    mark_price: OkexMarkPrice,
 ) -> Result<(), HedgingError> {
     let span = tracing::Span::current();
     if !crate::hack_user_trades_lag::lag_ok(pool).await? {
@@ -47,7 +49,10 @@ pub(super) async fn execute(
         tracing::field::display(current_position.usd_cents),
     );
 
-    let mut last_price_in_usd_cents = current_position.last_price_in_usd_cents;
+    let mut last_price_in_usd_cents = mark_price
+        .usd_cents_per_btc()
+        .await
+        .unwrap_or(current_position.last_price_in_usd_cents);
     if last_price_in_usd_cents.is_zero() {
         last_price_in_usd_cents = okex.get_last_price_in_usd_cents().await?.usd_cents;
     }
@@ -68,13 +73,26 @@ pub(super) async fn execute(
         "trading_available_balance",
         tracing::field::display(&trading_available_balance),
     );
-    let action = funding_adjustment.determine_action(
+    let mut action = funding_adjustment.determine_action(
         target_liability_in_cents,
         current_position.usd_cents.into(),
         trading_available_balance.total_amt_in_btc,
         last_price_in_usd_cents,
         funding_available_balance.total_amt_in_btc,
     );
+    if let Some(master) = okex_master.as_ref() {
+        if matches!(
+            action,
+            OkexFundingAdjustment::OnchainDeposit(_) | OkexFundingAdjustment::OnchainWithdraw(_)
+        ) {
+            let master_available_balance = master.funding_account_balance().await?;
+            span.record(
+                "master_available_balance",
+                tracing::field::display(&master_available_balance),
+            );
+            action = action.via_master_account(master_available_balance.free_amt_in_btc);
+        }
+    }
     span.record("action", tracing::field::display(&action));
 
     let fees = okex.get_onchain_fees().await?;
@@ -197,6 +215,71 @@ pub(super) async fn execute(
                             .await?;
                     }
                 }
+                OkexFundingAdjustment::TransferMasterToSubAccount(amount) => {
+                    if let Some(master) = okex_master.as_ref() {
+                        let reservation = TransferReservation {
+                            shared: &shared,
+                            action_size: Some(amount),
+                            fee: Decimal::ZERO,
+                            transfer_from: "master".to_string(),
+                            transfer_to: master.sub_account_name().to_string(),
+                        };
+                        if let Some(client_id) =
+                            okex_transfers.reserve_transfer_slot(reservation).await?
+                        {
+                            span.record(
+                                "client_transfer_id",
+                                tracing::field::display(String::from(client_id.clone())),
+                            );
+
+                            let _ = master
+                                .transfer_master_to_sub_account(client_id.clone(), amount)
+                                .await?;
+                            let state = master
+                                .transfer_state_by_client_id(
+                                    client_id,
+                                    OkexTransferType::MasterToSubAccount,
+                                )
+                                .await?;
+                            let topped_up = state.state == "success";
+                            okex_transfers.update_transfer(state).await?;
+                            if topped_up {
+                                transfer_top_up_to_trading(
+                                    &okex,
+                                    &okex_transfers,
+                                    &funding_adjustment,
+                                    &shared,
+                                    funding_available_balance.total_amt_in_btc,
+                                    amount,
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                }
+                OkexFundingAdjustment::TransferSubAccountToMaster(amount) => {
+                    if let Some(master) = okex_master.as_ref() {
+                        let reservation = TransferReservation {
+                            shared: &shared,
+                            action_size: Some(amount),
+                            fee: Decimal::ZERO,
+                            transfer_from: master.sub_account_name().to_string(),
+                            transfer_to: "master".to_string(),
+                        };
+                        if let Some(client_id) =
+                            okex_transfers.reserve_transfer_slot(reservation).await?
+                        {
+                            span.record(
+                                "client_transfer_id",
+                                tracing::field::display(String::from(client_id.clone())),
+                            );
+
+                            let _ = master
+                                .transfer_sub_account_to_master(client_id, amount)
+                                .await?;
+                        }
+                    }
+                }
                 _ => unreachable!(),
             }
             span.record("transferred_funding", tracing::field::display(true));
@@ -204,3 +287,37 @@ pub(super) async fn execute(
     };
     Ok(())
 }
+
+/// A top-up lands in the sub-account's funding account, moving it on to trading
+/// right away keeps the next pass from seeing it as excess funding
+async fn transfer_top_up_to_trading(
+    okex: &OkexClient,
+    okex_transfers: &OkexTransfers,
+    funding_adjustment: &FundingAdjustment,
+    shared: &TransferReservationSharedData,
+    funding_btc_total_balance: Decimal,
+    top_up_in_btc: Decimal,
+) -> Result<(), HedgingError> {
+    let amount =
+        funding_adjustment.trading_share_of_top_up(funding_btc_total_balance, top_up_in_btc);
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let shared = TransferReservationSharedData {
+        action_type: OkexFundingAdjustment::TransferFundingToTrading(amount)
+            .action_type()
+            .to_string(),
+        ..shared.clone()
+    };
+    let reservation = TransferReservation {
+        shared: &shared,
+        action_size: Some(amount),
+        fee: Decimal::ZERO,
+        transfer_from: "funding".to_string(),
+        transfer_to: "trading".to_string(),
+    };
+    if let Some(client_id) = okex_transfers.reserve_transfer_slot(reservation).await? {
+        let _ = okex.transfer_funding_to_trading(client_id, amount).await?;
+    }
+    Ok(())
+}
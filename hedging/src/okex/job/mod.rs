@@ -11,7 +11,7 @@ use uuid::{uuid, Uuid};
 
 use std::collections::HashMap;
 
-use okex_client::OkexClient;
+use okex_client::{OkexClient, OkexMasterClient};
 use shared::{pubsub::CorrelationId, sqlxmq::JobExecutor};
 
 use crate::{error::*, okex::*};
@@ -78,10 +78,12 @@ pub async fn spawn_adjust_hedge<'a>(
 }
 
 #[job(name = "poll_okex")]
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn poll_okex(
     mut current_job: CurrentJob,
     OkexPollDelay(delay): OkexPollDelay,
     okex: OkexClient,
+    okex_master: Option<OkexMasterClient>,
     okex_orders: OkexOrders,
     okex_transfers: OkexTransfers,
     funding_config: OkexFundingConfig,
@@ -97,6 +99,7 @@ pub(super) async fn poll_okex(
                 okex_orders,
                 okex_transfers,
                 okex,
+                okex_master,
                 funding_config,
                 &ledger,
             )
@@ -171,13 +174,16 @@ pub async fn spawn_adjust_funding<'a>(
 }
 
 #[job(name = "adjust_funding")]
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn adjust_funding(
     mut current_job: CurrentJob,
     ledger: ledger::Ledger,
     okex: OkexClient,
+    okex_master: Option<OkexMasterClient>,
     okex_transfers: OkexTransfers,
     mut bria: BriaClient,
     funding_adjustment: FundingAdjustment,
+    mark_price: OkexMarkPrice,
 ) -> Result<(), HedgingError> {
     let pool = current_job.pool().clone();
     JobExecutor::builder(&mut current_job)
@@ -190,9 +196,11 @@ pub(super) async fn adjust_funding(
                 &pool,
                 ledger,
                 okex,
+                okex_master,
                 okex_transfers,
                 &mut bria,
                 funding_adjustment,
+                mark_price,
             )
             .await?;
             Ok::<_, HedgingError>(data)
@@ -1,6 +1,6 @@
 use tracing::instrument;
 
-use okex_client::{OkexClient, OkexClientError, PositionSize};
+use okex_client::{OkexClient, OkexClientError, OkexMasterClient, OkexTransferType, PositionSize};
 use shared::payload::OKEX_EXCHANGE_ID;
 
 use crate::{error::HedgingError, okex::*};
@@ -11,6 +11,7 @@ pub async fn execute(
     okex_orders: OkexOrders,
     okex_transfers: OkexTransfers,
     okex: OkexClient,
+    okex_master: Option<OkexMasterClient>,
     funding_config: OkexFundingConfig,
     ledger: &ledger::Ledger,
 ) -> Result<(), HedgingError> {
@@ -64,6 +65,30 @@ pub async fn execute(
         }
     }
 
+    if let Some(master) = okex_master.as_ref() {
+        for (id, action) in okex_transfers.get_pending_master_transfers().await? {
+            let transfer_type = if action == "transfer-master-to-sub" {
+                OkexTransferType::MasterToSubAccount
+            } else {
+                OkexTransferType::SubAccountToMaster
+            };
+            match master
+                .transfer_state_by_client_id(id.clone(), transfer_type)
+                .await
+            {
+                Ok(details) => {
+                    okex_transfers.update_transfer(details).await?;
+                }
+                Err(OkexClientError::ParameterClientIdError)
+                | Err(OkexClientError::ParameterClientIdNotFound) => {
+                    okex_transfers.mark_as_lost(id).await?;
+                    execute_transfer_sweep = true;
+                }
+                Err(res) => return Err(res.into()),
+            }
+        }
+    }
+
     for (id, address, amount, created_at) in okex_transfers.get_pending_deposits().await? {
         match okex.fetch_deposit(address, amount).await {
             Ok(details) => {
@@ -0,0 +1,81 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::sync::RwLock;
+
+use std::sync::Arc;
+
+use shared::{payload::ReferencePricePayload, time::TimeStamp};
+
+const SATS_PER_BTC: Decimal = dec!(100_000_000);
+
+/// Latest okex mark price as published on the price stream. Used instead of
+/// polling the ticker endpoint when determining funding adjustments.
+#[derive(Clone)]
+pub struct OkexMarkPrice {
+    stale_after: chrono::Duration,
+    latest: Arc<RwLock<Option<(Decimal, TimeStamp)>>>,
+}
+
+impl OkexMarkPrice {
+    pub fn new(stale_after: chrono::Duration) -> Self {
+        Self {
+            stale_after,
+            latest: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub async fn update(&self, payload: &ReferencePricePayload) {
+        let usd_cents_per_btc = payload.price.numerator_amount() * SATS_PER_BTC;
+        let mut latest = self.latest.write().await;
+        if latest
+            .map(|(_, timestamp)| timestamp <= payload.timestamp)
+            .unwrap_or(true)
+        {
+            *latest = Some((usd_cents_per_btc, payload.timestamp));
+        }
+    }
+
+    pub async fn usd_cents_per_btc(&self) -> Option<Decimal> {
+        self.latest
+            .read()
+            .await
+            .filter(|(_, timestamp)| timestamp.duration_since() <= self.stale_after)
+            .map(|(price, _)| price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::payload::*;
+
+    use super::*;
+
+    fn payload(price: Decimal, timestamp: TimeStamp) -> ReferencePricePayload {
+        ReferencePricePayload {
+            timestamp,
+            exchange: ExchangeIdRaw::from(OKEX_EXCHANGE_ID),
+            instrument_id: InstrumentIdRaw::from("BTC-USD-SWAP"),
+            price: PriceRatioRaw::from_one_btc_in_usd_price(price),
+        }
+    }
+
+    #[tokio::test]
+    async fn mark_price_in_usd_cents() {
+        let mark_price = OkexMarkPrice::new(chrono::Duration::try_seconds(30).unwrap());
+        assert_eq!(mark_price.usd_cents_per_btc().await, None);
+
+        mark_price
+            .update(&payload(dec!(20_000), TimeStamp::now()))
+            .await;
+        assert_eq!(mark_price.usd_cents_per_btc().await, Some(dec!(2_000_000)));
+    }
+
+    #[tokio::test]
+    async fn stale_mark_price_is_ignored() {
+        let mark_price = OkexMarkPrice::new(chrono::Duration::try_seconds(30).unwrap());
+        mark_price
+            .update(&payload(dec!(20_000), TimeStamp::from(1_u64)))
+            .await;
+        assert_eq!(mark_price.usd_cents_per_btc().await, None);
+    }
+}
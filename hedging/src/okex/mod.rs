@@ -1,8 +1,10 @@
+mod capacity;
 mod config;
 mod engine;
 mod funding_adjustment;
 mod hedge_adjustment;
 pub mod job;
+mod mark_price;
 mod orders;
 mod transfers;
 
@@ -10,5 +12,6 @@ pub use config::*;
 pub use engine::*;
 pub use funding_adjustment::*;
 pub use hedge_adjustment::*;
+pub use mark_price::*;
 pub use orders::*;
 pub use transfers::*;
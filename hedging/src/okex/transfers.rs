@@ -7,6 +7,7 @@ use shared::pubsub::CorrelationId;
 
 use crate::error::HedgingError;
 
+#[derive(Clone)]
 pub struct TransferReservationSharedData {
     pub correlation_id: CorrelationId,
     pub action_type: String,
@@ -150,6 +151,19 @@ impl OkexTransfers {
             .collect())
     }
 
+    pub async fn get_pending_master_transfers(
+        &self,
+    ) -> Result<Vec<(ClientTransferId, String)>, HedgingError> {
+        let res =
+            sqlx::query!(r#"SELECT client_transfer_id, action FROM okex_transfers WHERE action IN ('transfer-master-to-sub', 'transfer-sub-to-master') AND state = 'pending'"#)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(res
+            .into_iter()
+            .map(|r| (ClientTransferId::from(r.client_transfer_id), r.action))
+            .collect())
+    }
+
     pub async fn update_transfer(&self, details: TransferState) -> Result<(), HedgingError> {
         sqlx::query!(
             r#"UPDATE okex_transfers SET lost = false, transfer_id = $1, state = $2 WHERE client_transfer_id = $3"#,
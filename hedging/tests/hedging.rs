@@ -25,6 +25,7 @@ fn okex_config() -> OkexConfig {
             passphrase,
             secret_key,
             simulated: true,
+            ..Default::default()
         },
         ..Default::default()
     }
@@ -0,0 +1,106 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![cfg_attr(feature = "fail-on-warnings", deny(clippy::all))]
+
+pub mod error;
+pub mod order_book;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use shared::{
+    health::{FeedHealthCheck, HealthCheckTrigger},
+    payload::*,
+    pubsub::*,
+};
+use tokio::time::timeout;
+use url::Url;
+
+pub use error::*;
+pub use order_book::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KrakenPriceFeedConfig {
+    #[serde(default = "default_url")]
+    pub url: Url,
+}
+
+impl Default for KrakenPriceFeedConfig {
+    fn default() -> Self {
+        Self { url: default_url() }
+    }
+}
+
+fn default_url() -> Url {
+    Url::parse(KRAKEN_WS_URL).expect("invalid kraken_ws_url")
+}
+
+pub async fn run(
+    price_stream_publisher: memory::Publisher<PriceStreamPayload>,
+    health_check_trigger: HealthCheckTrigger,
+    unhealthy_msg_interval: std::time::Duration,
+    config: KrakenPriceFeedConfig,
+) -> Result<(), PriceFeedError> {
+    let health = FeedHealthCheck::new(
+        "kraken order book",
+        chrono::Duration::from_std(unhealthy_msg_interval).expect("invalid unhealthy interval"),
+    );
+    health.respond_to(health_check_trigger);
+
+    loop {
+        if let Err(e) = order_book_subscription(
+            &price_stream_publisher,
+            &health,
+            unhealthy_msg_interval,
+            &config.url,
+        )
+        .await
+        {
+            tracing::warn!(error = %e, "kraken_price.order_book.resubscribe");
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(5_u64)).await;
+    }
+}
+
+/// A fresh snapshot is sent on every subscription so checksum mismatches and
+/// stalls are recovered from by resubscribing
+async fn order_book_subscription(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    health: &FeedHealthCheck,
+    unhealthy_msg_interval: std::time::Duration,
+    url: &Url,
+) -> Result<(), PriceFeedError> {
+    let mut stream = subscribe_btc_usd_order_book(url).await?;
+    let full_load = timeout(unhealthy_msg_interval, stream.next())
+        .await
+        .map_err(|_| PriceFeedError::StreamStalled)?
+        .ok_or(PriceFeedError::StreamEnded)?;
+    let mut cache = OrderBookCache::new(CompleteOrderBook::try_from(full_load)?);
+    publish_order_book(publisher, health, &cache).await?;
+
+    loop {
+        match timeout(unhealthy_msg_interval, stream.next()).await {
+            Ok(Some(book)) => {
+                cache.update_order_book(book)?;
+                publish_order_book(publisher, health, &cache).await?;
+            }
+            Ok(None) => return Err(PriceFeedError::StreamEnded),
+            Err(_) => return Err(PriceFeedError::StreamStalled),
+        }
+    }
+}
+
+async fn publish_order_book(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    health: &FeedHealthCheck,
+    cache: &OrderBookCache,
+) -> Result<(), PriceFeedError> {
+    if let Ok(order_book) = OrderBookPayload::try_from(cache.latest().clone()) {
+        health.record_update().await;
+        publisher
+            .throttle_publish(
+                "KRAKEN_ORDER_BOOK",
+                PriceStreamPayload::KrakenBtcUsdOrderBookPayload(order_book),
+            )
+            .await?;
+    }
+    Ok(())
+}
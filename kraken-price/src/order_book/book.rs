@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use shared::{payload::*, time::*};
+use std::collections::BTreeMap;
+
+use crate::PriceFeedError;
+
+pub(crate) const BOOK_DEPTH: usize = 25;
+const CHECKSUM_DEPTH_LIMIT: usize = 10;
+/// Precisions of the BTC/USD pair. The checksum is computed over values formatted with them.
+const PRICE_PRECISION: usize = 1;
+const QTY_PRECISION: usize = 8;
+const CENTS_PER_USD: Decimal = dec!(100);
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum BookAction {
+    Snapshot,
+    Update,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Clone)]
+pub struct PriceQuantity {
+    pub price: Decimal,
+    pub qty: Decimal,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BookChannelData {
+    pub symbol: String,
+    pub bids: Vec<PriceQuantity>,
+    pub asks: Vec<PriceQuantity>,
+    pub checksum: u32,
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// https://docs.kraken.com/api/docs/websocket-v2/book
+#[derive(Debug, Deserialize, Clone)]
+pub struct KrakenBook {
+    pub channel: String,
+    #[serde(rename = "type")]
+    pub action: BookAction,
+    pub data: Vec<BookChannelData>,
+}
+
+#[derive(Debug, PartialOrd, PartialEq, Eq, Ord, Clone)]
+pub struct OrderPrice(Decimal);
+impl From<Decimal> for OrderPrice {
+    fn from(d: Decimal) -> Self {
+        Self(d)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompleteOrderBook {
+    asks: BTreeMap<OrderPrice, Decimal>,
+    bids: BTreeMap<OrderPrice, Decimal>,
+    timestamp: TimeStamp,
+}
+
+impl TryFrom<KrakenBook> for CompleteOrderBook {
+    type Error = PriceFeedError;
+
+    fn try_from(book: KrakenBook) -> Result<Self, Self::Error> {
+        if book.action != BookAction::Snapshot {
+            return Err(PriceFeedError::InitialFullLoad);
+        }
+        let mut result = Self {
+            asks: BTreeMap::new(),
+            bids: BTreeMap::new(),
+            timestamp: TimeStamp::now(),
+        };
+        result.apply(book)?;
+        Ok(result)
+    }
+}
+
+impl CompleteOrderBook {
+    /// https://docs.kraken.com/api/docs/guides/spot-ws-book-v2
+    #[allow(clippy::result_large_err)]
+    fn apply(&mut self, book: KrakenBook) -> Result<(), PriceFeedError> {
+        if book.action == BookAction::Snapshot {
+            self.asks.clear();
+            self.bids.clear();
+        }
+        for data in book.data {
+            for level in data.asks {
+                update_level(&mut self.asks, level);
+            }
+            for level in data.bids {
+                update_level(&mut self.bids, level);
+            }
+            while self.asks.len() > BOOK_DEPTH {
+                self.asks.pop_last();
+            }
+            while self.bids.len() > BOOK_DEPTH {
+                self.bids.pop_first();
+            }
+            self.timestamp = data
+                .timestamp
+                .map(|ts| TimeStamp::from(ts.timestamp() as u64))
+                .unwrap_or_else(TimeStamp::now);
+            if self.calculate_checksum() != data.checksum {
+                return Err(PriceFeedError::CheckSumValidation);
+            }
+        }
+        Ok(())
+    }
+
+    fn calculate_checksum(&self) -> u32 {
+        let asks = self.asks.iter().take(CHECKSUM_DEPTH_LIMIT);
+        let bids = self.bids.iter().rev().take(CHECKSUM_DEPTH_LIMIT);
+        let crc = asks
+            .chain(bids)
+            .map(|(price, qty)| {
+                format!(
+                    "{}{}",
+                    checksum_digits(price.0, PRICE_PRECISION),
+                    checksum_digits(*qty, QTY_PRECISION)
+                )
+            })
+            .collect::<String>();
+
+        crc32fast::hash(crc.as_bytes())
+    }
+}
+
+fn update_level(side: &mut BTreeMap<OrderPrice, Decimal>, level: PriceQuantity) {
+    if level.qty == Decimal::ZERO {
+        side.remove(&OrderPrice::from(level.price));
+    } else {
+        side.insert(OrderPrice::from(level.price), level.qty);
+    }
+}
+
+/// Fixed precision representation without the decimal point and leading zeros
+fn checksum_digits(value: Decimal, precision: usize) -> String {
+    format!("{:.*}", precision, value)
+        .replace('.', "")
+        .trim_start_matches('0')
+        .to_string()
+}
+
+impl TryFrom<CompleteOrderBook> for OrderBookPayload {
+    type Error = PriceFeedError;
+
+    fn try_from(book: CompleteOrderBook) -> Result<Self, Self::Error> {
+        if book.asks.is_empty() || book.bids.is_empty() {
+            return Err(PriceFeedError::EmptyBookSide);
+        }
+        let mut asks_map = BTreeMap::new();
+        for (ask_price, ask_qty) in book.asks {
+            let price = PriceRatioRaw::from_one_btc_in_usd_price(ask_price.0).numerator_amount();
+            let _ = asks_map.insert(
+                PriceRaw::from(price),
+                VolumeInCentsRaw::from(ask_qty * ask_price.0 * CENTS_PER_USD),
+            );
+        }
+
+        let mut bids_map = BTreeMap::new();
+        for (bid_price, bid_qty) in book.bids {
+            let price = PriceRatioRaw::from_one_btc_in_usd_price(bid_price.0).numerator_amount();
+            let _ = bids_map.insert(
+                PriceRaw::from(price),
+                VolumeInCentsRaw::from(bid_qty * bid_price.0 * CENTS_PER_USD),
+            );
+        }
+
+        Ok(Self {
+            asks: asks_map,
+            bids: bids_map,
+            timestamp: book.timestamp,
+            exchange: ExchangeIdRaw::from(KRAKEN_EXCHANGE_ID),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct OrderBookCache {
+    current: CompleteOrderBook,
+}
+
+impl OrderBookCache {
+    pub fn new(book: CompleteOrderBook) -> Self {
+        Self { current: book }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn update_order_book(&mut self, book: KrakenBook) -> Result<(), PriceFeedError> {
+        self.current.apply(book)
+    }
+
+    pub fn latest(&self) -> &CompleteOrderBook {
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded_feed() -> Vec<KrakenBook> {
+        include_str!("../../tests/fixtures/book-feed.jsonl")
+            .lines()
+            .filter_map(|line| serde_json::from_str::<KrakenBook>(line).ok())
+            .collect()
+    }
+
+    #[test]
+    fn applies_updates_and_verifies_checksums() -> anyhow::Result<()> {
+        let mut feed = recorded_feed().into_iter();
+        let mut cache =
+            OrderBookCache::new(CompleteOrderBook::try_from(feed.next().expect("snapshot"))?);
+        let update = feed.next().expect("update");
+        assert!(feed.next().is_none());
+        cache.update_order_book(update)?;
+
+        assert_eq!(cache.latest().bids.len(), 24);
+        assert_eq!(cache.latest().timestamp, TimeStamp::from(1718000000));
+        let payload = OrderBookPayload::try_from(cache.latest().clone())?;
+        assert_eq!(payload.exchange, ExchangeIdRaw::from(KRAKEN_EXCHANGE_ID));
+        assert_eq!(
+            payload.bids.get(&PriceRaw::from(dec!(0.064))),
+            Some(&VolumeInCentsRaw::from(dec!(9600000)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn detects_checksum_mismatch() -> anyhow::Result<()> {
+        let mut feed = recorded_feed().into_iter();
+        let mut cache =
+            OrderBookCache::new(CompleteOrderBook::try_from(feed.next().expect("snapshot"))?);
+        let mut update = feed.next().expect("update");
+        update.data[0].bids[1].qty = dec!(1.4);
+        assert!(matches!(
+            cache.update_order_book(update),
+            Err(PriceFeedError::CheckSumValidation)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn formats_checksum_digits() {
+        assert_eq!(checksum_digits(dec!(64000), PRICE_PRECISION), "640000");
+        assert_eq!(checksum_digits(dec!(0.05), QTY_PRECISION), "5000000");
+    }
+}
@@ -0,0 +1,47 @@
+mod book;
+
+use futures::{SinkExt, Stream, StreamExt};
+use std::pin::Pin;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use url::Url;
+
+use super::error::*;
+pub use book::*;
+
+pub const KRAKEN_WS_URL: &str = "wss://ws.kraken.com/v2";
+pub const BTC_USD_SYMBOL: &str = "BTC/USD";
+pub(crate) const BOOK_CHANNEL: &str = "book";
+
+pub async fn subscribe_btc_usd_order_book(
+    url: &Url,
+) -> Result<Pin<Box<dyn Stream<Item = KrakenBook> + Send>>, PriceFeedError> {
+    let request = url.as_str().into_client_request()?;
+    let (ws_stream, _) = connect_async(request).await?;
+    let (mut sender, receiver) = ws_stream.split();
+
+    let subscribe_args = serde_json::json!({
+        "method": "subscribe",
+        "params": {
+            "channel": BOOK_CHANNEL,
+            "symbol": [BTC_USD_SYMBOL],
+            "depth": BOOK_DEPTH,
+            "snapshot": true
+        }
+    })
+    .to_string();
+    sender.send(Message::from(subscribe_args)).await?;
+
+    Ok(Box::pin(receiver.filter_map(|message| async move {
+        if let Ok(msg) = message {
+            if let Ok(msg_str) = msg.into_text() {
+                if let Ok(book) = serde_json::from_str::<KrakenBook>(&msg_str) {
+                    if book.channel == BOOK_CHANNEL {
+                        return Some(book);
+                    }
+                }
+            }
+        }
+        None
+    })))
+}
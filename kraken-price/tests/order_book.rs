@@ -0,0 +1,44 @@
+use futures::StreamExt;
+use kraken_price::*;
+
+use shared::{payload::*, pubsub::*};
+
+#[tokio::test]
+async fn subscribes_to_book_channel() -> anyhow::Result<()> {
+    let config = KrakenPriceFeedConfig::default();
+    let mut stream = subscribe_btc_usd_order_book(&config.url)
+        .await
+        .expect("subscribe to book channel");
+    let book = stream.next().await.expect("order book");
+
+    assert_eq!(book.action, BookAction::Snapshot);
+    assert_eq!(book.data[0].symbol, BTC_USD_SYMBOL);
+    assert_eq!(book.data[0].bids.len(), 25);
+    assert_eq!(book.data[0].asks.len(), 25);
+    Ok(())
+}
+
+#[tokio::test]
+async fn publishes_to_price_stream() -> anyhow::Result<()> {
+    let (book_send, mut book_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (_, recv) = futures::channel::mpsc::unbounded();
+
+    tokio::spawn(async move {
+        let _res = kraken_price::run(
+            book_send,
+            recv,
+            std::time::Duration::from_secs(20),
+            KrakenPriceFeedConfig::default(),
+        )
+        .await;
+    });
+
+    let recv = book_recv.next().await.expect("expected order book");
+    if let PriceStreamPayload::KrakenBtcUsdOrderBookPayload(book) = recv.payload {
+        assert!(!book.asks.is_empty() && !book.bids.is_empty());
+    } else {
+        panic!("expected kraken order book");
+    }
+    Ok(())
+}
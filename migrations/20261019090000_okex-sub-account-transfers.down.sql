@@ -0,0 +1,3 @@
+ALTER TABLE okex_transfers DROP CONSTRAINT okex_transfers_action_check;
+ALTER TABLE okex_transfers ADD CONSTRAINT okex_transfers_action_check
+  CHECK (action in ('transfer-trading-to-funding', 'transfer-funding-to-trading', 'deposit', 'withdraw'));
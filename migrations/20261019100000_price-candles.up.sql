@@ -0,0 +1,19 @@
+CREATE TABLE price_candles (
+    resolution VARCHAR NOT NULL CHECK (resolution IN ('1m', '1h', '1d')),
+    started_at TIMESTAMPTZ NOT NULL,
+    mid_open NUMERIC NOT NULL,
+    mid_high NUMERIC NOT NULL,
+    mid_low NUMERIC NOT NULL,
+    mid_close NUMERIC NOT NULL,
+    bid_open NUMERIC NOT NULL,
+    bid_high NUMERIC NOT NULL,
+    bid_low NUMERIC NOT NULL,
+    bid_close NUMERIC NOT NULL,
+    ask_open NUMERIC NOT NULL,
+    ask_high NUMERIC NOT NULL,
+    ask_low NUMERIC NOT NULL,
+    ask_close NUMERIC NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (resolution, started_at)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE stablesats_quotes DROP COLUMN accept_idempotency_key;
+ALTER TABLE stablesats_quotes DROP COLUMN idempotency_key;
@@ -0,0 +1,2 @@
+ALTER TABLE stablesats_quotes ADD COLUMN idempotency_key VARCHAR UNIQUE;
+ALTER TABLE stablesats_quotes ADD COLUMN accept_idempotency_key VARCHAR UNIQUE;
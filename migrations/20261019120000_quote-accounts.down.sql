@@ -0,0 +1,2 @@
+DROP INDEX stablesats_quotes_account_id_idx;
+ALTER TABLE stablesats_quotes DROP COLUMN account_id;
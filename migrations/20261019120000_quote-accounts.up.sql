@@ -0,0 +1,2 @@
+ALTER TABLE stablesats_quotes ADD COLUMN account_id VARCHAR;
+CREATE INDEX stablesats_quotes_account_id_idx ON stablesats_quotes (account_id, created_at);
@@ -0,0 +1 @@
+DROP TABLE okex_hedging_capacity;
@@ -0,0 +1,8 @@
+CREATE TABLE okex_hedging_capacity (
+  id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+  trading_btc_total_balance NUMERIC NOT NULL,
+  funding_btc_total_balance NUMERIC NOT NULL,
+  usd_cents_per_btc NUMERIC NOT NULL,
+  high_bound_ratio_leverage NUMERIC NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+DROP INDEX stablesats_quotes_state_idx;
+ALTER TABLE stablesats_quotes DROP COLUMN state;
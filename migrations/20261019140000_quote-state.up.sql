@@ -0,0 +1,10 @@
+ALTER TABLE stablesats_quotes ADD COLUMN state VARCHAR NOT NULL DEFAULT 'open';
+UPDATE stablesats_quotes q SET state = s.event_type
+  FROM (
+    SELECT DISTINCT ON (id) id, event_type
+    FROM stablesats_quote_events
+    WHERE event_type IN ('accepted', 'cancelled', 'expired')
+    ORDER BY id, sequence DESC
+  ) s
+  WHERE s.id = q.id;
+CREATE INDEX stablesats_quotes_state_idx ON stablesats_quotes (state, created_at);
@@ -30,12 +30,17 @@ pub enum OkexClientError {
     DecimalConversion(#[from] rust_decimal::Error),
     #[error("OkexClientError - MisconfiguredAccount: {0}")]
     MisconfiguredAccount(String),
+    #[error("OkexClientError - RateLimited: {code:?} - {msg:?}")]
+    RateLimited { msg: String, code: String },
+    #[error("OkexClientError - RateLimitSaturated: {endpoint} has {queued} queued requests")]
+    RateLimitSaturated { endpoint: String, queued: usize },
 }
 
 impl From<(String, String)> for OkexClientError {
     fn from((msg, code): (String, String)) -> Self {
         match code.as_str() {
             "50001" => OkexClientError::ServiceUnavailable { msg, code },
+            "50011" | "50061" => OkexClientError::RateLimited { msg, code },
             "51000" => OkexClientError::ParameterClientIdError,
             "51603" => OkexClientError::OrderDoesNotExist,
             "58129" => OkexClientError::ParameterClientIdNotFound,
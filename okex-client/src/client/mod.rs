@@ -1,12 +1,14 @@
 mod error;
 mod okex_response;
 mod primitives;
+mod rate_limit;
+mod sub_account;
 
 use chrono::{SecondsFormat, Utc};
 use data_encoding::BASE64;
 use reqwest::{
-    header::{HeaderMap, HeaderValue, CONTENT_TYPE},
-    Client as ReqwestClient,
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE, RETRY_AFTER},
+    Client as ReqwestClient, RequestBuilder, StatusCode,
 };
 use ring::hmac;
 use rust_decimal::Decimal;
@@ -21,15 +23,9 @@ pub use okex_response::OrderDetails;
 pub use okex_response::TransferStateData;
 use okex_response::*;
 pub use primitives::*;
-
-use governor::{
-    clock::DefaultClock, state::keyed::DefaultKeyedStateStore, Jitter, Quota, RateLimiter,
-};
-use std::num::NonZeroU32;
-
-lazy_static::lazy_static! {
-    static ref LIMITER: RateLimiter<&'static str, DefaultKeyedStateStore<&'static str>, DefaultClock>  = RateLimiter::keyed(Quota::per_second(NonZeroU32::new(1).unwrap()));
-}
+use rate_limit::OkexRateLimiter;
+pub use rate_limit::{EndpointQuota, EndpointSaturation, OkexRateLimitConfig};
+pub use sub_account::*;
 
 const TESTNET_BURNER_ADDRESS: &str = "tb1qfqh7ksqcrhjgq35clnf06l5d9s6tk2ke46ecrj";
 const OKEX_API_URL: &str = "https://www.okx.com";
@@ -48,33 +44,23 @@ pub struct OkexClientConfig {
     pub secret_key: String,
     #[serde(default)]
     pub simulated: bool,
+    #[serde(default)]
+    pub rate_limits: OkexRateLimitConfig,
 }
 
 #[derive(Clone)]
 pub struct OkexClient {
     client: ReqwestClient,
+    rate_limiter: OkexRateLimiter,
     config: OkexClientConfig,
 }
 
 impl OkexClient {
     pub async fn new(config: OkexClientConfig) -> Result<Self, OkexClientError> {
-        let client = Self {
-            client: ReqwestClient::builder().use_rustls_tls().build()?,
-            config,
-        };
+        let client = Self::build(config)?;
         let path = "/api/v5/account/config";
-        let config_url = Self::url_for_path(path);
-        let headers = client.get_request_headers(path)?;
-
-        let response = client
-            .rate_limit_client(path)
-            .await
-            .get(config_url)
-            .headers(headers)
-            .send()
-            .await?;
-        let config_data =
-            Self::extract_response_data::<OkexAccountConfigurationData>(response).await?;
+        let response = client.get(path).await?;
+        let config_data = Self::extract_response_data::<OkexAccountConfigurationData>(&response)?;
 
         if &config_data.pos_mode != "net_mode" {
             return Err(OkexClientError::MisconfiguredAccount(format!(
@@ -92,6 +78,14 @@ impl OkexClient {
         Ok(client)
     }
 
+    fn build(config: OkexClientConfig) -> Result<Self, OkexClientError> {
+        Ok(Self {
+            client: ReqwestClient::builder().use_rustls_tls().build()?,
+            rate_limiter: OkexRateLimiter::new(config.rate_limits.clone()),
+            config,
+        })
+    }
+
     pub async fn check_leverage(&self, expected_leverage: Decimal) -> Result<(), OkexClientError> {
         let leverage_info = self.leverage_info().await?;
 
@@ -110,27 +104,25 @@ impl OkexClient {
 
     pub async fn leverage_info(&self) -> Result<OkexLeverageInfoData, OkexClientError> {
         let path = "/api/v5/account/leverage-info?instId=BTC-USD-SWAP&mgnMode=cross";
-        let config_url = Self::url_for_path(path);
-        let headers = self.get_request_headers(path)?;
-
-        let response = self
-            .rate_limit_client(path)
-            .await
-            .get(config_url)
-            .headers(headers)
-            .send()
-            .await?;
-        let leverage_info = Self::extract_response_data::<OkexLeverageInfoData>(response).await?;
+        let response = self.get(path).await?;
+        let leverage_info = Self::extract_response_data::<OkexLeverageInfoData>(&response)?;
 
         Ok(leverage_info)
     }
 
-    pub async fn rate_limit_client(&self, key: &'static str) -> &ReqwestClient {
-        let jitter = Jitter::new(Duration::from_secs(1), Duration::from_secs(1));
-        LIMITER.until_key_ready_with_jitter(&key, jitter).await;
+    pub async fn rate_limit_client(&self, request_path: &str) -> &ReqwestClient {
+        self.rate_limiter.until_ready(request_path).await;
         &self.client
     }
 
+    pub fn rate_limit_saturation(&self) -> Vec<EndpointSaturation> {
+        self.rate_limiter.saturation()
+    }
+
+    pub fn check_rate_limit_health(&self) -> Result<(), OkexClientError> {
+        self.rate_limiter.check_health()
+    }
+
     #[instrument(name = "okex_client.get_funding_deposit_address", skip(self), err)]
     pub async fn get_funding_deposit_address(&self) -> Result<DepositAddress, OkexClientError> {
         if self.config.simulated {
@@ -141,17 +133,9 @@ impl OkexClient {
 
         let request_path = "/api/v5/asset/deposit-address?ccy=BTC";
 
-        let headers = self.get_request_headers(request_path)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(request_path).await?;
 
-        let addresses = Self::extract_response_data_array::<DepositAddressData>(response).await?;
+        let addresses = Self::extract_response_data_array::<DepositAddressData>(&response)?;
 
         // Filter through results from above and find the selected BTC on-chain address that feeds the funding account
         let deposit_address = addresses.into_iter().find(|address_entry| {
@@ -174,17 +158,9 @@ impl OkexClient {
     pub async fn get_onchain_fees(&self) -> Result<OnchainFees, OkexClientError> {
         let request_path = "/api/v5/asset/currencies?ccy=BTC";
 
-        let headers = self.get_request_headers(request_path)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(request_path).await?;
 
-        let fees_data_resp = Self::extract_response_data::<OnchainFeesData>(response).await;
+        let fees_data_resp = Self::extract_response_data::<OnchainFeesData>(&response);
         match fees_data_resp {
             Ok(fees_data) => Ok(OnchainFees {
                 ccy: fees_data.ccy,
@@ -220,18 +196,9 @@ impl OkexClient {
         let request_body = serde_json::to_string(&body)?;
 
         let request_path = "/api/v5/asset/transfer";
-        let headers = self.post_request_headers(request_path, request_body.as_str())?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .post(Self::url_for_path(request_path))
-            .headers(headers)
-            .body(request_body)
-            .send()
-            .await?;
-
-        let transfer_data = Self::extract_response_data::<TransferData>(response).await?;
+        let response = self.post(request_path, request_body).await?;
+
+        let transfer_data = Self::extract_response_data::<TransferData>(&response)?;
         Ok(TransferId {
             value: transfer_data.trans_id,
         })
@@ -252,19 +219,9 @@ impl OkexClient {
         let request_body = serde_json::to_string(&body)?;
 
         let request_path = "/api/v5/asset/transfer";
-        LIMITER.until_key_ready(&request_path).await;
-        let headers = self.post_request_headers(request_path, request_body.as_str())?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .post(Self::url_for_path(request_path))
-            .headers(headers)
-            .body(request_body)
-            .send()
-            .await?;
-
-        let transfer_data = Self::extract_response_data::<TransferData>(response).await?;
+        let response = self.post(request_path, request_body).await?;
+
+        let transfer_data = Self::extract_response_data::<TransferData>(&response)?;
         Ok(TransferId {
             value: transfer_data.trans_id,
         })
@@ -275,17 +232,9 @@ impl OkexClient {
     pub async fn funding_account_balance(&self) -> Result<AvailableBalance, OkexClientError> {
         let request_path = "/api/v5/asset/balances?ccy=BTC";
 
-        let headers = self.get_request_headers(request_path)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(request_path).await?;
 
-        let funding_balance = Self::extract_response_data::<FundingBalanceData>(response).await?;
+        let funding_balance = Self::extract_response_data::<FundingBalanceData>(&response)?;
 
         Ok(AvailableBalance {
             free_amt_in_btc: funding_balance.avail_bal,
@@ -298,17 +247,9 @@ impl OkexClient {
     pub async fn trading_account_balance(&self) -> Result<AvailableBalance, OkexClientError> {
         let request_path = "/api/v5/account/balance?ccy=BTC";
 
-        let headers = self.get_request_headers(request_path)?;
+        let response = self.get(request_path).await?;
 
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
-
-        let trading_balance = Self::extract_response_data::<TradingBalanceData>(response).await?;
+        let trading_balance = Self::extract_response_data::<TradingBalanceData>(&response)?;
 
         let mut free_amt_in_btc = Decimal::ZERO;
         let mut used_amt_in_btc = Decimal::ZERO;
@@ -335,17 +276,9 @@ impl OkexClient {
         let static_request_path = "/api/v5/asset/transfer-state?ccy=BTC&transId=";
         let request_path = format!("{static_request_path}{}", transfer_id.value);
 
-        let headers = self.get_request_headers(&request_path)?;
-
-        let response = self
-            .rate_limit_client(static_request_path)
-            .await
-            .get(Self::url_for_path(&request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(&request_path).await?;
 
-        let state_data = Self::extract_response_data::<TransferStateData>(response).await?;
+        let state_data = Self::extract_response_data::<TransferStateData>(&response)?;
 
         Ok(TransferState {
             state: state_data.state,
@@ -361,17 +294,9 @@ impl OkexClient {
         let static_request_path = "/api/v5/asset/transfer-state?ccy=BTC&clientId=";
         let request_path = format!("{}{}", static_request_path, client_id.0);
 
-        let headers = self.get_request_headers(&request_path)?;
+        let response = self.get(&request_path).await?;
 
-        let response = self
-            .rate_limit_client(static_request_path)
-            .await
-            .get(Self::url_for_path(&request_path))
-            .headers(headers)
-            .send()
-            .await?;
-
-        let state_data = Self::extract_response_data::<TransferStateData>(response).await?;
+        let state_data = Self::extract_response_data::<TransferStateData>(&response)?;
 
         Ok(TransferState {
             state: state_data.state,
@@ -399,18 +324,9 @@ impl OkexClient {
         let request_body = serde_json::to_string(&body)?;
 
         let request_path = "/api/v5/asset/withdrawal";
-        let headers = self.post_request_headers(request_path, request_body.as_str())?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .post(Self::url_for_path(request_path))
-            .headers(headers)
-            .body(request_body)
-            .send()
-            .await?;
+        let response = self.post(request_path, request_body).await?;
 
-        let withdraw_data = Self::extract_response_data::<WithdrawData>(response).await?;
+        let withdraw_data = Self::extract_response_data::<WithdrawData>(&response)?;
 
         Ok(WithdrawId {
             value: withdraw_data.wd_id,
@@ -431,16 +347,9 @@ impl OkexClient {
     ) -> Result<DepositStatus, OkexClientError> {
         // 1. Get all deposit history
         let request_path = "/api/v5/asset/deposit-history";
-        let headers = self.get_request_headers(request_path)?;
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(request_path).await?;
 
-        let history = Self::extract_response_data_array::<DepositHistoryData>(response).await?;
+        let history = Self::extract_response_data_array::<DepositHistoryData>(&response)?;
 
         // 2. Filter through results from above and find any entry that matches addr and amt_in_btc
         let deposit = history.into_iter().find(|deposit_entry| {
@@ -477,17 +386,10 @@ impl OkexClient {
     ) -> Result<WithdrawalStatus, OkexClientError> {
         let static_request_path = "/api/v5/asset/withdrawal-history?ccy=BTC&clientId=";
         let request_path = format!("{}{}", static_request_path, client_id.0);
-        let headers = self.get_request_headers(&request_path)?;
-        let response = self
-            .rate_limit_client(static_request_path)
-            .await
-            .get(Self::url_for_path(&request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(&request_path).await?;
 
         let withdrawal_data_option =
-            Self::extract_optional_response_data::<WithdrawalHistoryData>(response).await?;
+            Self::extract_optional_response_data::<WithdrawalHistoryData>(&response)?;
 
         match withdrawal_data_option {
             Some(withdrawal_data) => {
@@ -534,18 +436,9 @@ impl OkexClient {
         let request_body = serde_json::to_string(&body)?;
 
         let request_path = "/api/v5/trade/order";
-        let headers = self.post_request_headers(request_path, &request_body)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .post(Self::url_for_path(request_path))
-            .headers(headers)
-            .body(request_body)
-            .send()
-            .await?;
-
-        let order_data = Self::extract_response_data::<OrderData>(response).await?;
+        let response = self.post(request_path, request_body).await?;
+
+        let order_data = Self::extract_response_data::<OrderData>(&response)?;
         if order_data.ord_id.is_empty() && !order_data.s_code.is_empty() {
             return Err(OkexClientError::UnexpectedResponse {
                 msg: order_data.s_msg,
@@ -561,17 +454,9 @@ impl OkexClient {
     pub async fn order_details(&self, id: ClientOrderId) -> Result<OrderDetails, OkexClientError> {
         let static_request_path = "/api/v5/trade/order?instId=BTC-USD-SWAP&clOrdId=";
         let request_path = format!("{}{}", static_request_path, id.0);
-        let headers = self.get_request_headers(&request_path)?;
+        let response = self.get(&request_path).await?;
 
-        let response = self
-            .rate_limit_client(static_request_path)
-            .await
-            .get(Self::url_for_path(&request_path))
-            .headers(headers)
-            .send()
-            .await?;
-
-        let mut details = Self::extract_response_data::<OrderDetails>(response).await?;
+        let mut details = Self::extract_response_data::<OrderDetails>(&response)?;
         if details.state == "filled" || details.state == "canceled" {
             details.complete = true;
         }
@@ -580,18 +465,10 @@ impl OkexClient {
 
     pub async fn get_last_price_in_usd_cents(&self) -> Result<LastPrice, OkexClientError> {
         let request_path = "/api/v5/market/ticker?instId=BTC-USD-SWAP";
-        let headers = self.get_request_headers(request_path)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(request_path).await?;
 
         if let Some(LastPriceData { last, .. }) =
-            Self::extract_optional_response_data::<LastPriceData>(response).await?
+            Self::extract_optional_response_data::<LastPriceData>(&response)?
         {
             Ok(LastPrice {
                 usd_cents: last * Decimal::ONE_HUNDRED,
@@ -609,22 +486,14 @@ impl OkexClient {
     )]
     pub async fn get_position_in_signed_usd_cents(&self) -> Result<PositionSize, OkexClientError> {
         let request_path = "/api/v5/account/positions?instId=BTC-USD-SWAP";
-        let headers = self.get_request_headers(request_path)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .get(Self::url_for_path(request_path))
-            .headers(headers)
-            .send()
-            .await?;
+        let response = self.get(request_path).await?;
 
         if let Some(PositionData {
             notional_usd,
             pos,
             last,
             ..
-        }) = Self::extract_optional_response_data::<PositionData>(response).await?
+        }) = Self::extract_optional_response_data::<PositionData>(&response)?
         {
             let span = tracing::Span::current();
             span.record("notional_usd", tracing::field::display(&notional_usd));
@@ -690,18 +559,9 @@ impl OkexClient {
         let request_body = serde_json::to_string(&body)?;
 
         let request_path = "/api/v5/trade/close-position";
-        let headers = self.post_request_headers(request_path, &request_body)?;
-
-        let response = self
-            .rate_limit_client(request_path)
-            .await
-            .post(Self::url_for_path(request_path))
-            .headers(headers)
-            .body(request_body)
-            .send()
-            .await?;
-
-        match Self::extract_optional_response_data::<ClosePositionData>(response).await {
+        let response = self.post(request_path, request_body).await?;
+
+        match Self::extract_optional_response_data::<ClosePositionData>(&response) {
             Err(OkexClientError::UnexpectedResponse { msg, code })
                 if code == "51023"
                     || msg.starts_with("Position does not exist")
@@ -713,13 +573,82 @@ impl OkexClient {
         }
     }
 
+    async fn get(&self, request_path: &str) -> Result<String, OkexClientError> {
+        self.send_with_retry(request_path, || {
+            let headers = self.get_request_headers(request_path)?;
+            Ok(self
+                .client
+                .get(Self::url_for_path(request_path))
+                .headers(headers))
+        })
+        .await
+    }
+
+    async fn post(
+        &self,
+        request_path: &str,
+        request_body: String,
+    ) -> Result<String, OkexClientError> {
+        self.send_with_retry(request_path, || {
+            let headers = self.post_request_headers(request_path, &request_body)?;
+            Ok(self
+                .client
+                .post(Self::url_for_path(request_path))
+                .headers(headers)
+                .body(request_body.clone()))
+        })
+        .await
+    }
+
+    /// Sends the request once the endpoint quota allows it and backs off
+    /// when okex rejects it for exceeding its rate limit.
+    /// Headers are rebuilt on every attempt so the signature timestamp stays valid.
+    async fn send_with_retry(
+        &self,
+        request_path: &str,
+        build_request: impl Fn() -> Result<RequestBuilder, OkexClientError>,
+    ) -> Result<String, OkexClientError> {
+        let mut attempt = 0;
+        loop {
+            self.rate_limiter.until_ready(request_path).await;
+            let response = build_request()?.send().await?;
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let too_many_requests = response.status() == StatusCode::TOO_MANY_REQUESTS;
+            let response_text = response.text().await?;
+            let code = serde_json::from_str::<OkexResponseCode>(&response_text)
+                .map(|response| response.code)
+                .ok();
+            let rate_limited =
+                too_many_requests || matches!(code.as_deref(), Some("50011") | Some("50061"));
+            if !rate_limited {
+                return Ok(response_text);
+            }
+            if attempt >= self.rate_limiter.config().max_retries {
+                return Err(OkexClientError::RateLimited {
+                    msg: response_text,
+                    code: code
+                        .unwrap_or_else(|| StatusCode::TOO_MANY_REQUESTS.as_str().to_string()),
+                });
+            }
+            let backoff = self.rate_limiter.config().backoff(attempt, retry_after);
+            self.rate_limiter
+                .record_rate_limited(request_path, attempt, backoff);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
     /// Extracts the first entry in the response data
-    async fn extract_response_data<T: serde::de::DeserializeOwned>(
-        response: reqwest::Response,
+    fn extract_response_data<T: serde::de::DeserializeOwned>(
+        response_text: &str,
     ) -> Result<T, OkexClientError> {
-        let response_text = response.text().await?;
         let OkexResponse { code, msg, data } =
-            serde_json::from_str::<OkexResponse<T>>(&response_text)?;
+            serde_json::from_str::<OkexResponse<T>>(response_text)?;
         if let Some(data) = data {
             if let Some(first) = data.into_iter().next() {
                 return Ok(first);
@@ -728,12 +657,11 @@ impl OkexClient {
         Err(OkexClientError::from((msg, code)))
     }
 
-    async fn extract_optional_response_data<T: serde::de::DeserializeOwned>(
-        response: reqwest::Response,
+    fn extract_optional_response_data<T: serde::de::DeserializeOwned>(
+        response_text: &str,
     ) -> Result<Option<T>, OkexClientError> {
-        let response_text = response.text().await?;
         let OkexResponse { code, msg, data } =
-            serde_json::from_str::<OkexResponse<T>>(&response_text)?;
+            serde_json::from_str::<OkexResponse<T>>(response_text)?;
         if code == "0" && data.is_some() {
             return Ok(data.and_then(|v| v.into_iter().next()));
         }
@@ -741,12 +669,11 @@ impl OkexClient {
     }
 
     /// Extracts the array of entries in the response data
-    async fn extract_response_data_array<T: serde::de::DeserializeOwned>(
-        response: reqwest::Response,
+    fn extract_response_data_array<T: serde::de::DeserializeOwned>(
+        response_text: &str,
     ) -> Result<Vec<T>, OkexClientError> {
-        let response_text = response.text().await?;
         let OkexResponse { code, msg, data } =
-            serde_json::from_str::<OkexResponse<T>>(&response_text)?;
+            serde_json::from_str::<OkexResponse<T>>(response_text)?;
 
         if let Some(data) = data {
             return Ok(data);
@@ -10,6 +10,11 @@ pub struct OkexResponse<T> {
     pub data: Option<Vec<T>>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct OkexResponseCode {
+    pub code: String,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
@@ -114,6 +119,21 @@ pub struct TradingBalanceDetails {
     pub spot_in_use_amt: String,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubAccountTradingBalanceData {
+    pub details: Vec<SubAccountTradingBalanceDetails>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubAccountTradingBalanceDetails {
+    pub ccy: String,
+    pub avail_eq: Decimal,
+    pub eq: Decimal,
+    pub frozen_bal: Decimal,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
@@ -260,6 +280,8 @@ pub struct OkexAccountConfigurationData {
     pub mgn_iso_mode: String,
     pub pos_mode: String,
     pub uid: String,
+    #[serde(default)]
+    pub main_uid: String,
 }
 
 #[derive(Deserialize, Debug)]
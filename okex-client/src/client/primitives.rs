@@ -148,6 +148,24 @@ impl Display for Account {
     }
 }
 
+/// https://www.okx.com/docs-v5/en/#funding-account-rest-api-funds-transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OkexTransferType {
+    WithinAccount,
+    MasterToSubAccount,
+    SubAccountToMaster,
+}
+
+impl Display for OkexTransferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            OkexTransferType::WithinAccount => write!(f, "0"),
+            OkexTransferType::MasterToSubAccount => write!(f, "1"),
+            OkexTransferType::SubAccountToMaster => write!(f, "2"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TransferId {
     pub value: String,
@@ -0,0 +1,355 @@
+use governor::{
+    clock::{Clock, DefaultClock},
+    state::{direct::NotKeyed, InMemoryState},
+    Jitter, Quota, RateLimiter,
+};
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use super::error::OkexClientError;
+
+/// Request limits per endpoint as documented in
+/// https://www.okx.com/docs-v5/en/#overview-rate-limits
+const OKEX_DOCUMENTED_QUOTAS: &[(&str, u32, u64)] = &[
+    ("/api/v5/account/config", 5, 2),
+    ("/api/v5/account/leverage-info", 20, 2),
+    ("/api/v5/account/balance", 10, 2),
+    ("/api/v5/account/positions", 10, 2),
+    ("/api/v5/account/subaccount/balances", 6, 2),
+    ("/api/v5/asset/deposit-address", 6, 1),
+    ("/api/v5/asset/currencies", 6, 1),
+    ("/api/v5/asset/transfer", 1, 1),
+    ("/api/v5/asset/transfer-state", 10, 1),
+    ("/api/v5/asset/balances", 6, 1),
+    ("/api/v5/asset/subaccount/balances", 6, 2),
+    ("/api/v5/asset/withdrawal", 6, 1),
+    ("/api/v5/asset/deposit-history", 6, 1),
+    ("/api/v5/asset/withdrawal-history", 6, 1),
+    ("/api/v5/trade/order", 60, 2),
+    ("/api/v5/trade/close-position", 20, 2),
+    ("/api/v5/market/ticker", 20, 2),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndpointQuota {
+    pub requests: u32,
+    pub period_secs: u64,
+}
+
+impl EndpointQuota {
+    fn quota(&self) -> Quota {
+        let requests = NonZeroU32::new(self.requests.max(1)).expect("requests is never zero");
+        let period = Duration::from_secs(self.period_secs.max(1)) / requests.get();
+        Quota::with_period(period)
+            .expect("period is never zero")
+            .allow_burst(requests)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OkexRateLimitConfig {
+    /// Overrides of the documented quotas keyed by endpoint path (without query string)
+    #[serde(default)]
+    pub quotas: HashMap<String, EndpointQuota>,
+    #[serde(default = "default_fallback_quota")]
+    pub fallback_quota: EndpointQuota,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    #[serde(default = "default_unhealthy_queue_depth")]
+    pub unhealthy_queue_depth: usize,
+    /// Upper bound of the random delay added to a throttled request, as a percentage of its wait
+    #[serde(default = "default_wait_jitter_percent")]
+    pub wait_jitter_percent: u32,
+}
+
+impl Default for OkexRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            quotas: HashMap::new(),
+            fallback_quota: default_fallback_quota(),
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+            unhealthy_queue_depth: default_unhealthy_queue_depth(),
+            wait_jitter_percent: default_wait_jitter_percent(),
+        }
+    }
+}
+
+impl OkexRateLimitConfig {
+    pub fn quota_for(&self, endpoint: &str) -> EndpointQuota {
+        if let Some(quota) = self.quotas.get(endpoint) {
+            return *quota;
+        }
+        OKEX_DOCUMENTED_QUOTAS
+            .iter()
+            .find(|(path, _, _)| *path == endpoint)
+            .map(|(_, requests, period_secs)| EndpointQuota {
+                requests: *requests,
+                period_secs: *period_secs,
+            })
+            .unwrap_or(self.fallback_quota)
+    }
+
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let exponential = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(self.max_backoff_ms);
+        let backoff = Duration::from_millis(exponential);
+        match retry_after {
+            Some(retry_after) if retry_after > backoff => retry_after,
+            _ => backoff,
+        }
+    }
+
+    pub fn max_jitter(&self, wait: Duration) -> Duration {
+        wait * self.wait_jitter_percent / 100
+    }
+}
+
+fn default_fallback_quota() -> EndpointQuota {
+    EndpointQuota {
+        requests: 1,
+        period_secs: 1,
+    }
+}
+fn default_max_retries() -> u32 {
+    3
+}
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+fn default_max_backoff_ms() -> u64 {
+    10_000
+}
+fn default_unhealthy_queue_depth() -> usize {
+    20
+}
+fn default_wait_jitter_percent() -> u32 {
+    10
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointSaturation {
+    pub endpoint: String,
+    pub queued: usize,
+    pub throttled_total: u64,
+    pub rate_limited_total: u64,
+}
+
+struct EndpointLimiter {
+    limiter: RateLimiter<NotKeyed, InMemoryState, DefaultClock>,
+    queued: AtomicUsize,
+    throttled_total: AtomicU64,
+    rate_limited_total: AtomicU64,
+}
+
+struct QueuedRequest<'a>(&'a AtomicUsize);
+impl Drop for QueuedRequest<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct OkexRateLimiter {
+    config: OkexRateLimitConfig,
+    limiters: Arc<Mutex<HashMap<String, Arc<EndpointLimiter>>>>,
+}
+
+impl OkexRateLimiter {
+    pub fn new(config: OkexRateLimitConfig) -> Self {
+        Self {
+            config,
+            limiters: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &OkexRateLimitConfig {
+        &self.config
+    }
+
+    /// Waits (queueing behind other callers) until the endpoint quota allows another request
+    pub async fn until_ready(&self, request_path: &str) {
+        let endpoint = endpoint_of(request_path);
+        let limiter = self.limiter_for(endpoint);
+        let wait = match limiter.limiter.check() {
+            Ok(()) => return,
+            Err(not_until) => not_until.wait_time_from(DefaultClock::default().now()),
+        };
+        let queued = limiter.queued.fetch_add(1, Ordering::SeqCst) + 1;
+        let throttled_total = limiter.throttled_total.fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::warn!(
+            endpoint,
+            queued,
+            throttled_total,
+            wait_ms = wait.as_millis() as u64,
+            "okex_client.rate_limit.throttled"
+        );
+        let _dequeue_on_drop = QueuedRequest(&limiter.queued);
+        let jitter = Jitter::up_to(self.config.max_jitter(wait));
+        limiter.limiter.until_ready_with_jitter(jitter).await;
+    }
+
+    pub fn record_rate_limited(&self, request_path: &str, attempt: u32, backoff: Duration) {
+        let endpoint = endpoint_of(request_path);
+        let limiter = self.limiter_for(endpoint);
+        let rate_limited_total = limiter.rate_limited_total.fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::warn!(
+            endpoint,
+            attempt,
+            backoff_ms = backoff.as_millis() as u64,
+            rate_limited_total,
+            "okex_client.rate_limit.server_rejected"
+        );
+    }
+
+    pub fn saturation(&self) -> Vec<EndpointSaturation> {
+        let limiters = self.limiters.lock().expect("rate limiter lock poisoned");
+        let mut saturation: Vec<_> = limiters
+            .iter()
+            .map(|(endpoint, limiter)| EndpointSaturation {
+                endpoint: endpoint.clone(),
+                queued: limiter.queued.load(Ordering::SeqCst),
+                throttled_total: limiter.throttled_total.load(Ordering::SeqCst),
+                rate_limited_total: limiter.rate_limited_total.load(Ordering::SeqCst),
+            })
+            .collect();
+        saturation.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        saturation
+    }
+
+    pub fn check_health(&self) -> Result<(), OkexClientError> {
+        if let Some(saturated) = self
+            .saturation()
+            .into_iter()
+            .find(|s| s.queued >= self.config.unhealthy_queue_depth)
+        {
+            return Err(OkexClientError::RateLimitSaturated {
+                endpoint: saturated.endpoint,
+                queued: saturated.queued,
+            });
+        }
+        Ok(())
+    }
+
+    fn limiter_for(&self, endpoint: &str) -> Arc<EndpointLimiter> {
+        let mut limiters = self.limiters.lock().expect("rate limiter lock poisoned");
+        if let Some(limiter) = limiters.get(endpoint) {
+            return Arc::clone(limiter);
+        }
+        let limiter = Arc::new(EndpointLimiter {
+            limiter: RateLimiter::direct(self.config.quota_for(endpoint).quota()),
+            queued: AtomicUsize::new(0),
+            throttled_total: AtomicU64::new(0),
+            rate_limited_total: AtomicU64::new(0),
+        });
+        limiters.insert(endpoint.to_string(), Arc::clone(&limiter));
+        limiter
+    }
+}
+
+/// Quotas are tracked per endpoint so the query string is not part of the key
+pub fn endpoint_of(request_path: &str) -> &str {
+    request_path
+        .split_once('?')
+        .map(|(endpoint, _)| endpoint)
+        .unwrap_or(request_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_strips_query() {
+        assert_eq!(
+            endpoint_of("/api/v5/asset/transfer-state?ccy=BTC&transId="),
+            "/api/v5/asset/transfer-state"
+        );
+        assert_eq!(
+            endpoint_of("/api/v5/asset/transfer"),
+            "/api/v5/asset/transfer"
+        );
+    }
+
+    #[test]
+    fn quota_for_uses_documented_limits() {
+        let config = OkexRateLimitConfig::default();
+        assert_eq!(
+            config.quota_for("/api/v5/trade/order"),
+            EndpointQuota {
+                requests: 60,
+                period_secs: 2
+            }
+        );
+        assert_eq!(
+            config.quota_for("/api/v5/unknown"),
+            default_fallback_quota()
+        );
+    }
+
+    #[test]
+    fn quota_for_prefers_overrides() {
+        let config: OkexRateLimitConfig = serde_yaml::from_str(
+            "quotas:\n  /api/v5/trade/order:\n    requests: 10\n    period_secs: 1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.quota_for("/api/v5/trade/order"),
+            EndpointQuota {
+                requests: 10,
+                period_secs: 1
+            }
+        );
+    }
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        let config = OkexRateLimitConfig::default();
+        assert_eq!(config.backoff(0, None), Duration::from_millis(500));
+        assert_eq!(config.backoff(2, None), Duration::from_millis(2000));
+        assert_eq!(config.backoff(10, None), Duration::from_millis(10_000));
+    }
+
+    #[test]
+    fn jitter_is_proportional_to_wait() {
+        let config = OkexRateLimitConfig::default();
+        assert_eq!(
+            config.max_jitter(Duration::from_millis(500)),
+            Duration::from_millis(50)
+        );
+        let config = OkexRateLimitConfig {
+            wait_jitter_percent: 0,
+            ..Default::default()
+        };
+        assert_eq!(config.max_jitter(Duration::from_secs(2)), Duration::ZERO);
+    }
+
+    #[test]
+    fn backoff_honours_longer_retry_after() {
+        let config = OkexRateLimitConfig::default();
+        assert_eq!(
+            config.backoff(0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            config.backoff(3, Some(Duration::from_secs(1))),
+            Duration::from_millis(4000)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use std::collections::HashMap;
+
+use super::{okex_response::*, primitives::*, OkexClient, OkexClientConfig, OkexClientError};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OkexMasterClientConfig {
+    #[serde(default)]
+    pub client: OkexClientConfig,
+    #[serde(default)]
+    pub sub_account_name: String,
+}
+
+/// Client for the master account that owns the hedging sub-account.
+/// Uses the master api key to move collateral between the master funding
+/// account and the sub-account without going onchain.
+#[derive(Clone)]
+pub struct OkexMasterClient {
+    client: OkexClient,
+    sub_account_name: String,
+}
+
+impl OkexMasterClient {
+    pub async fn new(config: OkexMasterClientConfig) -> Result<Self, OkexClientError> {
+        if config.sub_account_name.is_empty() {
+            return Err(OkexClientError::MisconfiguredAccount(
+                "Expected `sub_account_name` to be set".to_string(),
+            ));
+        }
+        let client = OkexClient::build(config.client)?;
+        let path = "/api/v5/account/config";
+        let response = client.get(path).await?;
+        let config_data =
+            OkexClient::extract_response_data::<OkexAccountConfigurationData>(&response)?;
+
+        if !config_data.main_uid.is_empty() && config_data.main_uid != config_data.uid {
+            return Err(OkexClientError::MisconfiguredAccount(format!(
+                "Expected master account, got sub-account of `{}`",
+                config_data.main_uid
+            )));
+        }
+        Ok(Self {
+            client,
+            sub_account_name: config.sub_account_name,
+        })
+    }
+
+    pub fn sub_account_name(&self) -> &str {
+        &self.sub_account_name
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        self.client.is_simulated()
+    }
+
+    pub fn check_rate_limit_health(&self) -> Result<(), OkexClientError> {
+        self.client.check_rate_limit_health()
+    }
+
+    #[instrument(name = "okex_client.master_funding_account_balance", skip(self), err)]
+    pub async fn funding_account_balance(&self) -> Result<AvailableBalance, OkexClientError> {
+        self.client.funding_account_balance().await
+    }
+
+    /// https://www.okx.com/docs-v5/en/#sub-account-rest-api-get-sub-account-funding-balance
+    #[instrument(name = "okex_client.sub_account_funding_balance", skip(self), err)]
+    pub async fn sub_account_funding_balance(&self) -> Result<AvailableBalance, OkexClientError> {
+        let request_path = format!(
+            "/api/v5/asset/subaccount/balances?ccy=BTC&subAcct={}",
+            self.sub_account_name
+        );
+        let response = self.client.get(&request_path).await?;
+
+        let balance = OkexClient::extract_optional_response_data::<FundingBalanceData>(&response)?;
+        Ok(balance
+            .map(|funding_balance| AvailableBalance {
+                free_amt_in_btc: funding_balance.avail_bal,
+                used_amt_in_btc: funding_balance.frozen_bal,
+                total_amt_in_btc: funding_balance.bal,
+            })
+            .unwrap_or(AvailableBalance {
+                free_amt_in_btc: Decimal::ZERO,
+                used_amt_in_btc: Decimal::ZERO,
+                total_amt_in_btc: Decimal::ZERO,
+            }))
+    }
+
+    /// https://www.okx.com/docs-v5/en/#sub-account-rest-api-get-sub-account-trading-balance
+    #[instrument(name = "okex_client.sub_account_trading_balance", skip(self), err)]
+    pub async fn sub_account_trading_balance(&self) -> Result<AvailableBalance, OkexClientError> {
+        let request_path = format!(
+            "/api/v5/account/subaccount/balances?subAcct={}",
+            self.sub_account_name
+        );
+        let response = self.client.get(&request_path).await?;
+
+        let trading_balance =
+            OkexClient::extract_response_data::<SubAccountTradingBalanceData>(&response)?;
+
+        let mut balance = AvailableBalance {
+            free_amt_in_btc: Decimal::ZERO,
+            used_amt_in_btc: Decimal::ZERO,
+            total_amt_in_btc: Decimal::ZERO,
+        };
+        if let Some(details) = trading_balance
+            .details
+            .into_iter()
+            .find(|details| details.ccy == TradeCurrency::BTC.to_string())
+        {
+            balance.free_amt_in_btc = details.avail_eq;
+            balance.used_amt_in_btc = details.frozen_bal;
+            balance.total_amt_in_btc = details.eq;
+        }
+        Ok(balance)
+    }
+
+    #[instrument(name = "okex_client.transfer_master_to_sub_account", skip(self), err)]
+    pub async fn transfer_master_to_sub_account(
+        &self,
+        client_id: ClientTransferId,
+        amt: Decimal,
+    ) -> Result<TransferId, OkexClientError> {
+        self.sub_account_transfer(client_id, amt, OkexTransferType::MasterToSubAccount)
+            .await
+    }
+
+    #[instrument(name = "okex_client.transfer_sub_account_to_master", skip(self), err)]
+    pub async fn transfer_sub_account_to_master(
+        &self,
+        client_id: ClientTransferId,
+        amt: Decimal,
+    ) -> Result<TransferId, OkexClientError> {
+        self.sub_account_transfer(client_id, amt, OkexTransferType::SubAccountToMaster)
+            .await
+    }
+
+    pub async fn transfer_state_by_client_id(
+        &self,
+        client_id: ClientTransferId,
+        transfer_type: OkexTransferType,
+    ) -> Result<TransferState, OkexClientError> {
+        let request_path = format!(
+            "/api/v5/asset/transfer-state?ccy=BTC&type={}&clientId={}",
+            transfer_type, client_id.0
+        );
+        let response = self.client.get(&request_path).await?;
+
+        let state_data = OkexClient::extract_response_data::<TransferStateData>(&response)?;
+
+        Ok(TransferState {
+            state: state_data.state,
+            transfer_id: state_data.trans_id,
+            client_id: state_data.client_id,
+        })
+    }
+
+    /// Moves funds between the funding accounts of the master and the sub-account
+    async fn sub_account_transfer(
+        &self,
+        client_id: ClientTransferId,
+        amt: Decimal,
+        transfer_type: OkexTransferType,
+    ) -> Result<TransferId, OkexClientError> {
+        let mut body: HashMap<String, String> = HashMap::new();
+        body.insert("ccy".to_string(), TradeCurrency::BTC.to_string());
+        body.insert("amt".to_string(), amt.to_string());
+        body.insert("from".to_string(), Account::FUNDING.to_string());
+        body.insert("to".to_string(), Account::FUNDING.to_string());
+        body.insert("subAcct".to_string(), self.sub_account_name.clone());
+        body.insert("type".to_string(), transfer_type.to_string());
+        body.insert("clientId".to_string(), client_id.0);
+        let request_body = serde_json::to_string(&body)?;
+
+        let request_path = "/api/v5/asset/transfer";
+        let response = self.client.post(request_path, request_body).await?;
+
+        let transfer_data = OkexClient::extract_response_data::<TransferData>(&response)?;
+        Ok(TransferId {
+            value: transfer_data.trans_id,
+        })
+    }
+}
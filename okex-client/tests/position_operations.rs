@@ -8,6 +8,7 @@ fn okex_client_config() -> OkexClientConfig {
         secret_key: std::env::var("OKEX_SECRET_KEY").expect("OKEX_SECRET_KEY must be set"),
         passphrase: std::env::var("OKEX_PASSPHRASE").expect("OKEX_PASSPHRASE must be set"),
         simulated: true,
+        ..Default::default()
     }
 }
 
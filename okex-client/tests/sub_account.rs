@@ -0,0 +1,57 @@
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use std::env;
+
+use okex_client::*;
+
+async fn configured_master_client() -> anyhow::Result<OkexMasterClient> {
+    let api_key = env::var("OKEX_MASTER_API_KEY").expect("OKEX_MASTER_API_KEY not set");
+    let passphrase = env::var("OKEX_MASTER_PASSPHRASE").expect("OKEX_MASTER_PASSPHRASE not set");
+    let secret_key = env::var("OKEX_MASTER_SECRET_KEY").expect("OKEX_MASTER_SECRET_KEY not set");
+    let sub_account_name =
+        env::var("OKEX_SUB_ACCOUNT_NAME").expect("OKEX_SUB_ACCOUNT_NAME not set");
+
+    let client = OkexMasterClient::new(OkexMasterClientConfig {
+        client: OkexClientConfig {
+            api_key,
+            passphrase,
+            secret_key,
+            simulated: true,
+            ..Default::default()
+        },
+        sub_account_name,
+    })
+    .await?;
+
+    Ok(client)
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "requires master account credentials"]
+async fn sub_account_balances() -> anyhow::Result<()> {
+    let client = configured_master_client().await?;
+    let funding = client.sub_account_funding_balance().await?;
+    assert!(funding.total_amt_in_btc >= Decimal::ZERO);
+    let trading = client.sub_account_trading_balance().await?;
+    assert!(trading.total_amt_in_btc >= Decimal::ZERO);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "requires master account credentials"]
+async fn unknown_sub_account_transfer_state() -> anyhow::Result<()> {
+    let client = configured_master_client().await?;
+    let result = client
+        .transfer_state_by_client_id(
+            ClientTransferId::new(),
+            OkexTransferType::MasterToSubAccount,
+        )
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 use crate::*;
 use shared::{payload::*, time::*};
 
-use super::price_feed::{OkexPriceTick, PriceFeedError};
+use super::price_feed::{OkexIndexTicker, OkexMarkPrice, OkexPriceTick, PriceFeedError};
 
 impl TryFrom<OkexPriceTick> for PriceStreamPayload {
     type Error = PriceFeedError;
@@ -23,6 +23,46 @@ impl TryFrom<OkexPriceTick> for PriceStreamPayload {
     }
 }
 
+impl TryFrom<OkexMarkPrice> for PriceStreamPayload {
+    type Error = PriceFeedError;
+
+    fn try_from(OkexMarkPrice { data, .. }: OkexMarkPrice) -> Result<Self, Self::Error> {
+        let mark_price = data
+            .into_iter()
+            .next()
+            .ok_or(PriceFeedError::EmptyPriceData)?;
+
+        Ok(PriceStreamPayload::OkexBtcUsdSwapMarkPricePayload(
+            ReferencePricePayload {
+                exchange: ExchangeIdRaw::from(OKEX_EXCHANGE_ID),
+                instrument_id: InstrumentIdRaw::from(mark_price.inst_id),
+                timestamp: TimeStamp::try_from(&mark_price.ts)?,
+                price: PriceRatioRaw::from_one_btc_in_usd_price(mark_price.mark_px),
+            },
+        ))
+    }
+}
+
+impl TryFrom<OkexIndexTicker> for PriceStreamPayload {
+    type Error = PriceFeedError;
+
+    fn try_from(OkexIndexTicker { data, .. }: OkexIndexTicker) -> Result<Self, Self::Error> {
+        let index = data
+            .into_iter()
+            .next()
+            .ok_or(PriceFeedError::EmptyPriceData)?;
+
+        Ok(PriceStreamPayload::OkexBtcUsdIndexPricePayload(
+            ReferencePricePayload {
+                exchange: ExchangeIdRaw::from(OKEX_EXCHANGE_ID),
+                instrument_id: InstrumentIdRaw::from(index.inst_id),
+                timestamp: TimeStamp::try_from(&index.ts)?,
+                price: PriceRatioRaw::from_one_btc_in_usd_price(index.idx_px),
+            },
+        ))
+    }
+}
+
 impl TryFrom<OkexOrderBook> for OrderBookIncrement {
     type Error = PriceFeedError;
 
@@ -59,3 +99,34 @@ impl TryFrom<OkexOrderBook> for OrderBookIncrement {
         Ok(inner)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn convert_mark_and_index_prices() -> anyhow::Result<()> {
+        let mark_price = serde_json::from_str::<OkexMarkPrice>(
+            r#"{"arg":{"channel":"mark-price","instId":"BTC-USD-SWAP"},"data":[{"instType":"SWAP","instId":"BTC-USD-SWAP","markPx":"20276.3","ts":"1666759645503"}]}"#,
+        )?;
+        let PriceStreamPayload::OkexBtcUsdSwapMarkPricePayload(payload) =
+            PriceStreamPayload::try_from(mark_price)?
+        else {
+            panic!("expected mark price payload")
+        };
+        assert_eq!(payload.price.numerator_amount(), dec!(0.020276300000));
+
+        let index_price = serde_json::from_str::<OkexIndexTicker>(
+            r#"{"arg":{"channel":"index-tickers","instId":"BTC-USD"},"data":[{"instId":"BTC-USD","idxPx":"20270.1","high24h":"20500","low24h":"20100","open24h":"20200","sodUtc0":"20300","sodUtc8":"20250","ts":"1666759645503"}]}"#,
+        )?;
+        let PriceStreamPayload::OkexBtcUsdIndexPricePayload(payload) =
+            PriceStreamPayload::try_from(index_price)?
+        else {
+            panic!("expected index price payload")
+        };
+        assert_eq!(payload.instrument_id.as_str(), "BTC-USD");
+        Ok(())
+    }
+}
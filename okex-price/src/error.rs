@@ -13,7 +13,7 @@ use shared::{
 pub enum PriceFeedError {
     #[error("PriceFeedError - OkexWsError: {0}")]
     OkexWsError(#[from] TungsteniteError),
-    #[error("PriceFeedError - EmptyPriceData: OkexPriceTick.data was empty")]
+    #[error("PriceFeedError - EmptyPriceData: price channel data was empty")]
     EmptyPriceData,
     #[error("PriceFeedError - EmptyOrderBookData: OkexOrderBook.data was empty")]
     EmptyOrderBookData,
@@ -21,6 +21,12 @@ pub enum PriceFeedError {
     EmptyBookSide,
     #[error("PriceFeedError - InvalidTimestamp: {0}")]
     InvalidTimestamp(#[from] shared::time::TimeStampError),
+    #[error("PriceFeedError - RestRequest: {0}")]
+    RestRequest(#[from] reqwest::Error),
+    #[error("PriceFeedError - RestSnapshot: {0}")]
+    RestSnapshot(String),
+    #[error("PriceFeedError - Io: {0}")]
+    Io(#[from] std::io::Error),
     #[error("PriceFeedError - SerdeError: {0}")]
     SerializationError(#[from] SerdeError),
     #[error("PriceFeedError - PublisherError: {0}")]
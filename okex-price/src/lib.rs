@@ -6,40 +6,94 @@ pub mod error;
 pub mod okex_shared;
 pub mod order_book;
 pub mod price_feed;
+pub mod recorder;
+pub mod replay;
 
-use futures::StreamExt;
-use shared::{payload::*, pubsub::*};
-use tokio::{join, time::timeout};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use shared::{health::HealthCheckTrigger, payload::*, pubsub::*};
+use tokio::{
+    join,
+    time::{sleep_until, timeout, Instant},
+};
 
 pub use okex_shared::*;
 pub use order_book::*;
 pub use price_feed::*;
+pub use recorder::*;
+pub use replay::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OkexPriceFeedConfig {
+    #[serde(default)]
+    pub recorder: Option<FeedRecorderConfig>,
+    /// Publishes a recording instead of subscribing to the live feed.
+    /// `run` returns once the recording is exhausted.
+    #[serde(default)]
+    pub replay: Option<FeedReplayConfig>,
+    #[serde(default)]
+    pub resync: OrderBookResyncConfig,
+}
 
 pub async fn run(
     price_stream_publisher: memory::Publisher<PriceStreamPayload>,
+    mut health_check_trigger: HealthCheckTrigger,
     unhealthy_msg_interval: std::time::Duration,
+    config: OkexPriceFeedConfig,
 ) -> Result<(), PriceFeedError> {
+    let resyncs = OrderBookResyncs::new(config.resync);
+    let health_resyncs = resyncs.clone();
+    tokio::spawn(async move {
+        while let Some(check) = health_check_trigger.next().await {
+            let _ = check.send(health_resyncs.healthy());
+        }
+    });
+
+    if let Some(replay_config) = config.replay {
+        return replay(price_stream_publisher, replay_config).await;
+    }
+    let recorder = config.recorder.map(FeedRecorder::start).transpose()?;
+
     let _ = tokio::spawn(async move {
         loop {
             let tick_publisher = price_stream_publisher.clone();
-            if let Ok(mut stream) = subscribe_btc_usd_swap_price_tick().await {
+            if let Ok(mut stream) =
+                subscribe_btc_usd_swap_price_tick_recorded(recorder.clone()).await
+            {
                 let tick_task = tokio::spawn(async move {
                     while let Some(tick) = stream.next().await {
                         let _res = okex_price_tick_received(&tick_publisher, tick).await;
                     }
                 });
                 let order_book_publisher = price_stream_publisher.clone();
+                let order_book_recorder = recorder.clone();
+                let order_book_resyncs = resyncs.clone();
                 let order_book_task = tokio::spawn(async move {
                     loop {
                         let _res = order_book_subscription(
                             order_book_publisher.clone(),
                             unhealthy_msg_interval,
+                            order_book_recorder.clone(),
+                            &order_book_resyncs,
                         )
                         .await;
                         tokio::time::sleep(std::time::Duration::from_secs(5_u64)).await;
                     }
                 });
-                let _ = join!(tick_task, order_book_task);
+                let reference_publisher = price_stream_publisher.clone();
+                let reference_recorder = recorder.clone();
+                let reference_task = tokio::spawn(async move {
+                    loop {
+                        let _res = reference_price_subscription(
+                            reference_publisher.clone(),
+                            unhealthy_msg_interval,
+                            reference_recorder.clone(),
+                        )
+                        .await;
+                        tokio::time::sleep(std::time::Duration::from_secs(5_u64)).await;
+                    }
+                });
+                let _ = join!(tick_task, order_book_task, reference_task);
             }
         }
     })
@@ -51,39 +105,83 @@ pub async fn run(
 async fn order_book_subscription(
     publisher: memory::Publisher<PriceStreamPayload>,
     unhealthy_msg_interval: std::time::Duration,
+    recorder: Option<FeedRecorder>,
+    resyncs: &OrderBookResyncs,
 ) -> Result<(), PriceFeedError> {
-    let mut stream = subscribe_btc_usd_swap_order_book().await?;
+    let mut stream = subscribe_btc_usd_swap_order_book_recorded(recorder).await?;
     let full_load = stream.next().await.ok_or(PriceFeedError::InitialFullLoad)?;
     let order_book = CompleteOrderBook::try_from(OrderBookIncrement::try_from(full_load)?)?;
     let mut cache = OrderBookCache::new(order_book);
 
-    let (send, recv) = tokio::sync::oneshot::channel();
-
-    tokio::spawn(async move {
-        loop {
-            match timeout(unhealthy_msg_interval, stream.next()).await {
-                Ok(Some(book)) => {
-                    if let Err(e) = okex_order_book_received(&publisher, book, &mut cache).await {
-                        let _ = send.send(e);
-                        break;
-                    }
-                }
-                Ok(None) => {
-                    let _ = send.send(PriceFeedError::StreamEnded);
-                    break;
-                }
-                Err(_) => {
-                    let _ = send.send(PriceFeedError::StreamStalled);
-                    break;
+    let mut consecutive_resyncs = 0;
+    loop {
+        let reason = match timeout(unhealthy_msg_interval, stream.next()).await {
+            Ok(Some(book)) => match okex_order_book_received(&publisher, book, &mut cache).await {
+                Ok(()) => {
+                    consecutive_resyncs = 0;
+                    continue;
                 }
+                Err(PriceFeedError::CheckSumValidation) => PriceFeedError::CheckSumValidation,
+                Err(e) => return Err(e),
+            },
+            Ok(None) => return Err(PriceFeedError::StreamEnded),
+            Err(_) => PriceFeedError::StreamStalled,
+        };
+        if consecutive_resyncs >= resyncs.config().max_consecutive_resyncs {
+            return Err(reason);
+        }
+        consecutive_resyncs += 1;
+        resyncs.record(&reason);
+        okex_order_book_resynced(&publisher, &mut cache).await?;
+    }
+}
+
+/// Mark and index prices share a connection lifecycle. Either stream ending or going quiet for
+/// longer than `unhealthy_msg_interval` resubscribes both.
+async fn reference_price_subscription(
+    publisher: memory::Publisher<PriceStreamPayload>,
+    unhealthy_msg_interval: std::time::Duration,
+    recorder: Option<FeedRecorder>,
+) -> Result<(), PriceFeedError> {
+    let mark_prices = subscribe_btc_usd_swap_mark_price_recorded(recorder.clone()).await?;
+    let index_prices = subscribe_btc_usd_index_price_recorded(recorder).await?;
+    forward_reference_prices(
+        &publisher,
+        unhealthy_msg_interval,
+        mark_prices,
+        index_prices,
+    )
+    .await
+}
+
+async fn forward_reference_prices(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    unhealthy_msg_interval: std::time::Duration,
+    mut mark_prices: impl Stream<Item = OkexMarkPrice> + Unpin,
+    mut index_prices: impl Stream<Item = OkexIndexTicker> + Unpin,
+) -> Result<(), PriceFeedError> {
+    let mut mark_deadline = Instant::now() + unhealthy_msg_interval;
+    let mut index_deadline = Instant::now() + unhealthy_msg_interval;
+    loop {
+        tokio::select! {
+            mark_price = mark_prices.next() => {
+                let mark_price = mark_price.ok_or(PriceFeedError::StreamEnded)?;
+                mark_deadline = Instant::now() + unhealthy_msg_interval;
+                okex_mark_price_received(publisher, mark_price).await?;
+            }
+            index_price = index_prices.next() => {
+                let index_price = index_price.ok_or(PriceFeedError::StreamEnded)?;
+                index_deadline = Instant::now() + unhealthy_msg_interval;
+                okex_index_price_received(publisher, index_price).await?;
+            }
+            _ = sleep_until(mark_deadline.min(index_deadline)) => {
+                return Err(PriceFeedError::StreamStalled);
             }
         }
-    });
-    let _receiver = recv.await;
-    Ok(())
+    }
 }
 
-async fn okex_price_tick_received(
+pub(crate) async fn okex_price_tick_received(
     publisher: &memory::Publisher<PriceStreamPayload>,
     tick: OkexPriceTick,
 ) -> Result<(), PriceFeedError> {
@@ -95,7 +193,31 @@ async fn okex_price_tick_received(
     Ok(())
 }
 
-async fn okex_order_book_received(
+pub(crate) async fn okex_mark_price_received(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    mark_price: OkexMarkPrice,
+) -> Result<(), PriceFeedError> {
+    if let Ok(payload) = PriceStreamPayload::try_from(mark_price) {
+        publisher
+            .throttle_publish("OKEX_MARK_PRICE", payload)
+            .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn okex_index_price_received(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    index_price: OkexIndexTicker,
+) -> Result<(), PriceFeedError> {
+    if let Ok(payload) = PriceStreamPayload::try_from(index_price) {
+        publisher
+            .throttle_publish("OKEX_INDEX_PRICE", payload)
+            .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn okex_order_book_received(
     publisher: &memory::Publisher<PriceStreamPayload>,
     book: OkexOrderBook,
     cache: &mut OrderBookCache,
@@ -114,3 +236,74 @@ async fn okex_order_book_received(
 
     Ok(())
 }
+
+/// Re-seeds the cache from a REST snapshot so prices keep flowing while the
+/// websocket catches up
+async fn okex_order_book_resynced(
+    publisher: &memory::Publisher<PriceStreamPayload>,
+    cache: &mut OrderBookCache,
+) -> Result<(), PriceFeedError> {
+    cache.reseed(fetch_btc_usd_swap_order_book_snapshot().await?);
+    if let Ok(complete_order_book) = OrderBookPayload::try_from(cache.latest().clone()) {
+        publisher
+            .throttle_publish(
+                "OKEX_ORDER_BOOK",
+                PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(complete_order_book),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn publisher() -> memory::Publisher<PriceStreamPayload> {
+        memory::channel(chrono::Duration::milliseconds(1)).0
+    }
+
+    #[tokio::test]
+    async fn resubscribes_when_one_reference_stream_ends() {
+        let res = forward_reference_prices(
+            &publisher(),
+            std::time::Duration::from_secs(60),
+            stream::empty::<OkexMarkPrice>(),
+            stream::pending::<OkexIndexTicker>(),
+        )
+        .await;
+
+        assert!(matches!(res, Err(PriceFeedError::StreamEnded)));
+    }
+
+    #[tokio::test]
+    async fn resubscribes_when_one_reference_stream_stalls() {
+        let index_prices = stream::repeat_with(|| OkexIndexTicker {
+            arg: ChannelArgs {
+                channel: "index-tickers".to_string(),
+                inst_id: "BTC-USD".to_string(),
+            },
+            data: Vec::new(),
+        })
+        .then(|ticker| async {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            ticker
+        });
+
+        let res = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            forward_reference_prices(
+                &publisher(),
+                std::time::Duration::from_millis(50),
+                stream::pending::<OkexMarkPrice>(),
+                Box::pin(index_prices),
+            ),
+        )
+        .await
+        .expect("stalled mark price stream was not detected");
+
+        assert!(matches!(res, Err(PriceFeedError::StreamStalled)));
+    }
+}
@@ -96,6 +96,18 @@ impl TryFrom<OrderBookIncrement> for CompleteOrderBook {
 }
 
 impl CompleteOrderBook {
+    /// REST snapshots carry no checksum so it is derived from the levels themselves
+    pub fn from_rest_snapshot(book: OrderBookIncrement) -> Self {
+        let mut result = CompleteOrderBook {
+            asks: book.asks,
+            bids: book.bids,
+            timestamp: book.timestamp,
+            checksum: 0,
+        };
+        result.checksum = result.calculate_checksum();
+        result
+    }
+
     #[allow(clippy::result_large_err)]
     fn verify_checksum(&self) -> Result<(), PriceFeedError> {
         let cs_res = self.calculate_checksum();
@@ -207,16 +219,33 @@ impl TryFrom<CompleteOrderBook> for OrderBookPayload {
 #[derive(Clone)]
 pub struct OrderBookCache {
     current: CompleteOrderBook,
+    reseeded_at: Option<TimeStamp>,
 }
 
 impl OrderBookCache {
     pub fn new(book: CompleteOrderBook) -> Self {
-        Self { current: book }
+        Self {
+            current: book,
+            reseeded_at: None,
+        }
+    }
+
+    /// Replaces the book with a REST snapshot. Increments older than the
+    /// snapshot are already reflected in it and get skipped.
+    pub fn reseed(&mut self, book: CompleteOrderBook) {
+        self.reseeded_at = Some(book.timestamp);
+        self.current = book;
     }
 
     #[allow(clippy::result_large_err)]
     pub fn update_order_book(&mut self, book: OrderBookIncrement) -> Result<(), PriceFeedError> {
+        if let Some(reseeded_at) = self.reseeded_at {
+            if book.action == OrderBookAction::Update && book.timestamp < reseeded_at {
+                return Ok(());
+            }
+        }
         self.current = self.current.try_merge(book)?;
+        self.reseeded_at = None;
         Ok(())
     }
 
@@ -262,4 +291,26 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn reseed_from_rest_snapshot() -> anyhow::Result<()> {
+        let snapshot = OrderBookIncrement::try_from(load_order_book("snapshot")?)?;
+        let expected_checksum = snapshot.new_checksum;
+        let rest_book = CompleteOrderBook::from_rest_snapshot(snapshot.clone());
+        assert_eq!(rest_book.checksum, expected_checksum);
+
+        let mut cache = OrderBookCache::new(snapshot.try_into()?);
+        cache.reseed(rest_book);
+
+        let mut stale = OrderBookIncrement::try_from(load_order_book("update-1")?)?;
+        stale.timestamp = TimeStamp::from(0);
+        assert!(cache.update_order_book(stale).is_ok());
+        assert_eq!(cache.latest().checksum, expected_checksum);
+
+        let incr_1 = OrderBookIncrement::try_from(load_order_book("update-1")?)?;
+        assert!(cache.update_order_book(incr_1.clone()).is_ok());
+        assert_eq!(cache.latest().checksum, incr_1.new_checksum);
+
+        Ok(())
+    }
 }
@@ -1,4 +1,6 @@
 mod book;
+mod resync;
+mod snapshot;
 
 use futures::{SinkExt, Stream, StreamExt};
 use std::pin::Pin;
@@ -7,9 +9,20 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 use url::Url;
 
 use super::error::*;
+use crate::recorder::FeedRecorder;
 pub use book::*;
+pub use resync::*;
+pub use snapshot::*;
+
+pub(crate) const BOOKS_CHANNEL: &str = "books";
 
 pub async fn subscribe_btc_usd_swap_order_book(
+) -> Result<Pin<Box<dyn Stream<Item = OkexOrderBook> + Send>>, PriceFeedError> {
+    subscribe_btc_usd_swap_order_book_recorded(None).await
+}
+
+pub(crate) async fn subscribe_btc_usd_swap_order_book_recorded(
+    recorder: Option<FeedRecorder>,
 ) -> Result<Pin<Box<dyn Stream<Item = OkexOrderBook> + Send>>, PriceFeedError> {
     let _ = Url::parse(super::OKEX_WS_URL).expect("invalid okex_ws_url");
     let request = super::OKEX_WS_URL.into_client_request()?;
@@ -20,7 +33,7 @@ pub async fn subscribe_btc_usd_swap_order_book(
         "op": "subscribe",
         "args": [
            {
-                "channel": "books",
+                "channel": BOOKS_CHANNEL,
                 "instId": "BTC-USD-SWAP"
             }
         ]
@@ -29,14 +42,20 @@ pub async fn subscribe_btc_usd_swap_order_book(
     let item = Message::from(subscribe_args);
     sender.send(item).await?;
 
-    Ok(Box::pin(receiver.filter_map(|message| async {
-        if let Ok(msg) = message {
-            if let Ok(msg_str) = msg.into_text() {
-                if let Ok(book) = serde_json::from_str::<OkexOrderBook>(&msg_str) {
-                    return Some(book);
+    Ok(Box::pin(receiver.filter_map(move |message| {
+        let recorder = recorder.clone();
+        async move {
+            if let Ok(msg) = message {
+                if let Ok(msg_str) = msg.into_text() {
+                    if let Some(recorder) = recorder {
+                        recorder.record(BOOKS_CHANNEL, &msg_str);
+                    }
+                    if let Ok(book) = serde_json::from_str::<OkexOrderBook>(&msg_str) {
+                        return Some(book);
+                    }
                 }
             }
+            None
         }
-        None
     })))
 }
@@ -0,0 +1,125 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use shared::{health::HealthCheckResponse, time::TimeStamp};
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::PriceFeedError;
+
+#[serde_with::serde_as]
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OrderBookResyncConfig {
+    /// REST re-seeds attempted on one subscription before it is torn down
+    #[serde(default = "default_max_consecutive_resyncs")]
+    pub max_consecutive_resyncs: usize,
+    #[serde(default = "default_unhealthy_resync_count")]
+    pub unhealthy_resync_count: usize,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_unhealthy_resync_window")]
+    pub unhealthy_resync_window: Duration,
+}
+
+impl Default for OrderBookResyncConfig {
+    fn default() -> Self {
+        Self {
+            max_consecutive_resyncs: default_max_consecutive_resyncs(),
+            unhealthy_resync_count: default_unhealthy_resync_count(),
+            unhealthy_resync_window: default_unhealthy_resync_window(),
+        }
+    }
+}
+
+fn default_max_consecutive_resyncs() -> usize {
+    3
+}
+fn default_unhealthy_resync_count() -> usize {
+    10
+}
+fn default_unhealthy_resync_window() -> Duration {
+    Duration::try_minutes(5).expect("valid duration")
+}
+
+#[derive(Clone)]
+pub struct OrderBookResyncs {
+    config: OrderBookResyncConfig,
+    recent: Arc<Mutex<VecDeque<TimeStamp>>>,
+    total: Arc<AtomicU64>,
+}
+
+impl OrderBookResyncs {
+    pub fn new(config: OrderBookResyncConfig) -> Self {
+        Self {
+            config,
+            recent: Arc::new(Mutex::new(VecDeque::new())),
+            total: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn config(&self) -> &OrderBookResyncConfig {
+        &self.config
+    }
+
+    pub fn record(&self, reason: &PriceFeedError) {
+        let resyncs_total = self.total.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut recent = self.recent.lock().expect("resync lock poisoned");
+        recent.push_back(TimeStamp::now());
+        self.prune(&mut recent);
+        tracing::warn!(
+            reason = %reason,
+            resyncs_total,
+            resyncs_in_window = recent.len(),
+            "okex_price.order_book.resync"
+        );
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::SeqCst)
+    }
+
+    pub fn healthy(&self) -> HealthCheckResponse {
+        let mut recent = self.recent.lock().expect("resync lock poisoned");
+        self.prune(&mut recent);
+        if recent.len() >= self.config.unhealthy_resync_count {
+            return Err(format!(
+                "{} okex order book resyncs in the last {} seconds",
+                recent.len(),
+                self.config.unhealthy_resync_window.num_seconds()
+            ));
+        }
+        Ok(())
+    }
+
+    fn prune(&self, recent: &mut VecDeque<TimeStamp>) {
+        while recent
+            .front()
+            .map(|ts| ts.duration_since() > self.config.unhealthy_resync_window)
+            .unwrap_or(false)
+        {
+            recent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unhealthy_after_too_many_resyncs() {
+        let resyncs = OrderBookResyncs::new(OrderBookResyncConfig {
+            unhealthy_resync_count: 2,
+            ..Default::default()
+        });
+        resyncs.record(&PriceFeedError::StreamStalled);
+        assert!(resyncs.healthy().is_ok());
+        resyncs.record(&PriceFeedError::CheckSumValidation);
+        assert!(resyncs.healthy().is_err());
+        assert_eq!(resyncs.total(), 2);
+    }
+}
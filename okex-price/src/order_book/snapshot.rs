@@ -0,0 +1,89 @@
+use serde::Deserialize;
+use shared::time::*;
+
+use std::collections::BTreeMap;
+
+use super::book::*;
+use crate::PriceFeedError;
+
+pub(crate) const OKEX_REST_URL: &str = "https://www.okx.com";
+const SNAPSHOT_DEPTH: usize = 400;
+
+#[derive(Debug, Deserialize)]
+struct OkexBooksResponse {
+    code: String,
+    msg: String,
+    data: Vec<OkexBooksData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OkexBooksData {
+    asks: Vec<PriceQuantity>,
+    bids: Vec<PriceQuantity>,
+    ts: TimeStampMilliStr,
+}
+
+/// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-get-order-book
+pub async fn fetch_btc_usd_swap_order_book_snapshot() -> Result<CompleteOrderBook, PriceFeedError> {
+    let url = format!(
+        "{}/api/v5/market/books?instId=BTC-USD-SWAP&sz={}",
+        OKEX_REST_URL, SNAPSHOT_DEPTH
+    );
+    let response = reqwest::get(url).await?.text().await?;
+    snapshot_from_response(&response)
+}
+
+#[allow(clippy::result_large_err)]
+fn snapshot_from_response(response: &str) -> Result<CompleteOrderBook, PriceFeedError> {
+    let response = serde_json::from_str::<OkexBooksResponse>(response)?;
+    if response.code != "0" {
+        return Err(PriceFeedError::RestSnapshot(format!(
+            "{} - {}",
+            response.code, response.msg
+        )));
+    }
+    let data = response
+        .data
+        .into_iter()
+        .next()
+        .ok_or(PriceFeedError::EmptyOrderBookData)?;
+
+    let mut asks = BTreeMap::new();
+    for ask in data.asks {
+        let _ = asks.insert(OrderPrice::from(ask.price), ask.quantity);
+    }
+    let mut bids = BTreeMap::new();
+    for bid in data.bids {
+        let _ = bids.insert(OrderPrice::from(bid.price), bid.quantity);
+    }
+
+    Ok(CompleteOrderBook::from_rest_snapshot(OrderBookIncrement {
+        asks,
+        bids,
+        timestamp: TimeStamp::try_from(&data.ts)?,
+        new_checksum: 0,
+        action: OrderBookAction::Snapshot,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rest_snapshot() -> anyhow::Result<()> {
+        let response = r#"{"code":"0","msg":"","data":[{"asks":[["20276.1","1393","0","10"]],"bids":[["20276","410","0","4"]],"ts":"1666759645503"}]}"#;
+        let book = snapshot_from_response(response)?;
+        assert!(shared::payload::OrderBookPayload::try_from(book).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_error_code() {
+        let response = r#"{"code":"51001","msg":"Instrument ID does not exist","data":[]}"#;
+        assert!(matches!(
+            snapshot_from_response(response),
+            Err(PriceFeedError::RestSnapshot(_))
+        ));
+    }
+}
@@ -1,17 +1,62 @@
+mod reference;
 mod tick;
 
 use futures::{SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use url::Url;
 
+use std::pin::Pin;
+
 pub use crate::error::*;
+use crate::recorder::FeedRecorder;
+pub use reference::*;
 pub use tick::*;
 
 pub(crate) const OKEX_WS_URL: &str = "wss://ws.okx.com:8443/ws/v5/public";
+pub(crate) const TICKERS_CHANNEL: &str = "tickers";
+pub(crate) const MARK_PRICE_CHANNEL: &str = "mark-price";
+pub(crate) const INDEX_TICKERS_CHANNEL: &str = "index-tickers";
 
 pub async fn subscribe_btc_usd_swap_price_tick(
-) -> Result<std::pin::Pin<Box<dyn Stream<Item = OkexPriceTick> + Send>>, PriceFeedError> {
+) -> Result<Pin<Box<dyn Stream<Item = OkexPriceTick> + Send>>, PriceFeedError> {
+    subscribe_btc_usd_swap_price_tick_recorded(None).await
+}
+
+pub(crate) async fn subscribe_btc_usd_swap_price_tick_recorded(
+    recorder: Option<FeedRecorder>,
+) -> Result<Pin<Box<dyn Stream<Item = OkexPriceTick> + Send>>, PriceFeedError> {
+    subscribe_channel(TICKERS_CHANNEL, "BTC-USD-SWAP", recorder).await
+}
+
+pub async fn subscribe_btc_usd_swap_mark_price(
+) -> Result<Pin<Box<dyn Stream<Item = OkexMarkPrice> + Send>>, PriceFeedError> {
+    subscribe_btc_usd_swap_mark_price_recorded(None).await
+}
+
+pub(crate) async fn subscribe_btc_usd_swap_mark_price_recorded(
+    recorder: Option<FeedRecorder>,
+) -> Result<Pin<Box<dyn Stream<Item = OkexMarkPrice> + Send>>, PriceFeedError> {
+    subscribe_channel(MARK_PRICE_CHANNEL, "BTC-USD-SWAP", recorder).await
+}
+
+pub async fn subscribe_btc_usd_index_price(
+) -> Result<Pin<Box<dyn Stream<Item = OkexIndexTicker> + Send>>, PriceFeedError> {
+    subscribe_btc_usd_index_price_recorded(None).await
+}
+
+pub(crate) async fn subscribe_btc_usd_index_price_recorded(
+    recorder: Option<FeedRecorder>,
+) -> Result<Pin<Box<dyn Stream<Item = OkexIndexTicker> + Send>>, PriceFeedError> {
+    subscribe_channel(INDEX_TICKERS_CHANNEL, "BTC-USD", recorder).await
+}
+
+async fn subscribe_channel<T: DeserializeOwned + Send + 'static>(
+    channel: &'static str,
+    inst_id: &'static str,
+    recorder: Option<FeedRecorder>,
+) -> Result<Pin<Box<dyn Stream<Item = T> + Send>>, PriceFeedError> {
     let _ = Url::parse(OKEX_WS_URL).expect("invalid okex_ws_url");
     let request = OKEX_WS_URL.into_client_request()?;
     let (ws_stream, _) = connect_async(request).await?;
@@ -21,8 +66,8 @@ pub async fn subscribe_btc_usd_swap_price_tick(
         "op": "subscribe",
         "args": [
            {
-                "channel": "tickers",
-                "instId": "BTC-USD-SWAP"
+                "channel": channel,
+                "instId": inst_id
             }
         ]
     })
@@ -31,14 +76,20 @@ pub async fn subscribe_btc_usd_swap_price_tick(
 
     sender.send(item).await?;
 
-    Ok(Box::pin(receiver.filter_map(|message| async {
-        if let Ok(msg) = message {
-            if let Ok(msg_str) = msg.into_text() {
-                if let Ok(tick) = serde_json::from_str::<OkexPriceTick>(&msg_str) {
-                    return Some(tick);
+    Ok(Box::pin(receiver.filter_map(move |message| {
+        let recorder = recorder.clone();
+        async move {
+            if let Ok(msg) = message {
+                if let Ok(msg_str) = msg.into_text() {
+                    if let Some(recorder) = recorder {
+                        recorder.record(channel, &msg_str);
+                    }
+                    if let Ok(item) = serde_json::from_str::<T>(&msg_str) {
+                        return Some(item);
+                    }
                 }
             }
+            None
         }
-        None
     })))
 }
@@ -0,0 +1,35 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::okex_shared::*;
+use shared::time::*;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkPriceChannelData {
+    pub inst_id: String,
+    pub mark_px: Decimal,
+    pub ts: TimeStampMilliStr,
+}
+
+/// https://www.okx.com/docs-v5/en/#public-data-websocket-mark-price-channel
+#[derive(Clone, Deserialize, Debug)]
+pub struct OkexMarkPrice {
+    pub arg: ChannelArgs,
+    pub data: Vec<MarkPriceChannelData>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexTickerChannelData {
+    pub inst_id: String,
+    pub idx_px: Decimal,
+    pub ts: TimeStampMilliStr,
+}
+
+/// https://www.okx.com/docs-v5/en/#public-data-websocket-index-tickers-channel
+#[derive(Clone, Deserialize, Debug)]
+pub struct OkexIndexTicker {
+    pub arg: ChannelArgs,
+    pub data: Vec<IndexTickerChannelData>,
+}
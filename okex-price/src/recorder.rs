@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::error::PriceFeedError;
+
+const RECORDING_EXTENSION: &str = "jsonl";
+
+/// A raw websocket frame as it was received from the exchange
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFrame {
+    pub received_at: DateTime<Utc>,
+    pub channel: String,
+    pub frame: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedRecorderConfig {
+    pub dir: PathBuf,
+    #[serde(default = "default_file_prefix")]
+    pub file_prefix: String,
+    #[serde(default = "default_max_file_size_bytes")]
+    pub max_file_size_bytes: u64,
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+fn default_file_prefix() -> String {
+    "okex-feed".to_string()
+}
+fn default_max_file_size_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+fn default_max_files() -> usize {
+    24
+}
+
+/// Appends raw frames to rotating JSONL files from a blocking background task
+/// so the websocket streams never wait on disk io.
+#[derive(Clone)]
+pub struct FeedRecorder {
+    sender: mpsc::UnboundedSender<RecordedFrame>,
+}
+
+impl FeedRecorder {
+    pub fn start(config: FeedRecorderConfig) -> Result<Self, PriceFeedError> {
+        fs::create_dir_all(&config.dir)?;
+        let (sender, mut receiver) = mpsc::unbounded_channel::<RecordedFrame>();
+        let mut writer = RotatingWriter::new(config);
+        tokio::task::spawn_blocking(move || {
+            while let Some(frame) = receiver.blocking_recv() {
+                if let Err(e) = writer.write(&frame) {
+                    tracing::error!(error = %e, "okex_price.recorder.write_failed");
+                }
+            }
+        });
+        Ok(Self { sender })
+    }
+
+    pub fn record(&self, channel: &str, frame: &str) {
+        let _ = self.sender.send(RecordedFrame {
+            received_at: Utc::now(),
+            channel: channel.to_string(),
+            frame: frame.to_string(),
+        });
+    }
+}
+
+struct RotatingWriter {
+    config: FeedRecorderConfig,
+    current: Option<BufWriter<File>>,
+    bytes_written: u64,
+}
+
+impl RotatingWriter {
+    fn new(config: FeedRecorderConfig) -> Self {
+        Self {
+            config,
+            current: None,
+            bytes_written: 0,
+        }
+    }
+
+    fn write(&mut self, frame: &RecordedFrame) -> Result<(), PriceFeedError> {
+        if self.current.is_none() || self.bytes_written >= self.config.max_file_size_bytes {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_string(frame)?;
+        line.push('\n');
+        let file = self.current.as_mut().expect("file opened by rotate");
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+        self.bytes_written += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<(), PriceFeedError> {
+        if let Some(mut file) = self.current.take() {
+            file.flush()?;
+        }
+        let path = self.config.dir.join(format!(
+            "{}-{}.{}",
+            self.config.file_prefix,
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            RECORDING_EXTENSION
+        ));
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.current = Some(BufWriter::new(file));
+        self.bytes_written = 0;
+        self.prune()
+    }
+
+    fn prune(&self) -> Result<(), PriceFeedError> {
+        let mut recordings = recordings_in(&self.config.dir, &self.config.file_prefix)?;
+        while recordings.len() > self.config.max_files.max(1) {
+            fs::remove_file(recordings.remove(0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Recording files in `dir` ordered from oldest to newest
+pub fn recordings_in(dir: &Path, file_prefix: &str) -> Result<Vec<PathBuf>, PriceFeedError> {
+    let mut recordings = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_recording = path.extension().map(|ext| ext == RECORDING_EXTENSION) == Some(true)
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with(file_prefix))
+                .unwrap_or(false);
+        if is_recording {
+            recordings.push(path);
+        }
+    }
+    recordings.sort();
+    Ok(recordings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "okex-price-{}-{}-{}",
+            name,
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn frame(n: usize) -> RecordedFrame {
+        RecordedFrame {
+            received_at: Utc::now(),
+            channel: "tickers".to_string(),
+            frame: format!("{{\"n\":{}}}", n),
+        }
+    }
+
+    #[test]
+    fn rotates_and_prunes_recordings() -> anyhow::Result<()> {
+        let dir = scratch_dir("rotate");
+        let mut writer = RotatingWriter::new(FeedRecorderConfig {
+            dir: dir.clone(),
+            file_prefix: default_file_prefix(),
+            max_file_size_bytes: 1,
+            max_files: 2,
+        });
+        for n in 0..4 {
+            writer.write(&frame(n))?;
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let recordings = recordings_in(&dir, &default_file_prefix())?;
+        assert_eq!(recordings.len(), 2);
+        let last: RecordedFrame =
+            serde_json::from_str(fs::read_to_string(&recordings[1])?.trim_end())?;
+        assert_eq!(last.frame, frame(3).frame);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
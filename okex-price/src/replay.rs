@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncBufReadExt;
+
+use std::path::{Path, PathBuf};
+
+use shared::{payload::*, pubsub::*};
+
+use crate::{
+    error::PriceFeedError,
+    okex_index_price_received, okex_mark_price_received, okex_order_book_received,
+    okex_price_tick_received,
+    order_book::*,
+    price_feed::*,
+    recorder::{recordings_in, RecordedFrame},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedReplayConfig {
+    /// A single recording or a directory of rotated recordings
+    pub path: PathBuf,
+    #[serde(default = "default_file_prefix")]
+    pub file_prefix: String,
+    /// 1.0 replays at the recorded pace, 0 replays as fast as possible
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// Move exchange timestamps to replay time so downstream staleness checks pass
+    #[serde(default = "bool_true")]
+    pub shift_timestamps: bool,
+}
+
+impl FeedReplayConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file_prefix: default_file_prefix(),
+            speed: default_speed(),
+            shift_timestamps: true,
+        }
+    }
+}
+
+fn default_file_prefix() -> String {
+    "okex-feed".to_string()
+}
+fn default_speed() -> f64 {
+    1.0
+}
+fn bool_true() -> bool {
+    true
+}
+
+/// Feeds recorded frames through the same conversion path as the live subscriptions
+pub async fn replay(
+    publisher: memory::Publisher<PriceStreamPayload>,
+    config: FeedReplayConfig,
+) -> Result<(), PriceFeedError> {
+    let files = if config.path.is_dir() {
+        recordings_in(&config.path, &config.file_prefix)?
+    } else {
+        vec![config.path.clone()]
+    };
+
+    let mut replayer = Replayer::new(publisher, config);
+    for file in files {
+        replayer.replay_file(&file).await?;
+    }
+    Ok(())
+}
+
+struct Replayer {
+    publisher: memory::Publisher<PriceStreamPayload>,
+    config: FeedReplayConfig,
+    started: Option<(tokio::time::Instant, DateTime<Utc>)>,
+    cache: Option<OrderBookCache>,
+}
+
+impl Replayer {
+    fn new(publisher: memory::Publisher<PriceStreamPayload>, config: FeedReplayConfig) -> Self {
+        Self {
+            publisher,
+            config,
+            started: None,
+            cache: None,
+        }
+    }
+
+    async fn replay_file(&mut self, path: &Path) -> Result<(), PriceFeedError> {
+        let file = tokio::fs::File::open(path).await?;
+        let mut lines = tokio::io::BufReader::new(file).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recorded = serde_json::from_str::<RecordedFrame>(&line)?;
+            self.wait_for(recorded.received_at).await;
+            self.dispatch(recorded).await?;
+        }
+        Ok(())
+    }
+
+    async fn wait_for(&mut self, received_at: DateTime<Utc>) {
+        let (started_at, first_received_at) = *self
+            .started
+            .get_or_insert((tokio::time::Instant::now(), received_at));
+        if self.config.speed <= 0.0 {
+            return;
+        }
+        if let Ok(offset) = (received_at - first_received_at).to_std() {
+            tokio::time::sleep_until(started_at + offset.div_f64(self.config.speed)).await;
+        }
+    }
+
+    async fn dispatch(&mut self, recorded: RecordedFrame) -> Result<(), PriceFeedError> {
+        let mut frame = serde_json::from_str::<serde_json::Value>(&recorded.frame)?;
+        if self.config.shift_timestamps {
+            shift_timestamps(&mut frame, recorded.received_at);
+        }
+        match recorded.channel.as_str() {
+            TICKERS_CHANNEL => {
+                if let Ok(tick) = serde_json::from_value::<OkexPriceTick>(frame) {
+                    okex_price_tick_received(&self.publisher, tick).await?;
+                }
+            }
+            MARK_PRICE_CHANNEL => {
+                if let Ok(mark_price) = serde_json::from_value::<OkexMarkPrice>(frame) {
+                    okex_mark_price_received(&self.publisher, mark_price).await?;
+                }
+            }
+            INDEX_TICKERS_CHANNEL => {
+                if let Ok(index_price) = serde_json::from_value::<OkexIndexTicker>(frame) {
+                    okex_index_price_received(&self.publisher, index_price).await?;
+                }
+            }
+            BOOKS_CHANNEL => {
+                if let Ok(book) = serde_json::from_value::<OkexOrderBook>(frame) {
+                    self.order_book_received(book).await?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    async fn order_book_received(&mut self, book: OkexOrderBook) -> Result<(), PriceFeedError> {
+        let result = match self.cache.as_mut() {
+            Some(cache) => okex_order_book_received(&self.publisher, book, cache).await,
+            None if book.action == OrderBookAction::Snapshot => OrderBookIncrement::try_from(book)
+                .and_then(CompleteOrderBook::try_from)
+                .map(|order_book| self.cache = Some(OrderBookCache::new(order_book))),
+            None => Ok(()),
+        };
+        match result {
+            Err(PriceFeedError::CheckSumValidation) => {
+                tracing::warn!("okex_price.replay.checksum_mismatch - waiting for next snapshot");
+                self.cache = None;
+                Ok(())
+            }
+            res => res,
+        }
+    }
+}
+
+/// Keeps the original exchange-to-receive latency but anchors it at the current time
+fn shift_timestamps(frame: &mut serde_json::Value, received_at: DateTime<Utc>) {
+    let now = Utc::now();
+    if let Some(data) = frame.get_mut("data").and_then(|d| d.as_array_mut()) {
+        for item in data {
+            if let Some(ts) = item.get_mut("ts") {
+                if let Some(millis) = ts.as_str().and_then(|ts| ts.parse::<i64>().ok()) {
+                    let latency = received_at.timestamp_millis() - millis;
+                    *ts = serde_json::Value::String((now.timestamp_millis() - latency).to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifts_timestamps_keeping_latency() {
+        let received_at = DateTime::from_timestamp_millis(1_000_500).unwrap();
+        let mut frame = serde_json::json!({ "data": [{ "ts": "1000000" }] });
+
+        shift_timestamps(&mut frame, received_at);
+
+        let shifted = frame["data"][0]["ts"]
+            .as_str()
+            .unwrap()
+            .parse::<i64>()
+            .unwrap();
+        let latency = Utc::now().timestamp_millis() - shifted;
+        assert!((500..1500).contains(&latency));
+    }
+}
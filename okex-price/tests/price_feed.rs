@@ -50,13 +50,40 @@ async fn subscribe_to_order_book_channel() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn subscribes_to_mark_price_channel() -> anyhow::Result<()> {
+    let mut received = subscribe_btc_usd_swap_mark_price()
+        .await
+        .expect("subscribe_btc_usd_swap_mark_price");
+    let mark_price = received.next().await.expect("expected mark price");
+
+    assert_eq!(
+        mark_price.arg,
+        ChannelArgs {
+            channel: "mark-price".to_string(),
+            inst_id: "BTC-USD-SWAP".to_string(),
+        }
+    );
+    assert_eq!(mark_price.data.len(), 1);
+    assert!(mark_price.data[0].mark_px > rust_decimal::Decimal::ZERO);
+    Ok(())
+}
+
 #[tokio::test]
 async fn publishes_to_price_stream() -> anyhow::Result<()> {
     let (tick_send, mut tick_recv) =
         memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
 
+    let (_, recv) = futures::channel::mpsc::unbounded();
+
     let _ = tokio::spawn(async move {
-        let _res = okex_price::run(tick_send, std::time::Duration::from_secs(20)).await;
+        let _res = okex_price::run(
+            tick_send,
+            recv,
+            std::time::Duration::from_secs(20),
+            OkexPriceFeedConfig::default(),
+        )
+        .await;
     });
 
     let recv = tick_recv.next().await.expect("expected price tick");
@@ -74,3 +101,29 @@ async fn publishes_to_price_stream() -> anyhow::Result<()> {
     ));
     Ok(())
 }
+
+#[tokio::test]
+async fn replays_recorded_feed() -> anyhow::Result<()> {
+    let (tick_send, mut tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+
+    let config = FeedReplayConfig::new("./tests/fixtures/recorded-feed.jsonl");
+    let replay = tokio::spawn(okex_price::replay(tick_send, config));
+
+    let recv = tick_recv.next().await.expect("expected price tick");
+    assert!(matches!(
+        recv.payload,
+        PriceStreamPayload::OkexBtcSwapPricePayload(_)
+    ));
+
+    let recv = tick_recv.next().await.expect("expected order book");
+    if let PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(book) = recv.payload {
+        assert!(book.timestamp.duration_since() < Duration::try_seconds(30).unwrap());
+        assert!(!book.asks.is_empty() && !book.bids.is_empty());
+    } else {
+        panic!("expected order book");
+    }
+
+    replay.await??;
+    Ok(())
+}
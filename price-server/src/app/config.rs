@@ -1,11 +1,6 @@
 use chrono::Duration;
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-pub struct ExchangeWeights {
-    pub okex: Option<Decimal>,
-}
-
 #[serde_with::serde_as]
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PriceServerHealthCheckConfig {
@@ -1,195 +1,277 @@
 mod config;
 
+use chrono::{DateTime, Duration, Utc};
 use futures::stream::StreamExt;
 use rust_decimal::Decimal;
-use tracing::{instrument, trace_span, Instrument};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::instrument;
 
+use pricing::{
+    subscribe_price_mixer, ConversionResult, ExchangePriceCacheConfig, PriceCalculator,
+    PriceMetadata, PriceMixer, PriceMixerConfig,
+};
 use shared::{
-    health::HealthCheckTrigger,
-    payload::{PriceStreamPayload, OKEX_EXCHANGE_ID},
+    health::{HealthCheckTrigger, HealthDetailsTrigger},
+    payload::PriceStreamPayload,
     pubsub::*,
 };
 
-use crate::{cache_config::ExchangePriceCacheConfig, price_mixer::PriceMixer, OrderBookCache};
+use crate::{
+    fx::FxRates,
+    price_history::{Candle, CandleResolution, PriceHistory, PriceHistoryError},
+};
 
-pub use crate::{currency::*, error::*, fee_calculator::*};
+pub use crate::{
+    error::*,
+    fx::{FxError, FxRate},
+};
 pub use config::*;
+pub use pricing::{
+    currency::*, fee_calculator::*, ExchangePriceCacheError, ExchangeWeights, Execution,
+};
+
+/// A converted amount along with the fee and the exchange snapshots it was derived from
+#[derive(Clone, Debug)]
+pub struct PriceConversion<T> {
+    pub amount: T,
+    pub pre_fee_amount: T,
+    pub fee_rate: Decimal,
+    pub metadata: PriceMetadata,
+}
+
+impl From<ConversionResult> for PriceConversion<UsdCents> {
+    fn from(res: ConversionResult) -> Self {
+        Self {
+            amount: res.cents,
+            pre_fee_amount: res.pre_fee_cents,
+            fee_rate: res.fee_rate,
+            metadata: res.metadata,
+        }
+    }
+}
+
+impl From<ConversionResult> for PriceConversion<Sats> {
+    fn from(res: ConversionResult) -> Self {
+        Self {
+            amount: res.sats,
+            pre_fee_amount: res.pre_fee_sats,
+            fee_rate: res.fee_rate,
+            metadata: res.metadata,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExchangeMidRate {
+    pub cents_per_sat: f64,
+    pub metadata: PriceMetadata,
+}
+
+/// A conversion into or out of a non-USD fiat along with the FX rate it was priced at
+#[derive(Clone, Debug)]
+pub struct FiatPriceConversion<T> {
+    pub conversion: PriceConversion<T>,
+    pub fx_rate: FxRate,
+}
+
+#[derive(Clone, Debug)]
+pub struct FiatMidRate {
+    pub fiat_minor_units_per_sat: f64,
+    pub fx_rate: FxRate,
+    pub metadata: PriceMetadata,
+}
+
+/// Same semantics as the `buy`/`sell` variants of the USD conversions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConversionSide {
+    Buy,
+    Sell,
+}
+
+/// A zero duration leaves the time of a future execution unspecified
+pub fn future_execution(duration: Duration) -> Execution {
+    Execution::Future(Some(duration).filter(|duration| *duration > Duration::zero()))
+}
 
 pub struct PriceApp {
-    price_mixer: PriceMixer,
-    fee_calculator: FeeCalculator,
+    price_mixer: Arc<PriceMixer>,
+    calculator: PriceCalculator,
+    price_updated: broadcast::Sender<()>,
+    history: Option<PriceHistory>,
+    fx: Option<FxRates>,
 }
 
 impl PriceApp {
+    #[allow(clippy::too_many_arguments)]
     pub async fn run(
         mut health_check_trigger: HealthCheckTrigger,
+        mut health_details_trigger: HealthDetailsTrigger,
         health_check_cfg: PriceServerHealthCheckConfig,
         fee_calc_cfg: FeeCalculatorConfig,
         subscriber: memory::Subscriber<PriceStreamPayload>,
         price_cache_config: ExchangePriceCacheConfig,
+        mixer_config: PriceMixerConfig,
         exchange_weights: ExchangeWeights,
+        history: Option<PriceHistory>,
+        fx: Option<FxRates>,
     ) -> Result<Self, PriceAppError> {
+        let (price_updated, _) = broadcast::channel(1);
+        let (price_mixer, volatility) = subscribe_price_mixer(
+            &subscriber,
+            &price_cache_config,
+            mixer_config,
+            &exchange_weights,
+            Some(price_updated.clone()),
+        );
+
+        let mixer_health = price_mixer.health();
+        tokio::spawn(async move {
+            while let Some(request) = health_details_trigger.next().await {
+                let _ = request.send(mixer_health.details().await);
+            }
+        });
+
         let health_subscriber = subscriber.resubscribe();
+        let mixer_health = price_mixer.health();
+        let fx_health = fx.clone();
         tokio::spawn(async move {
             while let Some(check) = health_check_trigger.next().await {
-                let _ = check.send(
-                    health_subscriber
-                        .healthy(health_check_cfg.unhealthy_msg_interval_price)
-                        .await,
-                );
+                let res = match health_subscriber
+                    .healthy(health_check_cfg.unhealthy_msg_interval_price)
+                    .await
+                {
+                    Ok(()) => mixer_health.healthy().await,
+                    err => err,
+                };
+                let res = match (res, fx_health.as_ref()) {
+                    (Ok(()), Some(fx)) => fx.healthy().await,
+                    (res, _) => res,
+                };
+                let _ = check.send(res);
             }
         });
 
-        let mut price_mixer = PriceMixer::new();
-
-        if let Some(weight) = exchange_weights.okex {
-            if weight > Decimal::ZERO {
-                let okex_order_book_cache = OrderBookCache::new(price_cache_config.clone());
-                Self::subscribe_okex(subscriber.resubscribe(), okex_order_book_cache.clone())
-                    .await?;
-                price_mixer.add_provider(OKEX_EXCHANGE_ID, okex_order_book_cache, weight);
-            }
+        let price_mixer = Arc::new(price_mixer);
+        if let Some(history) = history.as_ref() {
+            history.spawn_recorder(Arc::clone(&price_mixer), price_updated.subscribe());
         }
 
-        let fee_calculator = FeeCalculator::new(fee_calc_cfg);
+        let calculator = PriceCalculator::new(fee_calc_cfg, Arc::clone(&price_mixer), volatility);
         let app = Self {
             price_mixer,
-            fee_calculator,
+            calculator,
+            price_updated,
+            history,
+            fx,
         };
 
         Ok(app)
     }
 
-    async fn subscribe_okex(
-        mut subscriber: memory::Subscriber<PriceStreamPayload>,
-        order_book_cache: OrderBookCache,
-    ) -> Result<(), PriceAppError> {
-        tokio::spawn(async move {
-            while let Some(msg) = subscriber.next().await {
-                if let PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(price_msg) = msg.payload {
-                    let span = trace_span!(
-                        "price_server.okex_order_book_received",
-                        message_type = %msg.payload_type,
-                        correlation_id = %msg.meta.correlation_id
-                    );
-                    shared::tracing::inject_tracing_data(&span, &msg.meta.tracing_data);
-                    async {
-                        order_book_cache.apply_update(price_msg).await;
-                    }
-                    .instrument(span)
-                    .await;
-                }
-            }
-        });
-
-        Ok(())
+    /// Notifies receivers whenever one of the order book caches was updated
+    pub fn subscribe_price_updates(&self) -> broadcast::Receiver<()> {
+        self.price_updated.subscribe()
     }
 
     #[instrument(name = "price_server.get_cents_from_sats_for_immediate_buy", skip_all, fields(correlation_id, amount = %sats.amount()), ret, err)]
     pub async fn get_cents_from_sats_for_immediate_buy(
         &self,
         sats: Sats,
-    ) -> Result<UsdCents, PriceAppError> {
-        let cents = UsdCents::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.buy_usd().cents_from_sats(sats.clone()).amount())
-                .await?,
-        );
-
-        Ok(self.fee_calculator.decrease_by_immediate_fee(cents).floor())
+    ) -> Result<PriceConversion<UsdCents>, PriceAppError> {
+        Ok(self
+            .calculator
+            .cents_from_sats_for_buy(sats, Execution::Immediate)
+            .await?
+            .into())
     }
 
     #[instrument(name = "price_server.get_cents_from_sats_for_immediate_sell", skip_all, fields(correlation_id, amount = %sats.amount()), ret, err)]
     pub async fn get_cents_from_sats_for_immediate_sell(
         &self,
         sats: Sats,
-    ) -> Result<UsdCents, PriceAppError> {
-        let cents = UsdCents::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.sell_usd().cents_from_sats(sats.clone()).amount())
-                .await?,
-        );
-        Ok(self.fee_calculator.increase_by_immediate_fee(cents).ceil())
+    ) -> Result<PriceConversion<UsdCents>, PriceAppError> {
+        Ok(self
+            .calculator
+            .cents_from_sats_for_sell(sats, Execution::Immediate)
+            .await?
+            .into())
     }
 
-    #[instrument(name = "price_server.get_cents_from_sats_for_future_buy", skip_all, fields(correlation_id, amount = %sats.amount()), ret, err)]
+    #[instrument(name = "price_server.get_cents_from_sats_for_future_buy", skip_all, fields(correlation_id, amount = %sats.amount(), duration = %duration), ret, err)]
     pub async fn get_cents_from_sats_for_future_buy(
         &self,
         sats: Sats,
-    ) -> Result<UsdCents, PriceAppError> {
-        let cents = UsdCents::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.buy_usd().cents_from_sats(sats.clone()).amount())
-                .await?,
-        );
-        Ok(self.fee_calculator.decrease_by_delayed_fee(cents).floor())
+        duration: Duration,
+    ) -> Result<PriceConversion<UsdCents>, PriceAppError> {
+        Ok(self
+            .calculator
+            .cents_from_sats_for_buy(sats, future_execution(duration))
+            .await?
+            .into())
     }
 
-    #[instrument(name = "price_server.get_cents_from_sats_for_future_sell", skip_all, fields(correlation_id, amount = %sats.amount()), ret, err)]
+    #[instrument(name = "price_server.get_cents_from_sats_for_future_sell", skip_all, fields(correlation_id, amount = %sats.amount(), duration = %duration), ret, err)]
     pub async fn get_cents_from_sats_for_future_sell(
         &self,
         sats: Sats,
-    ) -> Result<UsdCents, PriceAppError> {
-        let cents = UsdCents::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.sell_usd().cents_from_sats(sats.clone()).amount())
-                .await?,
-        );
-        Ok(self.fee_calculator.increase_by_delayed_fee(cents).ceil())
+        duration: Duration,
+    ) -> Result<PriceConversion<UsdCents>, PriceAppError> {
+        Ok(self
+            .calculator
+            .cents_from_sats_for_sell(sats, future_execution(duration))
+            .await?
+            .into())
     }
 
     #[instrument(name = "price_server.get_sats_from_cents_for_immediate_buy", skip_all, fields(correlation_id, amount = %cents.amount()), ret, err)]
     pub async fn get_sats_from_cents_for_immediate_buy(
         &self,
         cents: UsdCents,
-    ) -> Result<Sats, PriceAppError> {
-        let sats = Sats::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.buy_usd().sats_from_cents(cents.clone()).amount())
-                .await?,
-        );
-        Ok(self.fee_calculator.increase_by_immediate_fee(sats).ceil())
+    ) -> Result<PriceConversion<Sats>, PriceAppError> {
+        Ok(self
+            .calculator
+            .sats_from_cents_for_buy(cents, Execution::Immediate)
+            .await?
+            .into())
     }
 
     #[instrument(name = "price_server.get_sats_from_cents_for_immediate_sell", skip_all, fields(correlation_id, amount = %cents.amount()), ret, err)]
     pub async fn get_sats_from_cents_for_immediate_sell(
         &self,
         cents: UsdCents,
-    ) -> Result<Sats, PriceAppError> {
-        let sats = Sats::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.sell_usd().sats_from_cents(cents.clone()).amount())
-                .await?,
-        );
-
-        Ok(self.fee_calculator.decrease_by_immediate_fee(sats).floor())
+    ) -> Result<PriceConversion<Sats>, PriceAppError> {
+        Ok(self
+            .calculator
+            .sats_from_cents_for_sell(cents, Execution::Immediate)
+            .await?
+            .into())
     }
 
-    #[instrument(name = "price_server.get_sats_from_cents_for_future_buy", skip_all, fields(correlation_id, amount = %cents.amount()), ret, err)]
+    #[instrument(name = "price_server.get_sats_from_cents_for_future_buy", skip_all, fields(correlation_id, amount = %cents.amount(), duration = %duration), ret, err)]
     pub async fn get_sats_from_cents_for_future_buy(
         &self,
         cents: UsdCents,
-    ) -> Result<Sats, PriceAppError> {
-        let sats = Sats::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.buy_usd().sats_from_cents(cents.clone()).amount())
-                .await?,
-        );
-
-        Ok(self.fee_calculator.increase_by_delayed_fee(sats).ceil())
+        duration: Duration,
+    ) -> Result<PriceConversion<Sats>, PriceAppError> {
+        Ok(self
+            .calculator
+            .sats_from_cents_for_buy(cents, future_execution(duration))
+            .await?
+            .into())
     }
 
-    #[instrument(name = "price_server.get_sats_from_cents_for_future_sell", skip_all, fields(correlation_id, amount = %cents.amount()), ret, err)]
+    #[instrument(name = "price_server.get_sats_from_cents_for_future_sell", skip_all, fields(correlation_id, amount = %cents.amount(), duration = %duration), ret, err)]
     pub async fn get_sats_from_cents_for_future_sell(
         &self,
         cents: UsdCents,
-    ) -> Result<Sats, PriceAppError> {
-        let sats = Sats::from_decimal(
-            self.price_mixer
-                .apply(|p| *p.sell_usd().sats_from_cents(cents.clone()).amount())
-                .await?,
-        );
-        Ok(self.fee_calculator.decrease_by_delayed_fee(sats).floor())
+        duration: Duration,
+    ) -> Result<PriceConversion<Sats>, PriceAppError> {
+        Ok(self
+            .calculator
+            .sats_from_cents_for_sell(cents, future_execution(duration))
+            .await?
+            .into())
     }
 
     #[instrument(
@@ -199,11 +281,109 @@ impl PriceApp {
         ret,
         err
     )]
-    pub async fn get_cents_per_sat_exchange_mid_rate(&self) -> Result<f64, PriceAppError> {
-        let cents_per_sat = self
+    pub async fn get_cents_per_sat_exchange_mid_rate(
+        &self,
+    ) -> Result<ExchangeMidRate, PriceAppError> {
+        let (cents_per_sat, metadata) = self
             .price_mixer
-            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .apply_with_metadata(|p| *p.mid_price_of_one_sat().amount())
             .await?;
-        Ok(f64::try_from(cents_per_sat)?)
+        Ok(ExchangeMidRate {
+            cents_per_sat: f64::try_from(cents_per_sat)?,
+            metadata,
+        })
+    }
+
+    #[instrument(name = "price_server.get_fiat_from_sats", skip_all, fields(correlation_id, currency = %currency, amount = %sats.amount(), ?side, ?execution), ret, err)]
+    pub async fn get_fiat_from_sats(
+        &self,
+        currency: FiatCurrency,
+        sats: Sats,
+        side: ConversionSide,
+        execution: Execution,
+    ) -> Result<FiatPriceConversion<FiatCents>, PriceAppError> {
+        let fx_rate = self.fx_rate(&currency).await?;
+        let res = self
+            .calculator
+            .fiat_from_sats(
+                sats,
+                side == ConversionSide::Buy,
+                execution,
+                currency.minor_units_per_usd_cent(fx_rate.fiat_per_usd),
+            )
+            .await?;
+        Ok(FiatPriceConversion {
+            conversion: PriceConversion {
+                amount: FiatCents::from_decimal(currency.clone(), res.amount),
+                pre_fee_amount: FiatCents::from_decimal(currency, res.pre_fee_amount),
+                fee_rate: res.fee_rate,
+                metadata: res.metadata,
+            },
+            fx_rate,
+        })
+    }
+
+    #[instrument(name = "price_server.get_sats_from_fiat", skip_all, fields(correlation_id, currency = %fiat.currency(), amount = %fiat.amount(), ?side, ?execution), ret, err)]
+    pub async fn get_sats_from_fiat(
+        &self,
+        fiat: FiatCents,
+        side: ConversionSide,
+        execution: Execution,
+    ) -> Result<FiatPriceConversion<Sats>, PriceAppError> {
+        let fx_rate = self.fx_rate(fiat.currency()).await?;
+        let res = self
+            .calculator
+            .sats_from_fiat(
+                *fiat.amount(),
+                side == ConversionSide::Buy,
+                execution,
+                fiat.currency()
+                    .minor_units_per_usd_cent(fx_rate.fiat_per_usd),
+            )
+            .await?;
+        Ok(FiatPriceConversion {
+            conversion: PriceConversion {
+                amount: Sats::from_decimal(res.amount),
+                pre_fee_amount: Sats::from_decimal(res.pre_fee_amount),
+                fee_rate: res.fee_rate,
+                metadata: res.metadata,
+            },
+            fx_rate,
+        })
+    }
+
+    #[instrument(name = "price_server.get_fiat_per_sat_exchange_mid_rate", skip_all, fields(correlation_id, currency = %currency), ret, err)]
+    pub async fn get_fiat_per_sat_exchange_mid_rate(
+        &self,
+        currency: FiatCurrency,
+    ) -> Result<FiatMidRate, PriceAppError> {
+        let fx_rate = self.fx_rate(&currency).await?;
+        let (cents_per_sat, metadata) = self
+            .price_mixer
+            .apply_with_metadata(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        Ok(FiatMidRate {
+            fiat_minor_units_per_sat: f64::try_from(
+                cents_per_sat * currency.minor_units_per_usd_cent(fx_rate.fiat_per_usd),
+            )?,
+            fx_rate,
+            metadata,
+        })
+    }
+
+    async fn fx_rate(&self, currency: &FiatCurrency) -> Result<FxRate, PriceAppError> {
+        let fx = self.fx.as_ref().ok_or(FxError::Disabled)?;
+        Ok(fx.rate(currency).await?)
+    }
+
+    #[instrument(name = "price_server.get_price_history", skip(self), err)]
+    pub async fn get_price_history(
+        &self,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>, PriceAppError> {
+        let history = self.history.as_ref().ok_or(PriceHistoryError::Disabled)?;
+        Ok(history.list(resolution, from, to).await?)
     }
 }
@@ -1,7 +1,9 @@
 use thiserror::Error;
 
-use crate::{currency::CurrencyError, order_book_cache::OrderBookCacheError};
-use shared::{pubsub::SubscriberError, time::*};
+use pricing::{currency::CurrencyError, ExchangePriceCacheError};
+
+use crate::{fx::FxError, price_history::PriceHistoryError};
+use shared::pubsub::SubscriberError;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Error, Debug)]
@@ -14,14 +16,10 @@ pub enum PriceAppError {
     ExchangePriceCacheError(#[from] ExchangePriceCacheError),
     #[error("PriceAppError - DecimalConversion: {0}")]
     DecimalConversion(#[from] rust_decimal::Error),
-}
-
-#[derive(Error, Debug)]
-pub enum ExchangePriceCacheError {
-    #[error("StalePrice: last update was at {0}")]
-    StalePrice(TimeStamp),
-    #[error("No price data available")]
-    NoPriceAvailable,
-    #[error("OrderBook: {0:?}")]
-    OrderBookCache(#[from] OrderBookCacheError),
+    #[error("PriceAppError - PriceHistoryError: {0}")]
+    PriceHistoryError(#[from] PriceHistoryError),
+    #[error("PriceAppError - InvalidConversionSide: {0}")]
+    InvalidConversionSide(i32),
+    #[error("PriceAppError - FxError: {0}")]
+    FxError(#[from] FxError),
 }
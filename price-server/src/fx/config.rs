@@ -0,0 +1,64 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf};
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FxConfig {
+    /// Offer conversions in fiat currencies other than USD
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub provider: FxProviderConfig,
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    #[serde(default = "default_refresh_interval")]
+    pub refresh_interval: std::time::Duration,
+    /// Rates older than this are rejected and reported by the health check
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_stale_after")]
+    pub stale_after: chrono::Duration,
+}
+
+impl Default for FxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: FxProviderConfig::default(),
+            refresh_interval: default_refresh_interval(),
+            stale_after: default_stale_after(),
+        }
+    }
+}
+
+/// Source of USD/fiat rates, expressed as units of fiat per USD (eg. `EUR: 0.92`)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FxProviderConfig {
+    Mock {
+        rates: HashMap<String, Decimal>,
+    },
+    /// JSON file of the form `{"rates": {"EUR": "0.92"}}`, re-read on every refresh
+    File {
+        path: PathBuf,
+    },
+    /// Endpoint returning the same JSON document as the file provider
+    Http {
+        url: url::Url,
+    },
+}
+
+impl Default for FxProviderConfig {
+    fn default() -> Self {
+        Self::Mock {
+            rates: HashMap::new(),
+        }
+    }
+}
+
+fn default_refresh_interval() -> std::time::Duration {
+    std::time::Duration::from_secs(60)
+}
+
+fn default_stale_after() -> chrono::Duration {
+    chrono::Duration::try_seconds(900).expect("valid duration")
+}
@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+use shared::time::TimeStamp;
+
+#[derive(Error, Debug)]
+pub enum FxError {
+    #[error("FxError - Disabled: fiat pricing is not enabled")]
+    Disabled,
+    #[error("FxError - UnsupportedCurrency: {0}")]
+    UnsupportedCurrency(String),
+    #[error("FxError - StaleRate: last update was at {0}")]
+    StaleRate(TimeStamp),
+    #[error("FxError - InvalidRate: {0}")]
+    InvalidRate(String),
+    #[error("FxError - Io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("FxError - Json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("FxError - Http: {0}")]
+    Http(#[from] reqwest::Error),
+}
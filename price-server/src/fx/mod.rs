@@ -0,0 +1,122 @@
+mod config;
+mod error;
+mod provider;
+
+use rust_decimal::Decimal;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+
+use shared::{
+    health::{FeedHealthCheck, HealthCheckResponse},
+    time::TimeStamp,
+};
+
+use pricing::currency::FiatCurrency;
+
+pub use config::*;
+pub use error::*;
+pub use provider::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FxRate {
+    pub fiat_per_usd: Decimal,
+    pub timestamp: TimeStamp,
+}
+
+/// Latest USD/fiat rates, refreshed in the background from the configured provider
+#[derive(Clone)]
+pub struct FxRates {
+    rates: Arc<RwLock<HashMap<FiatCurrency, FxRate>>>,
+    stale_after: chrono::Duration,
+    health: FeedHealthCheck,
+}
+
+impl FxRates {
+    pub fn new(stale_after: chrono::Duration) -> Self {
+        Self {
+            rates: Arc::new(RwLock::new(HashMap::new())),
+            stale_after,
+            health: FeedHealthCheck::new("fx_rates", stale_after),
+        }
+    }
+
+    pub fn run(config: FxConfig) -> Self {
+        let rates = Self::new(config.stale_after);
+        rates.spawn_refresh(provider_for(&config.provider), config.refresh_interval);
+        rates
+    }
+
+    pub async fn update(&self, rates: HashMap<FiatCurrency, Decimal>) {
+        let timestamp = TimeStamp::now();
+        let mut current = self.rates.write().await;
+        for (currency, fiat_per_usd) in rates {
+            current.insert(
+                currency,
+                FxRate {
+                    fiat_per_usd,
+                    timestamp,
+                },
+            );
+        }
+        self.health.record_update().await;
+    }
+
+    pub async fn rate(&self, currency: &FiatCurrency) -> Result<FxRate, FxError> {
+        let rate = self
+            .rates
+            .read()
+            .await
+            .get(currency)
+            .copied()
+            .ok_or_else(|| FxError::UnsupportedCurrency(currency.to_string()))?;
+        if rate.timestamp.duration_since() > self.stale_after {
+            return Err(FxError::StaleRate(rate.timestamp));
+        }
+        Ok(rate)
+    }
+
+    pub async fn healthy(&self) -> HealthCheckResponse {
+        self.health.healthy().await
+    }
+
+    fn spawn_refresh(&self, provider: Box<dyn FxProvider>, interval: std::time::Duration) {
+        let rates = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                match provider.fetch_rates().await {
+                    Ok(fetched) => rates.update(fetched).await,
+                    Err(e) => tracing::warn!(error = %e, "failed to refresh fx rates"),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn rejects_unknown_and_stale_rates() {
+        let eur = FiatCurrency::new("EUR").unwrap();
+        let rates = FxRates::new(chrono::Duration::try_seconds(60).unwrap());
+        assert!(matches!(
+            rates.rate(&eur).await,
+            Err(FxError::UnsupportedCurrency(_))
+        ));
+        assert!(rates.healthy().await.is_err());
+
+        rates
+            .update(HashMap::from([(eur.clone(), dec!(0.92))]))
+            .await;
+        assert_eq!(rates.rate(&eur).await.unwrap().fiat_per_usd, dec!(0.92));
+        assert!(rates.healthy().await.is_ok());
+
+        rates.rates.write().await.get_mut(&eur).unwrap().timestamp = TimeStamp::from(1_u64);
+        assert!(matches!(rates.rate(&eur).await, Err(FxError::StaleRate(_))));
+    }
+}
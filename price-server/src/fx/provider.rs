@@ -0,0 +1,121 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf};
+
+use pricing::currency::FiatCurrency;
+
+use super::{config::FxProviderConfig, error::FxError};
+
+#[async_trait::async_trait]
+pub trait FxProvider: Send + Sync {
+    /// Units of each fiat currency per USD
+    async fn fetch_rates(&self) -> Result<HashMap<FiatCurrency, Decimal>, FxError>;
+}
+
+pub fn provider_for(config: &FxProviderConfig) -> Box<dyn FxProvider> {
+    match config {
+        FxProviderConfig::Mock { rates } => Box::new(MockFxProvider {
+            rates: rates.clone(),
+        }),
+        FxProviderConfig::File { path } => Box::new(FileFxProvider { path: path.clone() }),
+        FxProviderConfig::Http { url } => Box::new(HttpFxProvider {
+            client: reqwest::Client::new(),
+            url: url.clone(),
+        }),
+    }
+}
+
+#[derive(Deserialize)]
+struct FxRatesDocument {
+    rates: HashMap<String, Decimal>,
+}
+
+fn parse_rates(
+    rates: &HashMap<String, Decimal>,
+) -> Result<HashMap<FiatCurrency, Decimal>, FxError> {
+    rates
+        .iter()
+        .map(|(code, rate)| {
+            let currency =
+                FiatCurrency::new(code).map_err(|_| FxError::UnsupportedCurrency(code.clone()))?;
+            if *rate <= Decimal::ZERO {
+                return Err(FxError::InvalidRate(format!("{code}: {rate}")));
+            }
+            Ok((currency, *rate))
+        })
+        .collect()
+}
+
+struct MockFxProvider {
+    rates: HashMap<String, Decimal>,
+}
+
+#[async_trait::async_trait]
+impl FxProvider for MockFxProvider {
+    async fn fetch_rates(&self) -> Result<HashMap<FiatCurrency, Decimal>, FxError> {
+        parse_rates(&self.rates)
+    }
+}
+
+struct FileFxProvider {
+    path: PathBuf,
+}
+
+#[async_trait::async_trait]
+impl FxProvider for FileFxProvider {
+    async fn fetch_rates(&self) -> Result<HashMap<FiatCurrency, Decimal>, FxError> {
+        let contents = tokio::fs::read_to_string(&self.path).await?;
+        let document: FxRatesDocument = serde_json::from_str(&contents)?;
+        parse_rates(&document.rates)
+    }
+}
+
+struct HttpFxProvider {
+    client: reqwest::Client,
+    url: url::Url,
+}
+
+#[async_trait::async_trait]
+impl FxProvider for HttpFxProvider {
+    async fn fetch_rates(&self) -> Result<HashMap<FiatCurrency, Decimal>, FxError> {
+        let document: FxRatesDocument = self
+            .client
+            .get(self.url.clone())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse_rates(&document.rates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn file_provider_reads_rates() {
+        let path = std::env::temp_dir().join("stablesats-fx-rates-test.json");
+        tokio::fs::write(&path, r#"{"rates": {"eur": "0.92", "GBP": 0.79}}"#)
+            .await
+            .unwrap();
+        let provider = provider_for(&FxProviderConfig::File { path });
+        let rates = provider.fetch_rates().await.unwrap();
+        assert_eq!(rates[&FiatCurrency::new("EUR").unwrap()], dec!(0.92));
+        assert_eq!(rates[&FiatCurrency::new("GBP").unwrap()], dec!(0.79));
+    }
+
+    #[tokio::test]
+    async fn rejects_non_positive_rates() {
+        let provider = provider_for(&FxProviderConfig::Mock {
+            rates: HashMap::from([("EUR".to_string(), Decimal::ZERO)]),
+        });
+        assert!(matches!(
+            provider.fetch_rates().await,
+            Err(FxError::InvalidRate(_))
+        ));
+    }
+}
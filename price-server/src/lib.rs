@@ -2,42 +2,57 @@
 #![cfg_attr(feature = "fail-on-warnings", deny(clippy::all))]
 
 pub mod app;
-mod cache_config;
-pub mod currency;
 mod error;
-mod exchange_tick_cache;
-mod fee_calculator;
-mod order_book_cache;
-mod price_converter;
-mod price_mixer;
+mod fx;
+mod price_history;
 mod server;
 
 use app::PriceApp;
-use shared::{health::HealthCheckTrigger, payload::*, pubsub::memory};
+use shared::{
+    health::{HealthCheckTrigger, HealthDetailsTrigger},
+    payload::*,
+    pubsub::memory,
+};
 
-pub use app::{ExchangeWeights, PriceServerHealthCheckConfig};
-pub use cache_config::ExchangePriceCacheConfig;
-pub use fee_calculator::FeeCalculatorConfig;
-pub use order_book_cache::*;
-pub use price_converter::*;
+pub use app::PriceServerHealthCheckConfig;
+pub use fx::{FxConfig, FxProviderConfig, FxRates};
+pub use price_history::{CandleResolution, PriceHistoryConfig};
+pub use pricing::{
+    ExchangePriceCacheConfig, ExchangeWeights, FeeCalculatorConfig, MixingMode,
+    OrderBookAggregationConfig, OrderBookCacheError, PriceMetadata, PriceMixerConfig, PriceSource,
+};
 pub use server::*;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     health_check_trigger: HealthCheckTrigger,
+    health_details_trigger: HealthDetailsTrigger,
     health_check_cfg: PriceServerHealthCheckConfig,
     server_config: PriceServerConfig,
     fee_calc_cfg: FeeCalculatorConfig,
     subscriber: memory::Subscriber<PriceStreamPayload>,
     price_cache_config: ExchangePriceCacheConfig,
+    mixer_config: PriceMixerConfig,
     exchange_weights: ExchangeWeights,
+    history_config: PriceHistoryConfig,
+    pool: Option<sqlx::PgPool>,
+    fx_config: FxConfig,
 ) -> Result<(), PriceServerError> {
+    let history = pool
+        .filter(|_| history_config.enabled)
+        .map(|pool| price_history::PriceHistory::new(&pool, history_config));
+    let fx = fx_config.enabled.then(|| fx::FxRates::run(fx_config));
     let app = PriceApp::run(
         health_check_trigger,
+        health_details_trigger,
         health_check_cfg,
         fee_calc_cfg,
         subscriber,
         price_cache_config,
+        mixer_config,
         exchange_weights,
+        history,
+        fx,
     )
     .await?;
 
@@ -0,0 +1,230 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CandleResolution {
+    OneMinute,
+    OneHour,
+    OneDay,
+}
+
+impl CandleResolution {
+    pub const ALL: [CandleResolution; 3] = [
+        CandleResolution::OneMinute,
+        CandleResolution::OneHour,
+        CandleResolution::OneDay,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleResolution::OneMinute => "1m",
+            CandleResolution::OneHour => "1h",
+            CandleResolution::OneDay => "1d",
+        }
+    }
+
+    fn seconds(&self) -> i64 {
+        match self {
+            CandleResolution::OneMinute => 60,
+            CandleResolution::OneHour => 60 * 60,
+            CandleResolution::OneDay => 24 * 60 * 60,
+        }
+    }
+
+    /// Start of the candle containing `timestamp`, aligned to UTC
+    pub fn start_of(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let secs = timestamp.timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(self.seconds()), 0)
+            .expect("aligned timestamp is in range")
+    }
+}
+
+/// Mid, best bid and best ask of one sat in cents at a point in time
+#[derive(Clone, Copy, Debug)]
+pub struct PriceSample {
+    pub timestamp: DateTime<Utc>,
+    pub mid: Decimal,
+    pub bid: Decimal,
+    pub ask: Decimal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ohlc {
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+}
+
+impl Ohlc {
+    fn new(price: Decimal) -> Self {
+        Self {
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+        }
+    }
+
+    fn update(&mut self, price: Decimal) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candle {
+    pub resolution: CandleResolution,
+    pub started_at: DateTime<Utc>,
+    pub mid: Ohlc,
+    pub bid: Ohlc,
+    pub ask: Ohlc,
+}
+
+impl Candle {
+    fn new(resolution: CandleResolution, sample: &PriceSample) -> Self {
+        Self {
+            resolution,
+            started_at: resolution.start_of(sample.timestamp),
+            mid: Ohlc::new(sample.mid),
+            bid: Ohlc::new(sample.bid),
+            ask: Ohlc::new(sample.ask),
+        }
+    }
+
+    fn update(&mut self, sample: &PriceSample) {
+        self.mid.update(sample.mid);
+        self.bid.update(sample.bid);
+        self.ask.update(sample.ask);
+    }
+}
+
+/// Aggregates samples into the open candle of every resolution and keeps
+/// track of the candles that still have to be persisted
+#[derive(Debug, Default)]
+pub(super) struct CandleBuilder {
+    open: HashMap<CandleResolution, Candle>,
+    closed: Vec<Candle>,
+    dirty: bool,
+}
+
+impl CandleBuilder {
+    pub fn record(&mut self, sample: &PriceSample) {
+        for resolution in CandleResolution::ALL {
+            let started_at = resolution.start_of(sample.timestamp);
+            match self.open.get_mut(&resolution) {
+                Some(candle) if candle.started_at == started_at => candle.update(sample),
+                Some(candle) if candle.started_at > started_at => continue,
+                _ => {
+                    if let Some(closed) = self
+                        .open
+                        .insert(resolution, Candle::new(resolution, sample))
+                    {
+                        self.closed.push(closed);
+                    }
+                }
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Candles that changed since the last call, oldest first
+    pub fn take_pending(&mut self) -> Vec<Candle> {
+        let mut pending = std::mem::take(&mut self.closed);
+        if std::mem::take(&mut self.dirty) {
+            pending.extend(self.open.values().cloned());
+        }
+        pending
+    }
+
+    /// Re-queues candles that could not be persisted
+    pub fn requeue(&mut self, candles: Vec<Candle>) {
+        let open = &self.open;
+        let closed = candles.into_iter().filter(|candle| {
+            open.get(&candle.resolution)
+                .is_none_or(|open| open.started_at != candle.started_at)
+        });
+        self.closed.splice(0..0, closed);
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn sample(secs: i64, mid: Decimal) -> PriceSample {
+        PriceSample {
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            mid,
+            bid: mid - dec!(1),
+            ask: mid + dec!(1),
+        }
+    }
+
+    #[test]
+    fn aligns_candles_to_resolution() {
+        let timestamp = DateTime::from_timestamp(90_061, 0).unwrap();
+        assert_eq!(
+            CandleResolution::OneMinute.start_of(timestamp).timestamp(),
+            90_060
+        );
+        assert_eq!(
+            CandleResolution::OneHour.start_of(timestamp).timestamp(),
+            86_400 + 3_600
+        );
+        assert_eq!(
+            CandleResolution::OneDay.start_of(timestamp).timestamp(),
+            86_400
+        );
+    }
+
+    #[test]
+    fn aggregates_samples_into_ohlc_candles() {
+        let mut builder = CandleBuilder::default();
+        for (secs, mid) in [(0, dec!(10)), (20, dec!(12)), (40, dec!(9)), (59, dec!(11))] {
+            builder.record(&sample(secs, mid));
+        }
+        builder.record(&sample(61, dec!(13)));
+
+        let pending = builder.take_pending();
+        assert_eq!(pending.len(), 4);
+        let closed = &pending[0];
+        assert_eq!(closed.resolution, CandleResolution::OneMinute);
+        assert_eq!(
+            closed.mid,
+            Ohlc {
+                open: dec!(10),
+                high: dec!(12),
+                low: dec!(9),
+                close: dec!(11),
+            }
+        );
+        assert_eq!(closed.bid.low, dec!(8));
+        let hour = pending
+            .iter()
+            .find(|candle| candle.resolution == CandleResolution::OneHour)
+            .unwrap();
+        assert_eq!(hour.mid.high, dec!(13));
+        assert_eq!(hour.mid.close, dec!(13));
+
+        assert!(builder.take_pending().is_empty());
+    }
+
+    #[test]
+    fn requeues_closed_candles() {
+        let mut builder = CandleBuilder::default();
+        builder.record(&sample(0, dec!(10)));
+        builder.record(&sample(60, dec!(11)));
+        let pending = builder.take_pending();
+        builder.requeue(pending);
+
+        let pending = builder.take_pending();
+        assert_eq!(pending.len(), 4);
+        assert_eq!(pending[0].started_at.timestamp(), 0);
+    }
+}
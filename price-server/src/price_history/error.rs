@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PriceHistoryError {
+    #[error("PriceHistoryError - Sqlx: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("PriceHistoryError - Disabled: price history is not recorded")]
+    Disabled,
+    #[error("PriceHistoryError - UnknownResolution")]
+    UnknownResolution,
+    #[error("PriceHistoryError - InvalidRange: from must be before to")]
+    InvalidRange,
+}
@@ -0,0 +1,130 @@
+mod candle;
+mod error;
+mod repo;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use pricing::{currency::Sats, ExchangePriceCacheError, PriceMixer};
+
+pub use candle::*;
+pub use error::*;
+pub use repo::*;
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceHistoryConfig {
+    /// Aggregate prices into candles persisted in postgres
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    #[serde(default = "default_flush_interval")]
+    pub flush_interval: Duration,
+    #[serde(default = "default_max_candles_per_request")]
+    pub max_candles_per_request: i64,
+}
+
+impl Default for PriceHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            flush_interval: default_flush_interval(),
+            max_candles_per_request: default_max_candles_per_request(),
+        }
+    }
+}
+
+fn default_flush_interval() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_max_candles_per_request() -> i64 {
+    1440
+}
+
+#[derive(Clone, Debug)]
+pub struct PriceHistory {
+    candles: PriceCandles,
+    config: PriceHistoryConfig,
+}
+
+impl PriceHistory {
+    pub fn new(pool: &sqlx::PgPool, config: PriceHistoryConfig) -> Self {
+        Self {
+            candles: PriceCandles::new(pool),
+            config,
+        }
+    }
+
+    pub async fn list(
+        &self,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>, PriceHistoryError> {
+        if from >= to {
+            return Err(PriceHistoryError::InvalidRange);
+        }
+        self.candles
+            .list(resolution, from, to, self.config.max_candles_per_request)
+            .await
+    }
+
+    /// Samples the mixed price on every order book update and periodically
+    /// persists the candles that changed
+    pub(crate) fn spawn_recorder(
+        &self,
+        price_mixer: Arc<PriceMixer>,
+        mut price_updated: broadcast::Receiver<()>,
+    ) {
+        let candles = self.candles.clone();
+        let flush_interval = self.config.flush_interval;
+        tokio::spawn(async move {
+            let mut builder = CandleBuilder::default();
+            let mut flush = tokio::time::interval(flush_interval);
+            loop {
+                tokio::select! {
+                    update = price_updated.recv() => {
+                        if let Err(RecvError::Closed) = update {
+                            break;
+                        }
+                        if let Ok(sample) = price_sample(&price_mixer).await {
+                            builder.record(&sample);
+                        }
+                    }
+                    _ = flush.tick() => {
+                        let pending = builder.take_pending();
+                        if pending.is_empty() {
+                            continue;
+                        }
+                        if let Err(err) = candles.persist(&pending).await {
+                            tracing::warn!(error = %err, "price_history.persist_failed");
+                            builder.requeue(pending);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+async fn price_sample(price_mixer: &PriceMixer) -> Result<PriceSample, ExchangePriceCacheError> {
+    let one_sat = Sats::from_major(1);
+    let ([mid, bid, ask], metadata) = price_mixer
+        .apply_many(|p| {
+            [
+                *p.mid_price_of_one_sat().amount(),
+                *p.buy_usd().cents_from_sats(one_sat).amount(),
+                *p.sell_usd().cents_from_sats(one_sat).amount(),
+            ]
+        })
+        .await?;
+    Ok(PriceSample {
+        timestamp: metadata.timestamp.into(),
+        mid,
+        bid,
+        ask,
+    })
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use tracing::instrument;
+
+use super::{candle::*, error::PriceHistoryError};
+
+#[derive(Debug, Clone)]
+pub struct PriceCandles {
+    pool: Pool<Postgres>,
+}
+
+impl PriceCandles {
+    pub fn new(pool: &Pool<Postgres>) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Merges the candles into the stored ones so that candles written by a
+    /// previous process keep their open, high and low
+    #[instrument(name = "price_candles.persist", skip_all, fields(n_candles = candles.len()))]
+    pub async fn persist(&self, candles: &[Candle]) -> Result<(), PriceHistoryError> {
+        let mut tx = self.pool.begin().await?;
+        for candle in candles {
+            sqlx::query!(
+                r#"INSERT INTO price_candles (resolution, started_at,
+                     mid_open, mid_high, mid_low, mid_close,
+                     bid_open, bid_high, bid_low, bid_close,
+                     ask_open, ask_high, ask_low, ask_close)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                   ON CONFLICT (resolution, started_at) DO UPDATE SET
+                     mid_high = GREATEST(price_candles.mid_high, EXCLUDED.mid_high),
+                     mid_low = LEAST(price_candles.mid_low, EXCLUDED.mid_low),
+                     mid_close = EXCLUDED.mid_close,
+                     bid_high = GREATEST(price_candles.bid_high, EXCLUDED.bid_high),
+                     bid_low = LEAST(price_candles.bid_low, EXCLUDED.bid_low),
+                     bid_close = EXCLUDED.bid_close,
+                     ask_high = GREATEST(price_candles.ask_high, EXCLUDED.ask_high),
+                     ask_low = LEAST(price_candles.ask_low, EXCLUDED.ask_low),
+                     ask_close = EXCLUDED.ask_close,
+                     updated_at = NOW()"#,
+                candle.resolution.as_str(),
+                candle.started_at,
+                candle.mid.open,
+                candle.mid.high,
+                candle.mid.low,
+                candle.mid.close,
+                candle.bid.open,
+                candle.bid.high,
+                candle.bid.low,
+                candle.bid.close,
+                candle.ask.open,
+                candle.ask.high,
+                candle.ask.low,
+                candle.ask.close
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[instrument(name = "price_candles.list", skip(self))]
+    pub async fn list(
+        &self,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Candle>, PriceHistoryError> {
+        let rows = sqlx::query!(
+            r#"SELECT started_at,
+                 mid_open, mid_high, mid_low, mid_close,
+                 bid_open, bid_high, bid_low, bid_close,
+                 ask_open, ask_high, ask_low, ask_close
+               FROM price_candles
+               WHERE resolution = $1 AND started_at >= $2 AND started_at < $3
+               ORDER BY started_at
+               LIMIT $4"#,
+            resolution.as_str(),
+            from,
+            to,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Candle {
+                resolution,
+                started_at: row.started_at,
+                mid: Ohlc {
+                    open: row.mid_open,
+                    high: row.mid_high,
+                    low: row.mid_low,
+                    close: row.mid_close,
+                },
+                bid: Ohlc {
+                    open: row.bid_open,
+                    high: row.bid_high,
+                    low: row.bid_low,
+                    close: row.bid_close,
+                },
+                ask: Ohlc {
+                    open: row.ask_open,
+                    high: row.ask_high,
+                    low: row.ask_low,
+                    close: row.ask_close,
+                },
+            })
+            .collect())
+    }
+}
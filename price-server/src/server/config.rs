@@ -1,14 +1,21 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PriceServerConfig {
     #[serde(default = "default_port")]
     pub listen_port: u16,
+    #[serde(default)]
+    pub subscriptions: PriceSubscriptionConfig,
+    #[serde(default)]
+    pub gateway: PriceGatewayConfig,
 }
 impl Default for PriceServerConfig {
     fn default() -> Self {
         Self {
             listen_port: default_port(),
+            subscriptions: PriceSubscriptionConfig::default(),
+            gateway: PriceGatewayConfig::default(),
         }
     }
 }
@@ -16,3 +23,61 @@ impl Default for PriceServerConfig {
 fn default_port() -> u16 {
     3325
 }
+
+/// HTTP/JSON gateway mirroring the gRPC service on its own port
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceGatewayConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_gateway_port")]
+    pub listen_port: u16,
+}
+impl Default for PriceGatewayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_port: default_gateway_port(),
+        }
+    }
+}
+
+fn default_gateway_port() -> u16 {
+    3335
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceSubscriptionConfig {
+    /// Lower bound for the throttle interval a subscriber can request
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    #[serde(default = "default_min_throttle_interval")]
+    pub min_throttle_interval: Duration,
+    /// Prices are re-evaluated at least this often so that stale books are
+    /// reported even when no order book update arrives
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    #[serde(default = "default_recheck_interval")]
+    pub recheck_interval: Duration,
+    #[serde(default = "default_reference_amounts_in_satoshis")]
+    pub default_reference_amounts_in_satoshis: Vec<u64>,
+}
+impl Default for PriceSubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            min_throttle_interval: default_min_throttle_interval(),
+            recheck_interval: default_recheck_interval(),
+            default_reference_amounts_in_satoshis: default_reference_amounts_in_satoshis(),
+        }
+    }
+}
+
+fn default_min_throttle_interval() -> Duration {
+    Duration::from_millis(500)
+}
+
+fn default_recheck_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_reference_amounts_in_satoshis() -> Vec<u64> {
+    vec![100_000, 1_000_000, 100_000_000]
+}
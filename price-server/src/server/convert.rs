@@ -1,4 +1,14 @@
-use crate::app::PriceAppError;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+
+use pricing::{currency::FiatCurrency, PriceMetadata};
+
+use super::proto;
+use crate::{
+    app::{future_execution, ConversionSide, Execution, PriceAppError},
+    fx::{FxError, FxRate},
+    price_history::{Candle, CandleResolution, Ohlc, PriceHistoryError},
+};
 
 impl From<PriceAppError> for tonic::Status {
     fn from(err: PriceAppError) -> Self {
@@ -10,6 +20,124 @@ impl From<PriceAppError> for tonic::Status {
                 tonic::Status::new(tonic::Code::Unknown, format!("{err}"))
             }
             DecimalConversion(err) => tonic::Status::new(tonic::Code::Unknown, format!("{err}")),
+            InvalidConversionSide(_) => {
+                tonic::Status::new(tonic::Code::InvalidArgument, format!("{err}"))
+            }
+            PriceHistoryError(err) => {
+                use crate::price_history::PriceHistoryError::*;
+                let code = match err {
+                    Disabled => tonic::Code::Unimplemented,
+                    UnknownResolution | InvalidRange => tonic::Code::InvalidArgument,
+                    Sqlx(_) => tonic::Code::Unknown,
+                };
+                tonic::Status::new(code, format!("{err}"))
+            }
+            FxError(err) => {
+                use crate::fx::FxError::*;
+                let code = match err {
+                    Disabled => tonic::Code::Unimplemented,
+                    UnsupportedCurrency(_) => tonic::Code::InvalidArgument,
+                    StaleRate(_) => tonic::Code::Unavailable,
+                    InvalidRate(_) | Io(_) | Json(_) | Http(_) => tonic::Code::Unknown,
+                };
+                tonic::Status::new(code, format!("{err}"))
+            }
+        }
+    }
+}
+
+impl From<PriceMetadata> for proto::PriceMetadata {
+    fn from(metadata: PriceMetadata) -> Self {
+        Self {
+            timestamp: metadata.timestamp.timestamp(),
+            exchanges: metadata
+                .sources
+                .into_iter()
+                .map(|source| proto::ExchangeWeight {
+                    exchange_id: source.exchange_id.to_string(),
+                    weight: source.weight.to_f64().unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub(super) fn duration_from_secs(secs: u64) -> chrono::Duration {
+    chrono::Duration::from_std(std::time::Duration::from_secs(secs))
+        .unwrap_or(chrono::Duration::MAX)
+}
+
+impl From<Candle> for proto::PriceCandle {
+    fn from(candle: Candle) -> Self {
+        Self {
+            timestamp: candle.started_at.timestamp(),
+            mid_in_cents_per_satoshis: Some(candle.mid.into()),
+            bid_in_cents_per_satoshis: Some(candle.bid.into()),
+            ask_in_cents_per_satoshis: Some(candle.ask.into()),
+        }
+    }
+}
+
+impl From<Ohlc> for proto::Ohlc {
+    fn from(ohlc: Ohlc) -> Self {
+        Self {
+            open: ohlc.open.to_f64().unwrap_or_default(),
+            high: ohlc.high.to_f64().unwrap_or_default(),
+            low: ohlc.low.to_f64().unwrap_or_default(),
+            close: ohlc.close.to_f64().unwrap_or_default(),
+        }
+    }
+}
+
+pub(super) fn candle_resolution(resolution: i32) -> Result<CandleResolution, PriceAppError> {
+    match proto::CandleResolution::try_from(resolution) {
+        Ok(proto::CandleResolution::OneMinute) => Ok(CandleResolution::OneMinute),
+        Ok(proto::CandleResolution::OneHour) => Ok(CandleResolution::OneHour),
+        Ok(proto::CandleResolution::OneDay) => Ok(CandleResolution::OneDay),
+        _ => Err(PriceHistoryError::UnknownResolution.into()),
+    }
+}
+
+/// A `to_timestamp` of 0 means up to now
+pub(super) fn history_range(
+    from_timestamp: i64,
+    to_timestamp: i64,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), PriceAppError> {
+    let from =
+        DateTime::from_timestamp(from_timestamp, 0).ok_or(PriceHistoryError::InvalidRange)?;
+    let to = if to_timestamp == 0 {
+        Utc::now()
+    } else {
+        DateTime::from_timestamp(to_timestamp, 0).ok_or(PriceHistoryError::InvalidRange)?
+    };
+    Ok((from, to))
+}
+
+impl From<FxRate> for proto::FxRate {
+    fn from(rate: FxRate) -> Self {
+        Self {
+            fiat_per_usd: rate.fiat_per_usd.to_f64().unwrap_or_default(),
+            timestamp: rate.timestamp.timestamp(),
         }
     }
 }
+
+pub(super) fn fiat_currency(code: &str) -> Result<FiatCurrency, PriceAppError> {
+    FiatCurrency::new(code).map_err(|_| FxError::UnsupportedCurrency(code.to_string()).into())
+}
+
+pub(super) fn conversion_side(side: i32) -> Result<ConversionSide, PriceAppError> {
+    match proto::ConversionSide::try_from(side) {
+        Ok(proto::ConversionSide::Buy) => Ok(ConversionSide::Buy),
+        Ok(proto::ConversionSide::Sell) => Ok(ConversionSide::Sell),
+        _ => Err(PriceAppError::InvalidConversionSide(side)),
+    }
+}
+
+pub(super) fn execution(immediate_execution: bool, time_in_seconds: u64) -> Execution {
+    if immediate_execution {
+        Execution::Immediate
+    } else {
+        future_execution(duration_from_secs(time_in_seconds))
+    }
+}
@@ -7,6 +7,8 @@ use crate::app::PriceAppError;
 pub enum PriceServerError {
     #[error("PriceServerError - TonicError: {0}")]
     TonicError(#[from] tonic::transport::Error),
+    #[error("PriceServerError - GatewayError: {0}")]
+    GatewayError(#[from] std::io::Error),
     #[error("PriceServerError - AppError: {0}")]
     AppError(#[from] PriceAppError),
 }
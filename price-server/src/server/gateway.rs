@@ -0,0 +1,717 @@
+use axum::{
+    extract::State,
+    response::sse::{Event, Sse},
+    routing::get,
+    Router,
+};
+use futures::{future, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use shared::gateway::{GatewayError, GatewayErrorBody, Json, Query};
+use std::sync::Arc;
+use tonic::Status;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use super::{
+    config::{PriceGatewayConfig, PriceSubscriptionConfig},
+    convert::{duration_from_secs, execution, fiat_currency, history_range},
+    proto::{self, subscribe_prices_response::Message},
+    subscription,
+};
+use crate::{
+    app::*,
+    price_history::{CandleResolution, PriceHistoryError},
+};
+
+#[derive(Clone)]
+struct GatewayState {
+    app: Arc<PriceApp>,
+    subscriptions: PriceSubscriptionConfig,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct SatsAmountQuery {
+    amount_in_satoshis: u64,
+    /// Only used for future conversions
+    #[serde(default)]
+    time_in_seconds: u64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct CentsAmountQuery {
+    amount_in_cents: u64,
+    /// Only used for future conversions
+    #[serde(default)]
+    time_in_seconds: u64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct SubscribePricesQuery {
+    /// Comma separated list of amounts to quote buy and sell prices for
+    #[serde(default)]
+    reference_amounts_in_satoshis: Option<String>,
+    #[serde(default)]
+    throttle_interval_in_milliseconds: u64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct PriceHistoryQuery {
+    /// One of `1m`, `1h` or `1d`
+    resolution: String,
+    from_timestamp: i64,
+    /// Defaults to now
+    #[serde(default)]
+    to_timestamp: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct FiatFromSatsQuery {
+    /// ISO 4217 code, eg. `EUR`
+    currency: String,
+    amount_in_satoshis: u64,
+    /// `buy` or `sell`
+    side: String,
+    #[serde(default)]
+    immediate_execution: bool,
+    /// Only used when `immediate_execution` is not set
+    #[serde(default)]
+    time_in_seconds: u64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct SatsFromFiatQuery {
+    /// ISO 4217 code, eg. `EUR`
+    currency: String,
+    amount_in_minor_units: u64,
+    /// `buy` or `sell`
+    side: String,
+    #[serde(default)]
+    immediate_execution: bool,
+    /// Only used when `immediate_execution` is not set
+    #[serde(default)]
+    time_in_seconds: u64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct FiatMidRateQuery {
+    /// ISO 4217 code, eg. `EUR`
+    currency: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CentsAmount {
+    amount_in_cents: u64,
+    metadata: PriceMetadata,
+    pre_fee_amount_in_cents: u64,
+    fee_rate: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SatsAmount {
+    amount_in_satoshis: u64,
+    metadata: PriceMetadata,
+    pre_fee_amount_in_satoshis: u64,
+    fee_rate: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExchangeMidRate {
+    ratio_in_cents_per_satoshis: f64,
+    metadata: PriceMetadata,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FiatAmount {
+    currency: String,
+    amount_in_minor_units: u64,
+    metadata: PriceMetadata,
+    pre_fee_amount_in_minor_units: u64,
+    fee_rate: f64,
+    fx_rate: FxRate,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FiatSatsAmount {
+    amount_in_satoshis: u64,
+    metadata: PriceMetadata,
+    pre_fee_amount_in_satoshis: u64,
+    fee_rate: f64,
+    fx_rate: FxRate,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FiatMidRate {
+    ratio_in_minor_units_per_satoshis: f64,
+    metadata: PriceMetadata,
+    fx_rate: FxRate,
+}
+
+/// The USD/fiat rate a fiat amount was priced at
+#[derive(Serialize, ToSchema)]
+pub struct FxRate {
+    fiat_per_usd: f64,
+    timestamp: i64,
+}
+
+impl From<proto::FxRate> for FxRate {
+    fn from(rate: proto::FxRate) -> Self {
+        Self {
+            fiat_per_usd: rate.fiat_per_usd,
+            timestamp: rate.timestamp,
+        }
+    }
+}
+
+impl TryFrom<PriceConversion<UsdCents>> for CentsAmount {
+    type Error = PriceAppError;
+
+    fn try_from(conversion: PriceConversion<UsdCents>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            amount_in_cents: u64::try_from(conversion.amount)?,
+            metadata: proto::PriceMetadata::from(conversion.metadata).into(),
+            pre_fee_amount_in_cents: u64::try_from(conversion.pre_fee_amount)?,
+            fee_rate: f64::try_from(conversion.fee_rate)?,
+        })
+    }
+}
+
+impl TryFrom<PriceConversion<Sats>> for SatsAmount {
+    type Error = PriceAppError;
+
+    fn try_from(conversion: PriceConversion<Sats>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            amount_in_satoshis: u64::try_from(conversion.amount)?,
+            metadata: proto::PriceMetadata::from(conversion.metadata).into(),
+            pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)?,
+            fee_rate: f64::try_from(conversion.fee_rate)?,
+        })
+    }
+}
+
+impl TryFrom<FiatPriceConversion<FiatCents>> for FiatAmount {
+    type Error = PriceAppError;
+
+    fn try_from(
+        FiatPriceConversion {
+            conversion,
+            fx_rate,
+        }: FiatPriceConversion<FiatCents>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            currency: conversion.amount.currency().to_string(),
+            amount_in_minor_units: u64::try_from(conversion.amount)?,
+            metadata: proto::PriceMetadata::from(conversion.metadata).into(),
+            pre_fee_amount_in_minor_units: u64::try_from(conversion.pre_fee_amount)?,
+            fee_rate: f64::try_from(conversion.fee_rate)?,
+            fx_rate: proto::FxRate::from(fx_rate).into(),
+        })
+    }
+}
+
+impl TryFrom<FiatPriceConversion<Sats>> for FiatSatsAmount {
+    type Error = PriceAppError;
+
+    fn try_from(
+        FiatPriceConversion {
+            conversion,
+            fx_rate,
+        }: FiatPriceConversion<Sats>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            amount_in_satoshis: u64::try_from(conversion.amount)?,
+            metadata: proto::PriceMetadata::from(conversion.metadata).into(),
+            pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)?,
+            fee_rate: f64::try_from(conversion.fee_rate)?,
+            fx_rate: proto::FxRate::from(fx_rate).into(),
+        })
+    }
+}
+
+fn conversion_side(side: &str) -> Result<ConversionSide, GatewayError> {
+    match side {
+        "buy" => Ok(ConversionSide::Buy),
+        "sell" => Ok(ConversionSide::Sell),
+        _ => Err(GatewayError::invalid_argument(format!(
+            "Unknown conversion side: {side}"
+        ))),
+    }
+}
+
+/// The exchange snapshots a price was derived from
+#[derive(Serialize, ToSchema)]
+pub struct PriceMetadata {
+    /// Timestamp of the oldest contributing snapshot
+    timestamp: i64,
+    exchanges: Vec<ExchangeWeight>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExchangeWeight {
+    exchange_id: String,
+    weight: f64,
+}
+
+impl From<proto::PriceMetadata> for PriceMetadata {
+    fn from(metadata: proto::PriceMetadata) -> Self {
+        Self {
+            timestamp: metadata.timestamp,
+            exchanges: metadata
+                .exchanges
+                .into_iter()
+                .map(|exchange| ExchangeWeight {
+                    exchange_id: exchange.exchange_id,
+                    weight: exchange.weight,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PriceUpdate {
+    ratio_in_cents_per_satoshis: f64,
+    reference_prices: Vec<ReferencePrice>,
+    timestamp: i64,
+    metadata: Option<PriceMetadata>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReferencePrice {
+    amount_in_satoshis: u64,
+    buy_amount_in_cents: u64,
+    sell_amount_in_cents: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PriceStale {
+    reason: String,
+    last_update_timestamp: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PriceHistory {
+    candles: Vec<PriceCandle>,
+}
+
+/// Prices of one sat in cents during the candle starting at `timestamp`
+#[derive(Serialize, ToSchema)]
+pub struct PriceCandle {
+    timestamp: i64,
+    mid_in_cents_per_satoshis: Option<Ohlc>,
+    bid_in_cents_per_satoshis: Option<Ohlc>,
+    ask_in_cents_per_satoshis: Option<Ohlc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Ohlc {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+}
+
+impl From<proto::PriceCandle> for PriceCandle {
+    fn from(candle: proto::PriceCandle) -> Self {
+        Self {
+            timestamp: candle.timestamp,
+            mid_in_cents_per_satoshis: candle.mid_in_cents_per_satoshis.map(Ohlc::from),
+            bid_in_cents_per_satoshis: candle.bid_in_cents_per_satoshis.map(Ohlc::from),
+            ask_in_cents_per_satoshis: candle.ask_in_cents_per_satoshis.map(Ohlc::from),
+        }
+    }
+}
+
+impl From<proto::Ohlc> for Ohlc {
+    fn from(ohlc: proto::Ohlc) -> Self {
+        Self {
+            open: ohlc.open,
+            high: ohlc.high,
+            low: ohlc.low,
+            close: ohlc.close,
+        }
+    }
+}
+
+impl From<PriceAppError> for GatewayError {
+    fn from(err: PriceAppError) -> Self {
+        Status::from(err).into()
+    }
+}
+
+#[utoipa::path(get, path = "/v1/cents-from-sats/immediate-buy", params(SatsAmountQuery),
+    responses((status = 200, body = CentsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_cents_from_sats_for_immediate_buy(
+    State(state): State<GatewayState>,
+    Query(query): Query<SatsAmountQuery>,
+) -> Result<Json<CentsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_cents_from_sats_for_immediate_buy(Sats::from_major(query.amount_in_satoshis))
+        .await?;
+    Ok(Json(CentsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/cents-from-sats/immediate-sell", params(SatsAmountQuery),
+    responses((status = 200, body = CentsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_cents_from_sats_for_immediate_sell(
+    State(state): State<GatewayState>,
+    Query(query): Query<SatsAmountQuery>,
+) -> Result<Json<CentsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_cents_from_sats_for_immediate_sell(Sats::from_major(query.amount_in_satoshis))
+        .await?;
+    Ok(Json(CentsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/cents-from-sats/future-buy", params(SatsAmountQuery),
+    responses((status = 200, body = CentsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_cents_from_sats_for_future_buy(
+    State(state): State<GatewayState>,
+    Query(query): Query<SatsAmountQuery>,
+) -> Result<Json<CentsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_cents_from_sats_for_future_buy(
+            Sats::from_major(query.amount_in_satoshis),
+            duration_from_secs(query.time_in_seconds),
+        )
+        .await?;
+    Ok(Json(CentsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/cents-from-sats/future-sell", params(SatsAmountQuery),
+    responses((status = 200, body = CentsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_cents_from_sats_for_future_sell(
+    State(state): State<GatewayState>,
+    Query(query): Query<SatsAmountQuery>,
+) -> Result<Json<CentsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_cents_from_sats_for_future_sell(
+            Sats::from_major(query.amount_in_satoshis),
+            duration_from_secs(query.time_in_seconds),
+        )
+        .await?;
+    Ok(Json(CentsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/sats-from-cents/immediate-buy", params(CentsAmountQuery),
+    responses((status = 200, body = SatsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_sats_from_cents_for_immediate_buy(
+    State(state): State<GatewayState>,
+    Query(query): Query<CentsAmountQuery>,
+) -> Result<Json<SatsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_sats_from_cents_for_immediate_buy(UsdCents::from_major(query.amount_in_cents))
+        .await?;
+    Ok(Json(SatsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/sats-from-cents/immediate-sell", params(CentsAmountQuery),
+    responses((status = 200, body = SatsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_sats_from_cents_for_immediate_sell(
+    State(state): State<GatewayState>,
+    Query(query): Query<CentsAmountQuery>,
+) -> Result<Json<SatsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_sats_from_cents_for_immediate_sell(UsdCents::from_major(query.amount_in_cents))
+        .await?;
+    Ok(Json(SatsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/sats-from-cents/future-buy", params(CentsAmountQuery),
+    responses((status = 200, body = SatsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_sats_from_cents_for_future_buy(
+    State(state): State<GatewayState>,
+    Query(query): Query<CentsAmountQuery>,
+) -> Result<Json<SatsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_sats_from_cents_for_future_buy(
+            UsdCents::from_major(query.amount_in_cents),
+            duration_from_secs(query.time_in_seconds),
+        )
+        .await?;
+    Ok(Json(SatsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/sats-from-cents/future-sell", params(CentsAmountQuery),
+    responses((status = 200, body = SatsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_sats_from_cents_for_future_sell(
+    State(state): State<GatewayState>,
+    Query(query): Query<CentsAmountQuery>,
+) -> Result<Json<SatsAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_sats_from_cents_for_future_sell(
+            UsdCents::from_major(query.amount_in_cents),
+            duration_from_secs(query.time_in_seconds),
+        )
+        .await?;
+    Ok(Json(SatsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/exchange-mid-rate",
+    responses((status = 200, body = ExchangeMidRate), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_cents_per_sats_exchange_mid_rate(
+    State(state): State<GatewayState>,
+) -> Result<Json<ExchangeMidRate>, GatewayError> {
+    let mid_rate = state.app.get_cents_per_sat_exchange_mid_rate().await?;
+    Ok(Json(ExchangeMidRate {
+        ratio_in_cents_per_satoshis: mid_rate.cents_per_sat,
+        metadata: proto::PriceMetadata::from(mid_rate.metadata).into(),
+    }))
+}
+
+#[utoipa::path(get, path = "/v1/price-history", params(PriceHistoryQuery),
+    responses((status = 200, body = PriceHistory), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_price_history(
+    State(state): State<GatewayState>,
+    Query(query): Query<PriceHistoryQuery>,
+) -> Result<Json<PriceHistory>, GatewayError> {
+    let resolution = CandleResolution::ALL
+        .into_iter()
+        .find(|resolution| resolution.as_str() == query.resolution)
+        .ok_or(PriceAppError::from(PriceHistoryError::UnknownResolution))?;
+    let (from, to) = history_range(query.from_timestamp, query.to_timestamp)?;
+    let candles = state.app.get_price_history(resolution, from, to).await?;
+    Ok(Json(PriceHistory {
+        candles: candles
+            .into_iter()
+            .map(|candle| proto::PriceCandle::from(candle).into())
+            .collect(),
+    }))
+}
+
+#[utoipa::path(get, path = "/v1/fiat-from-sats", params(FiatFromSatsQuery),
+    responses((status = 200, body = FiatAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_fiat_from_sats(
+    State(state): State<GatewayState>,
+    Query(query): Query<FiatFromSatsQuery>,
+) -> Result<Json<FiatAmount>, GatewayError> {
+    let conversion = state
+        .app
+        .get_fiat_from_sats(
+            fiat_currency(&query.currency)?,
+            Sats::from_major(query.amount_in_satoshis),
+            conversion_side(&query.side)?,
+            execution(query.immediate_execution, query.time_in_seconds),
+        )
+        .await?;
+    Ok(Json(FiatAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/sats-from-fiat", params(SatsFromFiatQuery),
+    responses((status = 200, body = FiatSatsAmount), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_sats_from_fiat(
+    State(state): State<GatewayState>,
+    Query(query): Query<SatsFromFiatQuery>,
+) -> Result<Json<FiatSatsAmount>, GatewayError> {
+    let fiat = FiatCents::from_major(fiat_currency(&query.currency)?, query.amount_in_minor_units);
+    let conversion = state
+        .app
+        .get_sats_from_fiat(
+            fiat,
+            conversion_side(&query.side)?,
+            execution(query.immediate_execution, query.time_in_seconds),
+        )
+        .await?;
+    Ok(Json(FiatSatsAmount::try_from(conversion)?))
+}
+
+#[utoipa::path(get, path = "/v1/fiat-mid-rate", params(FiatMidRateQuery),
+    responses((status = 200, body = FiatMidRate), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_fiat_per_sats_exchange_mid_rate(
+    State(state): State<GatewayState>,
+    Query(query): Query<FiatMidRateQuery>,
+) -> Result<Json<FiatMidRate>, GatewayError> {
+    let mid_rate = state
+        .app
+        .get_fiat_per_sat_exchange_mid_rate(fiat_currency(&query.currency)?)
+        .await?;
+    Ok(Json(FiatMidRate {
+        ratio_in_minor_units_per_satoshis: mid_rate.fiat_minor_units_per_sat,
+        metadata: proto::PriceMetadata::from(mid_rate.metadata).into(),
+        fx_rate: proto::FxRate::from(mid_rate.fx_rate).into(),
+    }))
+}
+
+/// Server-sent events named `update` (`PriceUpdate`) or `stale` (`PriceStale`)
+#[utoipa::path(get, path = "/v1/prices/subscribe", params(SubscribePricesQuery),
+    responses((status = 200, content_type = "text/event-stream", body = PriceUpdate))
+)]
+async fn subscribe_prices(
+    State(state): State<GatewayState>,
+    Query(query): Query<SubscribePricesQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, serde_json::Error>>>, GatewayError> {
+    let reference_amounts_in_satoshis = query
+        .reference_amounts_in_satoshis
+        .iter()
+        .flat_map(|amounts| amounts.split(','))
+        .filter(|amount| !amount.trim().is_empty())
+        .map(|amount| amount.trim().parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| GatewayError::invalid_argument(err.to_string()))?;
+    let subscription = subscription::subscribe_prices(
+        state.app,
+        &state.subscriptions,
+        proto::SubscribePricesRequest {
+            reference_amounts_in_satoshis,
+            throttle_interval_in_milliseconds: query.throttle_interval_in_milliseconds,
+        },
+    );
+    let events = subscription
+        .take_while(|response| future::ready(response.is_ok()))
+        .filter_map(|response| future::ready(response.ok().and_then(|r| r.message)))
+        .map(|message| match message {
+            Message::Update(update) => Event::default().event("update").json_data(PriceUpdate {
+                ratio_in_cents_per_satoshis: update.ratio_in_cents_per_satoshis,
+                reference_prices: update
+                    .reference_prices
+                    .into_iter()
+                    .map(|price| ReferencePrice {
+                        amount_in_satoshis: price.amount_in_satoshis,
+                        buy_amount_in_cents: price.buy_amount_in_cents,
+                        sell_amount_in_cents: price.sell_amount_in_cents,
+                    })
+                    .collect(),
+                timestamp: update.timestamp,
+                metadata: update.metadata.map(PriceMetadata::from),
+            }),
+            Message::Stale(stale) => Event::default().event("stale").json_data(PriceStale {
+                reason: stale.reason,
+                last_update_timestamp: stale.last_update_timestamp,
+            }),
+        });
+    Ok(Sse::new(events))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_cents_from_sats_for_immediate_buy,
+        get_cents_from_sats_for_immediate_sell,
+        get_cents_from_sats_for_future_buy,
+        get_cents_from_sats_for_future_sell,
+        get_sats_from_cents_for_immediate_buy,
+        get_sats_from_cents_for_immediate_sell,
+        get_sats_from_cents_for_future_buy,
+        get_sats_from_cents_for_future_sell,
+        get_cents_per_sats_exchange_mid_rate,
+        subscribe_prices,
+        get_price_history,
+        get_fiat_from_sats,
+        get_sats_from_fiat,
+        get_fiat_per_sats_exchange_mid_rate,
+    ),
+    components(schemas(
+        CentsAmount,
+        SatsAmount,
+        ExchangeMidRate,
+        PriceMetadata,
+        ExchangeWeight,
+        PriceUpdate,
+        ReferencePrice,
+        PriceStale,
+        PriceHistory,
+        PriceCandle,
+        Ohlc,
+        FiatAmount,
+        FiatSatsAmount,
+        FiatMidRate,
+        FxRate,
+        GatewayErrorBody
+    ))
+)]
+pub struct PriceGatewayApiDoc;
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(PriceGatewayApiDoc::openapi())
+}
+
+pub(super) async fn start(
+    config: PriceGatewayConfig,
+    subscriptions: PriceSubscriptionConfig,
+    app: Arc<PriceApp>,
+) -> Result<(), std::io::Error> {
+    let router = Router::new()
+        .route(
+            "/v1/cents-from-sats/immediate-buy",
+            get(get_cents_from_sats_for_immediate_buy),
+        )
+        .route(
+            "/v1/cents-from-sats/immediate-sell",
+            get(get_cents_from_sats_for_immediate_sell),
+        )
+        .route(
+            "/v1/cents-from-sats/future-buy",
+            get(get_cents_from_sats_for_future_buy),
+        )
+        .route(
+            "/v1/cents-from-sats/future-sell",
+            get(get_cents_from_sats_for_future_sell),
+        )
+        .route(
+            "/v1/sats-from-cents/immediate-buy",
+            get(get_sats_from_cents_for_immediate_buy),
+        )
+        .route(
+            "/v1/sats-from-cents/immediate-sell",
+            get(get_sats_from_cents_for_immediate_sell),
+        )
+        .route(
+            "/v1/sats-from-cents/future-buy",
+            get(get_sats_from_cents_for_future_buy),
+        )
+        .route(
+            "/v1/sats-from-cents/future-sell",
+            get(get_sats_from_cents_for_future_sell),
+        )
+        .route(
+            "/v1/exchange-mid-rate",
+            get(get_cents_per_sats_exchange_mid_rate),
+        )
+        .route("/v1/prices/subscribe", get(subscribe_prices))
+        .route("/v1/price-history", get(get_price_history))
+        .route("/v1/fiat-from-sats", get(get_fiat_from_sats))
+        .route("/v1/sats-from-fiat", get(get_sats_from_fiat))
+        .route(
+            "/v1/fiat-mid-rate",
+            get(get_fiat_per_sats_exchange_mid_rate),
+        )
+        .route("/openapi.json", get(openapi))
+        .with_state(GatewayState { app, subscriptions });
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], config.listen_port));
+    axum::Server::try_bind(&addr)
+        .map_err(std::io::Error::other)?
+        .serve(router.into_make_service())
+        .await
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openapi_covers_every_rpc() {
+        let doc = PriceGatewayApiDoc::openapi();
+        assert_eq!(doc.paths.paths.len(), 14);
+        assert!(doc.paths.paths.contains_key("/v1/exchange-mid-rate"));
+    }
+}
@@ -2,6 +2,8 @@
 mod config;
 mod convert;
 mod error;
+mod gateway;
+mod subscription;
 
 #[allow(clippy::all)]
 pub mod proto {
@@ -11,21 +13,29 @@ pub mod proto {
 use opentelemetry::propagation::{Extractor, TextMapPropagator};
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use proto::{price_service_server::PriceService, *};
+use std::sync::Arc;
 use tonic::{transport::Server, Request, Response, Status};
 use tracing::instrument;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::app::*;
+use convert::{
+    candle_resolution, conversion_side, duration_from_secs, execution, fiat_currency, history_range,
+};
 
 pub use config::*;
 pub use error::*;
+pub use gateway::PriceGatewayApiDoc;
 
 pub struct Price {
-    app: PriceApp,
+    app: Arc<PriceApp>,
+    subscriptions: PriceSubscriptionConfig,
 }
 
 #[tonic::async_trait]
 impl PriceService for Price {
+    type SubscribePricesStream = subscription::PriceSubscription;
+
     #[instrument(name = "price_server.get_cents_from_sats_for_immediate_buy", skip_all,
         fields(amount_in_satoshis = request.get_ref().amount_in_satoshis,
                error, error.level, error.message),
@@ -39,12 +49,16 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_cents = self
+            let conversion = self
                 .app
                 .get_cents_from_sats_for_immediate_buy(Sats::from_major(req.amount_in_satoshis))
                 .await?;
             Ok(Response::new(GetCentsFromSatsForImmediateBuyResponse {
-                amount_in_cents: u64::try_from(amount_in_cents).map_err(PriceAppError::from)?,
+                amount_in_cents: u64::try_from(conversion.amount).map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_cents: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -63,12 +77,16 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_cents = self
+            let conversion = self
                 .app
                 .get_cents_from_sats_for_immediate_sell(Sats::from_major(req.amount_in_satoshis))
                 .await?;
             Ok(Response::new(GetCentsFromSatsForImmediateSellResponse {
-                amount_in_cents: u64::try_from(amount_in_cents).map_err(PriceAppError::from)?,
+                amount_in_cents: u64::try_from(conversion.amount).map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_cents: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -88,12 +106,19 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_cents = self
+            let conversion = self
                 .app
-                .get_cents_from_sats_for_future_buy(Sats::from_major(req.amount_in_satoshis))
+                .get_cents_from_sats_for_future_buy(
+                    Sats::from_major(req.amount_in_satoshis),
+                    duration_from_secs(req.time_in_seconds),
+                )
                 .await?;
             Ok(Response::new(GetCentsFromSatsForFutureBuyResponse {
-                amount_in_cents: u64::try_from(amount_in_cents).map_err(PriceAppError::from)?,
+                amount_in_cents: u64::try_from(conversion.amount).map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_cents: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -113,12 +138,19 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_cents = self
+            let conversion = self
                 .app
-                .get_cents_from_sats_for_future_sell(Sats::from_major(req.amount_in_satoshis))
+                .get_cents_from_sats_for_future_sell(
+                    Sats::from_major(req.amount_in_satoshis),
+                    duration_from_secs(req.time_in_seconds),
+                )
                 .await?;
             Ok(Response::new(GetCentsFromSatsForFutureSellResponse {
-                amount_in_cents: u64::try_from(amount_in_cents).map_err(PriceAppError::from)?,
+                amount_in_cents: u64::try_from(conversion.amount).map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_cents: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -137,13 +169,17 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_satoshis = self
+            let conversion = self
                 .app
                 .get_sats_from_cents_for_immediate_buy(UsdCents::from_major(req.amount_in_cents))
                 .await?;
             Ok(Response::new(GetSatsFromCentsForImmediateBuyResponse {
-                amount_in_satoshis: u64::try_from(amount_in_satoshis)
+                amount_in_satoshis: u64::try_from(conversion.amount)
                     .map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -162,13 +198,17 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_satoshis = self
+            let conversion = self
                 .app
                 .get_sats_from_cents_for_immediate_sell(UsdCents::from_major(req.amount_in_cents))
                 .await?;
             Ok(Response::new(GetSatsFromCentsForImmediateSellResponse {
-                amount_in_satoshis: u64::try_from(amount_in_satoshis)
+                amount_in_satoshis: u64::try_from(conversion.amount)
+                    .map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)
                     .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -188,13 +228,20 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_satoshis = self
+            let conversion = self
                 .app
-                .get_sats_from_cents_for_future_buy(UsdCents::from_major(req.amount_in_cents))
+                .get_sats_from_cents_for_future_buy(
+                    UsdCents::from_major(req.amount_in_cents),
+                    duration_from_secs(req.time_in_seconds),
+                )
                 .await?;
             Ok(Response::new(GetSatsFromCentsForFutureBuyResponse {
-                amount_in_satoshis: u64::try_from(amount_in_satoshis)
+                amount_in_satoshis: u64::try_from(conversion.amount)
+                    .map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)
                     .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -214,13 +261,20 @@ impl PriceService for Price {
             extract_tracing(&request);
 
             let req = request.into_inner();
-            let amount_in_satoshis = self
+            let conversion = self
                 .app
-                .get_sats_from_cents_for_future_sell(UsdCents::from_major(req.amount_in_cents))
+                .get_sats_from_cents_for_future_sell(
+                    UsdCents::from_major(req.amount_in_cents),
+                    duration_from_secs(req.time_in_seconds),
+                )
                 .await?;
             Ok(Response::new(GetSatsFromCentsForFutureSellResponse {
-                amount_in_satoshis: u64::try_from(amount_in_satoshis)
+                amount_in_satoshis: u64::try_from(conversion.amount)
                     .map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
             }))
         })
         .await
@@ -234,10 +288,170 @@ impl PriceService for Price {
         shared::tracing::record_error(tracing::Level::ERROR, || async move {
             extract_tracing(&request);
 
-            let ratio_in_cents_per_satoshis =
-                self.app.get_cents_per_sat_exchange_mid_rate().await?;
+            let mid_rate = self.app.get_cents_per_sat_exchange_mid_rate().await?;
             Ok(Response::new(GetCentsPerSatsExchangeMidRateResponse {
-                ratio_in_cents_per_satoshis,
+                ratio_in_cents_per_satoshis: mid_rate.cents_per_sat,
+                metadata: Some(mid_rate.metadata.into()),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "price_server.subscribe_prices", skip_all,
+        fields(reference_amounts_in_satoshis = ?request.get_ref().reference_amounts_in_satoshis,
+            throttle_interval_in_milliseconds = request.get_ref().throttle_interval_in_milliseconds,
+            error, error.level, error.message),
+        err
+    )]
+    async fn subscribe_prices(
+        &self,
+        request: Request<SubscribePricesRequest>,
+    ) -> Result<Response<Self::SubscribePricesStream>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+
+            Ok(Response::new(subscription::subscribe_prices(
+                Arc::clone(&self.app),
+                &self.subscriptions,
+                request.into_inner(),
+            )))
+        })
+        .await
+    }
+
+    #[instrument(name = "price_server.get_price_history", skip_all,
+        fields(resolution = request.get_ref().resolution,
+            from_timestamp = request.get_ref().from_timestamp,
+            to_timestamp = request.get_ref().to_timestamp,
+            error, error.level, error.message),
+        err
+    )]
+    async fn get_price_history(
+        &self,
+        request: Request<GetPriceHistoryRequest>,
+    ) -> Result<Response<GetPriceHistoryResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+
+            let req = request.into_inner();
+            let (from, to) = history_range(req.from_timestamp, req.to_timestamp)?;
+            let candles = self
+                .app
+                .get_price_history(candle_resolution(req.resolution)?, from, to)
+                .await?;
+            Ok(Response::new(GetPriceHistoryResponse {
+                candles: candles.into_iter().map(PriceCandle::from).collect(),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "price_server.get_fiat_from_sats", skip_all,
+        fields(currency = %request.get_ref().currency,
+            amount_in_satoshis = request.get_ref().amount_in_satoshis,
+            side = request.get_ref().side,
+            immediate_execution = request.get_ref().immediate_execution,
+            time_in_seconds = request.get_ref().time_in_seconds,
+            error, error.level, error.message),
+        err
+    )]
+    async fn get_fiat_from_sats(
+        &self,
+        request: Request<GetFiatFromSatsRequest>,
+    ) -> Result<Response<GetFiatFromSatsResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+
+            let req = request.into_inner();
+            let FiatPriceConversion {
+                conversion,
+                fx_rate,
+            } = self
+                .app
+                .get_fiat_from_sats(
+                    fiat_currency(&req.currency)?,
+                    Sats::from_major(req.amount_in_satoshis),
+                    conversion_side(req.side)?,
+                    execution(req.immediate_execution, req.time_in_seconds),
+                )
+                .await?;
+            Ok(Response::new(GetFiatFromSatsResponse {
+                currency: conversion.amount.currency().to_string(),
+                amount_in_minor_units: u64::try_from(conversion.amount)
+                    .map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_minor_units: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
+                fx_rate: Some(fx_rate.into()),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "price_server.get_sats_from_fiat", skip_all,
+        fields(currency = %request.get_ref().currency,
+            amount_in_minor_units = request.get_ref().amount_in_minor_units,
+            side = request.get_ref().side,
+            immediate_execution = request.get_ref().immediate_execution,
+            time_in_seconds = request.get_ref().time_in_seconds,
+            error, error.level, error.message),
+        err
+    )]
+    async fn get_sats_from_fiat(
+        &self,
+        request: Request<GetSatsFromFiatRequest>,
+    ) -> Result<Response<GetSatsFromFiatResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+
+            let req = request.into_inner();
+            let fiat =
+                FiatCents::from_major(fiat_currency(&req.currency)?, req.amount_in_minor_units);
+            let FiatPriceConversion {
+                conversion,
+                fx_rate,
+            } = self
+                .app
+                .get_sats_from_fiat(
+                    fiat,
+                    conversion_side(req.side)?,
+                    execution(req.immediate_execution, req.time_in_seconds),
+                )
+                .await?;
+            Ok(Response::new(GetSatsFromFiatResponse {
+                amount_in_satoshis: u64::try_from(conversion.amount)
+                    .map_err(PriceAppError::from)?,
+                metadata: Some(conversion.metadata.into()),
+                pre_fee_amount_in_satoshis: u64::try_from(conversion.pre_fee_amount)
+                    .map_err(PriceAppError::from)?,
+                fee_rate: f64::try_from(conversion.fee_rate).map_err(PriceAppError::from)?,
+                fx_rate: Some(fx_rate.into()),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "price_server.get_fiat_per_sats_exchange_mid_rate", skip_all,
+        fields(currency = %request.get_ref().currency, error, error.level, error.message),
+        err
+    )]
+    async fn get_fiat_per_sats_exchange_mid_rate(
+        &self,
+        request: Request<GetFiatPerSatsExchangeMidRateRequest>,
+    ) -> Result<Response<GetFiatPerSatsExchangeMidRateResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+
+            let req = request.into_inner();
+            let mid_rate = self
+                .app
+                .get_fiat_per_sat_exchange_mid_rate(fiat_currency(&req.currency)?)
+                .await?;
+            Ok(Response::new(GetFiatPerSatsExchangeMidRateResponse {
+                ratio_in_minor_units_per_satoshis: mid_rate.fiat_minor_units_per_sat,
+                metadata: Some(mid_rate.metadata.into()),
+                fx_rate: Some(mid_rate.fx_rate.into()),
             }))
         })
         .await
@@ -248,13 +462,30 @@ pub(crate) async fn start(
     server_config: PriceServerConfig,
     app: PriceApp,
 ) -> Result<(), PriceServerError> {
-    let price_service = Price { app };
-    Server::builder()
-        .add_service(proto::price_service_server::PriceServiceServer::new(
-            price_service,
-        ))
-        .serve(([0, 0, 0, 0], server_config.listen_port).into())
-        .await?;
+    let app = Arc::new(app);
+    let price_service = Price {
+        app: Arc::clone(&app),
+        subscriptions: server_config.subscriptions.clone(),
+    };
+    let listen_port = server_config.listen_port;
+    let grpc = async move {
+        Server::builder()
+            .add_service(proto::price_service_server::PriceServiceServer::new(
+                price_service,
+            ))
+            .serve(([0, 0, 0, 0], listen_port).into())
+            .await?;
+        Ok::<_, PriceServerError>(())
+    };
+    if server_config.gateway.enabled {
+        let gateway = async move {
+            gateway::start(server_config.gateway, server_config.subscriptions, app).await?;
+            Ok::<_, PriceServerError>(())
+        };
+        tokio::try_join!(grpc, gateway)?;
+    } else {
+        grpc.await?;
+    }
     Ok(())
 }
 
@@ -0,0 +1,118 @@
+use futures::{channel::mpsc, SinkExt};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::Status;
+
+use super::{
+    config::PriceSubscriptionConfig,
+    proto::{subscribe_prices_response::Message, *},
+};
+use pricing::OrderBookCacheError;
+
+use crate::app::*;
+
+pub(super) type PriceSubscription = mpsc::Receiver<Result<SubscribePricesResponse, Status>>;
+
+/// Streams prices to a single subscriber until it disconnects. Updates are
+/// triggered by order book updates but sent at most once per throttle interval.
+pub(super) fn subscribe_prices(
+    app: Arc<PriceApp>,
+    config: &PriceSubscriptionConfig,
+    request: SubscribePricesRequest,
+) -> PriceSubscription {
+    let throttle_interval = config
+        .min_throttle_interval
+        .max(std::time::Duration::from_millis(
+            request.throttle_interval_in_milliseconds,
+        ));
+    let reference_amounts = if request.reference_amounts_in_satoshis.is_empty() {
+        config.default_reference_amounts_in_satoshis.clone()
+    } else {
+        request.reference_amounts_in_satoshis
+    };
+    let recheck_interval = config.recheck_interval;
+
+    let (mut send, recv) = mpsc::channel(1);
+    let mut price_updated = app.subscribe_price_updates();
+    tokio::spawn(async move {
+        let mut stale = false;
+        loop {
+            let message = match price_update(&app, &reference_amounts).await {
+                Ok(update) => {
+                    stale = false;
+                    Some(Message::Update(update))
+                }
+                Err(PriceAppError::ExchangePriceCacheError(
+                    ExchangePriceCacheError::OrderBookCache(
+                        err @ OrderBookCacheError::OutdatedSnapshot(last_update),
+                    ),
+                )) if !stale => {
+                    stale = true;
+                    Some(Message::Stale(PriceStale {
+                        reason: err.to_string(),
+                        last_update_timestamp: last_update.timestamp(),
+                    }))
+                }
+                Err(PriceAppError::ExchangePriceCacheError(
+                    ExchangePriceCacheError::OrderBookCache(OrderBookCacheError::OutdatedSnapshot(
+                        _,
+                    )),
+                )) => None,
+                Err(err) => {
+                    tracing::warn!(error = %err, "price_server.subscribe_prices.update_failed");
+                    None
+                }
+            };
+            if let Some(message) = message {
+                let response = SubscribePricesResponse {
+                    message: Some(message),
+                };
+                if send.send(Ok(response)).await.is_err() {
+                    break;
+                }
+            }
+
+            tokio::time::sleep(throttle_interval).await;
+            if let Ok(Err(RecvError::Closed)) =
+                tokio::time::timeout(recheck_interval, price_updated.recv()).await
+            {
+                break;
+            }
+        }
+    });
+
+    recv
+}
+
+async fn price_update(
+    app: &PriceApp,
+    reference_amounts: &[u64],
+) -> Result<PriceUpdate, PriceAppError> {
+    let mid_rate = app.get_cents_per_sat_exchange_mid_rate().await?;
+    let mut timestamp = mid_rate.metadata.timestamp;
+    let mut reference_prices = Vec::with_capacity(reference_amounts.len());
+    for amount_in_satoshis in reference_amounts.iter().copied() {
+        let buy = app
+            .get_cents_from_sats_for_immediate_buy(Sats::from_major(amount_in_satoshis))
+            .await?;
+        let sell = app
+            .get_cents_from_sats_for_immediate_sell(Sats::from_major(amount_in_satoshis))
+            .await?;
+        for metadata in [&buy.metadata, &sell.metadata] {
+            if metadata.timestamp < timestamp {
+                timestamp = metadata.timestamp;
+            }
+        }
+        reference_prices.push(ReferencePrice {
+            amount_in_satoshis,
+            buy_amount_in_cents: u64::try_from(buy.amount)?,
+            sell_amount_in_cents: u64::try_from(sell.amount)?,
+        });
+    }
+    Ok(PriceUpdate {
+        ratio_in_cents_per_satoshis: mid_rate.cents_per_sat,
+        reference_prices,
+        timestamp: timestamp.timestamp(),
+        metadata: Some(mid_rate.metadata.into()),
+    })
+}
@@ -1,6 +1,9 @@
 use rust_decimal_macros::dec;
 
-use price_server::{app::*, ExchangePriceCacheConfig, OrderBookCacheError};
+use price_server::{
+    app::*, ExchangePriceCacheConfig, FxConfig, FxProviderConfig, FxRates, OrderBookCacheError,
+    PriceMixerConfig,
+};
 use shared::{payload::*, pubsub::*, time::*};
 
 fn load_fixture() -> OrderBookPayload {
@@ -30,9 +33,13 @@ async fn price_app() -> anyhow::Result<()> {
     let mut subscriber = tick_recv.resubscribe();
 
     let (_, recv) = futures::channel::mpsc::unbounded();
+    let (_, details_recv) = futures::channel::mpsc::unbounded();
 
     let ex_cfgs = ExchangeWeights {
         okex: Some(dec!(1.0)),
+        bitfinex: None,
+        binance: None,
+        kraken: None,
     };
 
     let base_fee_rate = dec!(0.001);
@@ -40,15 +47,20 @@ async fn price_app() -> anyhow::Result<()> {
     let delayed_fee_rate = dec!(0.1);
     let app = PriceApp::run(
         recv,
+        details_recv,
         PriceServerHealthCheckConfig::default(),
         FeeCalculatorConfig {
             base_fee_rate,
             immediate_fee_rate,
             delayed_fee_rate,
+            ..Default::default()
         },
         tick_recv,
         ExchangePriceCacheConfig::default(),
+        PriceMixerConfig::default(),
         ex_cfgs,
+        None,
+        None,
     )
     .await?;
 
@@ -95,76 +107,358 @@ async fn price_app() -> anyhow::Result<()> {
     let cents = app
         .get_cents_from_sats_for_immediate_buy(Sats::from_major(100_000_000))
         .await?;
-    assert_eq!(cents, UsdCents::from_major(98_900));
+    assert_eq!(cents.amount, UsdCents::from_major(98_900));
+    assert_eq!(cents.pre_fee_amount, UsdCents::from_major(100_000));
+    assert_eq!(cents.fee_rate, base_fee_rate + immediate_fee_rate);
 
     let cents = app
         .get_cents_from_sats_for_immediate_buy(Sats::from_major(1))
         .await?;
-    assert_eq!(cents, UsdCents::from_major(0));
+    assert_eq!(cents.amount, UsdCents::from_major(0));
 
     let cents = app
         .get_cents_from_sats_for_immediate_sell(Sats::from_major(100_000_000))
         .await?;
-    assert_eq!(cents, UsdCents::from_major(1_011_000));
+    assert_eq!(cents.amount, UsdCents::from_major(1_011_000));
 
     let cents = app
         .get_cents_from_sats_for_immediate_sell(Sats::from_major(1))
         .await?;
-    assert_eq!(cents, UsdCents::from_major(1));
+    assert_eq!(cents.amount, UsdCents::from_major(1));
 
     let cents = app
-        .get_cents_from_sats_for_future_buy(Sats::from_major(100_000_000))
+        .get_cents_from_sats_for_future_buy(Sats::from_major(100_000_000), chrono::Duration::zero())
         .await?;
-    assert_eq!(cents, UsdCents::from_major(89_900));
+    assert_eq!(cents.amount, UsdCents::from_major(89_900));
 
     let cents = app
-        .get_cents_from_sats_for_future_buy(Sats::from_major(1))
+        .get_cents_from_sats_for_future_buy(Sats::from_major(1), chrono::Duration::zero())
         .await?;
-    assert_eq!(cents, UsdCents::from_major(0));
+    assert_eq!(cents.amount, UsdCents::from_major(0));
 
     let cents = app
-        .get_cents_from_sats_for_future_sell(Sats::from_major(100_000_000))
+        .get_cents_from_sats_for_future_sell(
+            Sats::from_major(100_000_000),
+            chrono::Duration::zero(),
+        )
         .await?;
-    assert_eq!(cents, UsdCents::from_major(1_101_000));
+    assert_eq!(cents.amount, UsdCents::from_major(1_101_000));
     let cents = app
-        .get_cents_from_sats_for_future_sell(Sats::from_major(1))
+        .get_cents_from_sats_for_future_sell(Sats::from_major(1), chrono::Duration::zero())
         .await?;
-    assert_eq!(cents, UsdCents::from_major(1));
+    assert_eq!(cents.amount, UsdCents::from_major(1));
 
     let sats = app
         .get_sats_from_cents_for_immediate_buy(UsdCents::from_major(1000000))
         .await?;
-    assert_eq!(sats, Sats::from_major(1_011_000_000));
+    assert_eq!(sats.amount, Sats::from_major(1_011_000_000));
 
     let sats = app
         .get_sats_from_cents_for_immediate_sell(UsdCents::from_major(1000000))
         .await?;
-    assert_eq!(sats, Sats::from_major(98_900_000));
+    assert_eq!(sats.amount, Sats::from_major(98_900_000));
     let sats = app
         .get_sats_from_cents_for_immediate_sell(UsdCents::from_major(1))
         .await?;
-    assert_eq!(sats, Sats::from_major(98));
+    assert_eq!(sats.amount, Sats::from_major(98));
 
     let sats = app
-        .get_sats_from_cents_for_future_buy(UsdCents::from_major(1000000))
+        .get_sats_from_cents_for_future_buy(UsdCents::from_major(1000000), chrono::Duration::zero())
         .await?;
-    assert_eq!(sats, Sats::from_major(1_101_000_000));
+    assert_eq!(sats.amount, Sats::from_major(1_101_000_000));
     let sats = app
-        .get_sats_from_cents_for_future_buy(UsdCents::from_major(1))
+        .get_sats_from_cents_for_future_buy(UsdCents::from_major(1), chrono::Duration::zero())
         .await?;
-    assert_eq!(sats, Sats::from_major(1101));
+    assert_eq!(sats.amount, Sats::from_major(1101));
 
     let sats = app
-        .get_sats_from_cents_for_future_sell(UsdCents::from_major(1000000))
+        .get_sats_from_cents_for_future_sell(
+            UsdCents::from_major(1000000),
+            chrono::Duration::zero(),
+        )
         .await?;
-    assert_eq!(sats, Sats::from_major(89900000));
+    assert_eq!(sats.amount, Sats::from_major(89900000));
     let sats = app
-        .get_sats_from_cents_for_future_sell(UsdCents::from_major(1))
+        .get_sats_from_cents_for_future_sell(UsdCents::from_major(1), chrono::Duration::zero())
         .await?;
-    assert_eq!(sats, Sats::from_major(89));
+    assert_eq!(sats.amount, Sats::from_major(89));
 
     let ratio = app.get_cents_per_sat_exchange_mid_rate().await?;
-    assert_eq!(ratio, 0.0055);
+    assert_eq!(ratio.cents_per_sat, 0.0055);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn price_app_with_recorded_feed() -> anyhow::Result<()> {
+    let (tick_send, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (_, recv) = futures::channel::mpsc::unbounded();
+    let (_, details_recv) = futures::channel::mpsc::unbounded();
+
+    let app = PriceApp::run(
+        recv,
+        details_recv,
+        PriceServerHealthCheckConfig::default(),
+        FeeCalculatorConfig::default(),
+        tick_recv,
+        ExchangePriceCacheConfig::default(),
+        PriceMixerConfig::default(),
+        ExchangeWeights {
+            okex: Some(dec!(1.0)),
+            bitfinex: None,
+            binance: None,
+            kraken: None,
+        },
+        None,
+        None,
+    )
+    .await?;
+
+    let replay =
+        okex_price::FeedReplayConfig::new("../okex-price/tests/fixtures/recorded-feed.jsonl");
+    okex_price::replay(tick_send, replay).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let ratio = app.get_cents_per_sat_exchange_mid_rate().await?;
+    assert!(ratio.cents_per_sat > 0.0202 && ratio.cents_per_sat < 0.0204);
+
+    let cents = app
+        .get_cents_from_sats_for_immediate_buy(Sats::from_major(100_000_000))
+        .await?;
+    assert!(cents.amount.amount() > UsdCents::from_major(2_000_000).amount());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn price_app_mixes_exchange_order_books() -> anyhow::Result<()> {
+    let (tick_send, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (_, recv) = futures::channel::mpsc::unbounded();
+    let (_, details_recv) = futures::channel::mpsc::unbounded();
+
+    let app = PriceApp::run(
+        recv,
+        details_recv,
+        PriceServerHealthCheckConfig::default(),
+        FeeCalculatorConfig::default(),
+        tick_recv,
+        ExchangePriceCacheConfig {
+            stale_after_by_exchange: [(
+                KRAKEN_EXCHANGE_ID.to_string(),
+                chrono::Duration::try_seconds(120).unwrap(),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        },
+        // the synthetic books are far apart, keep both in the mix
+        PriceMixerConfig {
+            max_divergence_bps: dec!(10_000),
+            ..Default::default()
+        },
+        ExchangeWeights {
+            okex: Some(dec!(1.0)),
+            bitfinex: None,
+            binance: None,
+            kraken: Some(dec!(1.0)),
+        },
+        None,
+        None,
+    )
+    .await?;
+
+    let mut okex_book = load_fixture();
+    okex_book.timestamp = TimeStamp::now();
+    tick_send
+        .publish(PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(
+            okex_book,
+        ))
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let kraken_timestamp = TimeStamp::from(chrono::Utc::now().timestamp() as u64 - 60);
+    let kraken_book = OrderBookPayload {
+        bids: [(
+            PriceRaw::from(dec!(0.002)),
+            VolumeInCentsRaw::from(dec!(100_000_000)),
+        )]
+        .into_iter()
+        .collect(),
+        asks: [(
+            PriceRaw::from(dec!(0.012)),
+            VolumeInCentsRaw::from(dec!(100_000_000)),
+        )]
+        .into_iter()
+        .collect(),
+        timestamp: kraken_timestamp,
+        exchange: KRAKEN_EXCHANGE_ID.into(),
+    };
+    tick_send
+        .publish(PriceStreamPayload::KrakenBtcUsdOrderBookPayload(
+            kraken_book,
+        ))
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let ratio = app.get_cents_per_sat_exchange_mid_rate().await?;
+    assert_eq!(ratio.cents_per_sat, 0.00625);
+    assert_eq!(ratio.metadata.timestamp, kraken_timestamp);
+    assert_eq!(
+        ratio
+            .metadata
+            .sources
+            .iter()
+            .map(|source| source.exchange_id)
+            .collect::<Vec<_>>(),
+        vec![KRAKEN_EXCHANGE_ID, OKEX_EXCHANGE_ID]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn price_app_notifies_price_updates() -> anyhow::Result<()> {
+    let (tick_send, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (_, recv) = futures::channel::mpsc::unbounded();
+    let (_, details_recv) = futures::channel::mpsc::unbounded();
+
+    let app = PriceApp::run(
+        recv,
+        details_recv,
+        PriceServerHealthCheckConfig::default(),
+        FeeCalculatorConfig::default(),
+        tick_recv,
+        ExchangePriceCacheConfig::default(),
+        PriceMixerConfig::default(),
+        ExchangeWeights {
+            okex: Some(dec!(1.0)),
+            bitfinex: None,
+            binance: None,
+            kraken: None,
+        },
+        None,
+        None,
+    )
+    .await?;
+    let mut price_updated = app.subscribe_price_updates();
+
+    let mut payload = load_fixture();
+    payload.timestamp = TimeStamp::now();
+    tick_send
+        .publish(PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(payload))
+        .await?;
+
+    tokio::time::timeout(std::time::Duration::from_secs(1), price_updated.recv()).await??;
+    assert!(
+        app.get_cents_per_sat_exchange_mid_rate()
+            .await?
+            .cents_per_sat
+            > 0.0
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn price_app_converts_fiat_through_fx_rates() -> anyhow::Result<()> {
+    let (tick_send, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let mut subscriber = tick_recv.resubscribe();
+    let (_, recv) = futures::channel::mpsc::unbounded();
+    let (_, details_recv) = futures::channel::mpsc::unbounded();
+
+    let fx = FxRates::run(FxConfig {
+        enabled: true,
+        provider: FxProviderConfig::Mock {
+            rates: [("EUR".to_string(), dec!(0.5))].into_iter().collect(),
+        },
+        ..Default::default()
+    });
+    let app = PriceApp::run(
+        recv,
+        details_recv,
+        PriceServerHealthCheckConfig::default(),
+        FeeCalculatorConfig {
+            base_fee_rate: dec!(0.001),
+            immediate_fee_rate: dec!(0.01),
+            ..Default::default()
+        },
+        tick_recv,
+        ExchangePriceCacheConfig::default(),
+        PriceMixerConfig::default(),
+        ExchangeWeights {
+            okex: Some(dec!(1.0)),
+            bitfinex: None,
+            binance: None,
+            kraken: None,
+        },
+        None,
+        Some(fx),
+    )
+    .await?;
+
+    let mut payload = load_fixture();
+    payload.timestamp = TimeStamp::now();
+    tick_send
+        .publish(PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(payload))
+        .await?;
+    subscriber.next().await;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let eur = FiatCurrency::new("EUR")?;
+    let euros = app
+        .get_fiat_from_sats(
+            eur.clone(),
+            Sats::from_major(100_000_000),
+            ConversionSide::Buy,
+            Execution::Immediate,
+        )
+        .await?;
+    assert_eq!(euros.fx_rate.fiat_per_usd, dec!(0.5));
+    assert_eq!(
+        euros.conversion.pre_fee_amount,
+        FiatCents::from_major(eur.clone(), 50_000)
+    );
+    assert_eq!(
+        euros.conversion.amount,
+        FiatCents::from_major(eur.clone(), 49_450)
+    );
+
+    let euros = app
+        .get_fiat_from_sats(
+            eur.clone(),
+            Sats::from_major(100_000_000),
+            ConversionSide::Sell,
+            Execution::Immediate,
+        )
+        .await?;
+    assert_eq!(
+        euros.conversion.amount,
+        FiatCents::from_major(eur.clone(), 505_500)
+    );
+
+    let sats = app
+        .get_sats_from_fiat(
+            FiatCents::from_major(eur.clone(), 500_000),
+            ConversionSide::Buy,
+            Execution::Immediate,
+        )
+        .await?;
+    assert_eq!(sats.conversion.amount, Sats::from_major(1_011_000_000));
+
+    let ratio = app.get_fiat_per_sat_exchange_mid_rate(eur).await?;
+    assert_eq!(ratio.fiat_minor_units_per_sat, 0.00275);
+
+    let err = app
+        .get_fiat_per_sat_exchange_mid_rate(FiatCurrency::new("GBP")?)
+        .await;
+    assert!(matches!(
+        err,
+        Err(PriceAppError::FxError(FxError::UnsupportedCurrency(_)))
+    ));
 
     Ok(())
 }
@@ -0,0 +1,80 @@
+use chrono::Duration;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExchangePriceCacheConfig {
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_stale_after_duration")]
+    pub stale_after: Duration,
+    /// Overrides `stale_after` for individual exchanges
+    #[serde_as(as = "HashMap<_, serde_with::DurationSeconds<i64>>")]
+    #[serde(default)]
+    pub stale_after_by_exchange: HashMap<String, Duration>,
+    /// How long order book mid prices are kept to estimate realized volatility
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_volatility_window")]
+    pub volatility_window: Duration,
+    #[serde(default)]
+    pub aggregation: OrderBookAggregationConfig,
+    #[serde(default)]
+    pub dev_mock_price_btc_in_usd: Option<Decimal>,
+}
+
+impl ExchangePriceCacheConfig {
+    pub fn for_exchange(&self, exchange_id: &str) -> Self {
+        Self {
+            stale_after: self
+                .stale_after_by_exchange
+                .get(exchange_id)
+                .copied()
+                .unwrap_or(self.stale_after),
+            ..self.clone()
+        }
+    }
+}
+
+fn default_stale_after_duration() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(30)).unwrap()
+}
+
+fn default_volatility_window() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(900)).unwrap()
+}
+
+impl Default for ExchangePriceCacheConfig {
+    fn default() -> Self {
+        ExchangePriceCacheConfig {
+            stale_after: default_stale_after_duration(),
+            stale_after_by_exchange: HashMap::new(),
+            volatility_window: default_volatility_window(),
+            aggregation: OrderBookAggregationConfig::default(),
+            dev_mock_price_btc_in_usd: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct OrderBookAggregationConfig {
+    /// Price against the union of the order books that pass the mixer's consensus
+    /// check instead of mixing the prices of each exchange
+    #[serde(default)]
+    pub enabled: bool,
+    /// Fraction of an exchange's displayed depth that is not counted as hedgeable liquidity
+    #[serde(default)]
+    pub haircut_by_exchange: HashMap<String, Decimal>,
+}
+
+impl OrderBookAggregationConfig {
+    pub fn volume_factor(&self, exchange_id: &str) -> Decimal {
+        let haircut = self
+            .haircut_by_exchange
+            .get(exchange_id)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+        Decimal::ONE - haircut.clamp(Decimal::ZERO, Decimal::ONE)
+    }
+}
@@ -0,0 +1,293 @@
+mod convert;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CurrencyError {
+    #[error("CurrencyError: {0}")]
+    Unknown(#[from] rust_decimal::Error),
+    #[error("Can't convert {0} to {1}")]
+    Conversion(String, &'static str),
+    #[error("Unknown fiat currency: {0}")]
+    UnknownFiatCurrency(String),
+}
+
+macro_rules! currency {
+    ($name:ident, $code:ident) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(Decimal);
+
+        impl $name {
+            pub fn code() -> &'static str {
+                stringify!($code)
+            }
+
+            pub fn from_major(major: u64) -> Self {
+                Self(Decimal::from(major))
+            }
+
+            pub fn from_decimal(decimal: Decimal) -> Self {
+                Self(decimal)
+            }
+
+            pub fn amount(&self) -> &Decimal {
+                &self.0
+            }
+
+            pub fn floor(&self) -> Self {
+                Self(self.0.floor())
+            }
+
+            pub fn ceil(&self) -> Self {
+                Self(self.0.ceil())
+            }
+        }
+
+        impl std::ops::Mul<Decimal> for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: Decimal) -> Self::Output {
+                Self(self.0 * rhs)
+            }
+        }
+
+        impl std::ops::Add<&$name> for &$name {
+            type Output = $name;
+
+            fn add(self, rhs: &$name) -> Self::Output {
+                $name(self.0 + rhs.0)
+            }
+        }
+
+        impl std::ops::Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl std::ops::Div<&Decimal> for $name {
+            type Output = Self;
+
+            fn div(self, rhs: &Decimal) -> Self::Output {
+                Self(self.0 / rhs)
+            }
+        }
+
+        impl std::ops::Div<u32> for $name {
+            type Output = Self;
+
+            fn div(self, rhs: u32) -> Self::Output {
+                Self(self.0 / Decimal::from(rhs))
+            }
+        }
+
+        impl From<Decimal> for $name {
+            fn from(decimal: Decimal) -> Self {
+                Self(decimal)
+            }
+        }
+
+        impl From<$name> for Decimal {
+            fn from(amount: $name) -> Self {
+                amount.0
+            }
+        }
+
+        impl TryFrom<$name> for u64 {
+            type Error = CurrencyError;
+
+            fn try_from(value: $name) -> Result<Self, Self::Error> {
+                Ok(value.0.try_into()?)
+            }
+        }
+
+        impl TryFrom<$name> for f64 {
+            type Error = CurrencyError;
+
+            fn try_from(value: $name) -> Result<Self, Self::Error> {
+                Ok(value.0.try_into()?)
+            }
+        }
+
+        impl ExchangeableCurrency for $name {}
+    };
+}
+currency! { UsdCents, USD_CENT }
+currency! { Sats, SATOSHI }
+
+/// Amounts a `PriceMixer` can average across providers
+pub trait ExchangeableCurrency: Into<Decimal> + From<Decimal> {}
+impl ExchangeableCurrency for Decimal {}
+
+/// ISO 4217 code of a fiat currency that is priced through a USD/fiat FX rate
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FiatCurrency(String);
+
+impl FiatCurrency {
+    pub fn new(code: &str) -> Result<Self, CurrencyError> {
+        let code = code.trim().to_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) || code == "USD" {
+            return Err(CurrencyError::UnknownFiatCurrency(code));
+        }
+        Ok(Self(code))
+    }
+
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+
+    /// Decimal places of the ISO 4217 minor unit, e.g. 2 for EUR, 0 for JPY and 3 for KWD
+    pub fn exponent(&self) -> u32 {
+        match self.0.as_str() {
+            "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF"
+            | "UGX" | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+            "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+            _ => 2,
+        }
+    }
+
+    /// Minor units of the currency worth one USD cent at `fiat_per_usd`
+    pub fn minor_units_per_usd_cent(&self, fiat_per_usd: Decimal) -> Decimal {
+        fiat_per_usd * Decimal::from(10_u64.pow(self.exponent())) / Decimal::ONE_HUNDRED
+    }
+}
+
+impl std::fmt::Display for FiatCurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Amount in the minor unit of a fiat currency, see [`FiatCurrency::exponent`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FiatCents {
+    currency: FiatCurrency,
+    amount: Decimal,
+}
+
+impl FiatCents {
+    pub fn from_major(currency: FiatCurrency, major: u64) -> Self {
+        Self::from_decimal(currency, Decimal::from(major))
+    }
+
+    pub fn from_decimal(currency: FiatCurrency, amount: Decimal) -> Self {
+        Self { currency, amount }
+    }
+
+    pub fn from_usd_cents(currency: FiatCurrency, cents: &UsdCents, fiat_per_usd: Decimal) -> Self {
+        let amount = cents.amount() * currency.minor_units_per_usd_cent(fiat_per_usd);
+        Self::from_decimal(currency, amount)
+    }
+
+    pub fn to_usd_cents(&self, fiat_per_usd: Decimal) -> UsdCents {
+        UsdCents::from_decimal(self.amount / self.currency.minor_units_per_usd_cent(fiat_per_usd))
+    }
+
+    pub fn currency(&self) -> &FiatCurrency {
+        &self.currency
+    }
+
+    pub fn amount(&self) -> &Decimal {
+        &self.amount
+    }
+
+    pub fn floor(&self) -> Self {
+        Self::from_decimal(self.currency.clone(), self.amount.floor())
+    }
+
+    pub fn ceil(&self) -> Self {
+        Self::from_decimal(self.currency.clone(), self.amount.ceil())
+    }
+}
+
+impl std::ops::Mul<Decimal> for FiatCents {
+    type Output = Self;
+
+    fn mul(self, rhs: Decimal) -> Self::Output {
+        Self {
+            currency: self.currency,
+            amount: self.amount * rhs,
+        }
+    }
+}
+
+impl TryFrom<FiatCents> for u64 {
+    type Error = CurrencyError;
+
+    fn try_from(value: FiatCents) -> Result<Self, Self::Error> {
+        Ok(value.amount.try_into()?)
+    }
+}
+
+pub trait VolumePicker {
+    fn cents_from_sats(&self, volume: Sats) -> UsdCents;
+    fn sats_from_cents(&self, volume: UsdCents) -> Sats;
+}
+
+pub struct CurrencyConverter<'a> {
+    price_of_one_sat: &'a UsdCents,
+}
+
+impl VolumePicker for CurrencyConverter<'_> {
+    fn cents_from_sats(&self, volume: Sats) -> UsdCents {
+        UsdCents::from_decimal(volume.amount() * self.price_of_one_sat.amount())
+    }
+
+    fn sats_from_cents(&self, volume: UsdCents) -> Sats {
+        Sats::from_decimal(volume.amount() / self.price_of_one_sat.amount())
+    }
+}
+
+impl<'a> CurrencyConverter<'a> {
+    pub fn new(price_of_one_sat: &'a UsdCents) -> Self {
+        Self { price_of_one_sat }
+    }
+
+    pub fn cents_from_sats(&self, sats: Sats) -> UsdCents {
+        UsdCents::from_decimal(sats.amount() * self.price_of_one_sat.amount())
+    }
+
+    pub fn sats_from_cents(&self, cents: UsdCents) -> Sats {
+        Sats::from_decimal(cents.amount() / self.price_of_one_sat.amount())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn u64_try_from_usd_cents() {
+        let usd_cents = UsdCents::from_major(123);
+        let usd_cents_u64: u64 = usd_cents.try_into().unwrap();
+        assert_eq!(usd_cents_u64, 123);
+    }
+
+    #[test]
+    fn fiat_currency_codes() {
+        assert_eq!(FiatCurrency::new("eur").unwrap().code(), "EUR");
+        assert!(FiatCurrency::new("USD").is_err());
+        assert!(FiatCurrency::new("EURO").is_err());
+        let eur_cents = FiatCents::from_major(FiatCurrency::new("EUR").unwrap(), 150);
+        assert_eq!(u64::try_from(eur_cents * Decimal::new(15, 1)).unwrap(), 225);
+    }
+
+    #[test]
+    fn fiat_minor_units_follow_the_currency_exponent() {
+        let cents = UsdCents::from_major(100);
+        let fiat_per_usd = Decimal::from(150);
+        for (code, exponent, minor_units) in
+            [("EUR", 2, 15_000), ("JPY", 0, 150), ("KWD", 3, 150_000)]
+        {
+            let currency = FiatCurrency::new(code).unwrap();
+            assert_eq!(currency.exponent(), exponent);
+            let fiat = FiatCents::from_usd_cents(currency, &cents, fiat_per_usd);
+            assert_eq!(u64::try_from(fiat.clone()).unwrap(), minor_units);
+            assert_eq!(fiat.to_usd_cents(fiat_per_usd), cents);
+        }
+    }
+}
@@ -1,9 +1,8 @@
 use thiserror::Error;
 
+use crate::order_book_cache::OrderBookCacheError;
 use shared::time::*;
 
-use crate::cache::OrderBookCacheError;
-
 #[derive(Error, Debug)]
 pub enum ExchangePriceCacheError {
     #[error("StalePrice: last update was at {0}")]
@@ -12,4 +11,6 @@ pub enum ExchangePriceCacheError {
     NoPriceAvailable,
     #[error("OrderBook: {0:?}")]
     OrderBookCache(#[from] OrderBookCacheError),
+    #[error("InsufficientPriceConsensus: {agreeing} providers agree, {required} required")]
+    InsufficientPriceConsensus { agreeing: usize, required: usize },
 }
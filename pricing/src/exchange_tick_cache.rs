@@ -56,7 +56,7 @@ pub fn mock_price_tick(mock_price: rust_decimal::Decimal) -> BtcSatTick {
         timestamp: TimeStamp::now(),
         correlation_id: CorrelationId::new(),
         span_context: Span::current().context().span().span_context().clone(),
-        ask_price_of_one_sat: cent_price.clone(),
+        ask_price_of_one_sat: cent_price,
         bid_price_of_one_sat: cent_price,
     }
 }
@@ -82,6 +82,10 @@ impl SidePicker for BtcSatTick {
     fn mid_price_of_one_sat(&self) -> UsdCents {
         (&self.bid_price_of_one_sat + &self.ask_price_of_one_sat) / 2
     }
+
+    fn timestamp(&self) -> TimeStamp {
+        self.timestamp
+    }
 }
 
 struct ExchangePriceCacheInner {
@@ -0,0 +1,100 @@
+use rust_decimal::Decimal;
+use tokio::sync::broadcast;
+use tracing::{trace_span, Instrument};
+
+use shared::{
+    payload::{
+        ExchangeIdRaw, PriceStreamPayload, BINANCE_EXCHANGE_ID, BITFINEX_EXCHANGE_ID,
+        KRAKEN_EXCHANGE_ID, OKEX_EXCHANGE_ID,
+    },
+    pubsub::*,
+};
+
+use crate::{
+    fee_calculator::VolatilityEstimator, ExchangePriceCacheConfig, OrderBookCache, PriceMixer,
+    PriceMixerConfig,
+};
+
+pub struct ExchangeWeights {
+    pub okex: Option<Decimal>,
+    pub bitfinex: Option<Decimal>,
+    pub binance: Option<Decimal>,
+    pub kraken: Option<Decimal>,
+}
+
+impl ExchangeWeights {
+    pub fn by_exchange(&self) -> [(&'static str, Option<Decimal>); 4] {
+        [
+            (OKEX_EXCHANGE_ID, self.okex),
+            (BITFINEX_EXCHANGE_ID, self.bitfinex),
+            (BINANCE_EXCHANGE_ID, self.binance),
+            (KRAKEN_EXCHANGE_ID, self.kraken),
+        ]
+    }
+}
+
+/// Builds a mixer over one order book cache per weighted exchange, each kept up to date
+/// from the price feed. `price_updated` is notified after every applied snapshot.
+pub fn subscribe_price_mixer(
+    subscriber: &memory::Subscriber<PriceStreamPayload>,
+    price_cache_config: &ExchangePriceCacheConfig,
+    mixer_config: PriceMixerConfig,
+    exchange_weights: &ExchangeWeights,
+    price_updated: Option<broadcast::Sender<()>>,
+) -> (PriceMixer, VolatilityEstimator) {
+    let mut price_mixer = PriceMixer::new(mixer_config);
+    if price_cache_config.aggregation.enabled {
+        price_mixer.aggregate_order_books(price_cache_config.aggregation.clone());
+    }
+    let mut volatility = VolatilityEstimator::default();
+    for (exchange_id, weight) in exchange_weights.by_exchange() {
+        if let Some(weight) = weight.filter(|weight| *weight > Decimal::ZERO) {
+            let order_book_cache =
+                OrderBookCache::new(price_cache_config.for_exchange(exchange_id));
+            subscribe_order_book(
+                exchange_id,
+                subscriber.resubscribe(),
+                order_book_cache.clone(),
+                price_updated.clone(),
+            );
+            volatility.add_history(order_book_cache.mid_price_history());
+            price_mixer.add_provider(exchange_id, order_book_cache, weight);
+        }
+    }
+    (price_mixer, volatility)
+}
+
+fn subscribe_order_book(
+    exchange_id: &'static str,
+    mut subscriber: memory::Subscriber<PriceStreamPayload>,
+    order_book_cache: OrderBookCache,
+    price_updated: Option<broadcast::Sender<()>>,
+) {
+    let exchange = ExchangeIdRaw::from(exchange_id);
+    tokio::spawn(async move {
+        while let Some(msg) = subscriber.next().await {
+            let Some(price_msg) = msg
+                .payload
+                .order_book()
+                .filter(|book| book.exchange == exchange)
+            else {
+                continue;
+            };
+            let span = trace_span!(
+                "pricing.order_book_received",
+                exchange = exchange_id,
+                message_type = %msg.payload_type,
+                correlation_id = %msg.meta.correlation_id
+            );
+            shared::tracing::inject_tracing_data(&span, &msg.meta.tracing_data);
+            async {
+                order_book_cache.apply_update(price_msg.clone()).await;
+            }
+            .instrument(span)
+            .await;
+            if let Some(price_updated) = price_updated.as_ref() {
+                let _ = price_updated.send(());
+            }
+        }
+    });
+}
@@ -0,0 +1,153 @@
+use chrono::Duration;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FeeCalculatorConfig {
+    #[serde(default = "default_base_fee_rate")]
+    pub base_fee_rate: Decimal,
+    #[serde(default = "default_immediate_fee_rate")]
+    pub immediate_fee_rate: Decimal,
+    #[serde(default = "default_delayed_fee_rate")]
+    pub delayed_fee_rate: Decimal,
+    #[serde(default)]
+    pub volatility: VolatilityFeeConfig,
+    #[serde(default)]
+    pub tiers: Vec<FeeTier>,
+    #[serde(default)]
+    pub depth_surcharge: DepthSurchargeConfig,
+}
+
+/// Additional fee for conversions reaching a size band. The last listed tier
+/// whose threshold is reached by either amount applies.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FeeTier {
+    #[serde(default)]
+    pub min_amount_in_cents: Option<Decimal>,
+    #[serde(default)]
+    pub min_amount_in_sats: Option<Decimal>,
+    pub fee_bps: Decimal,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DepthSurchargeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Share of the volume at the best level a conversion may take before the surcharge applies
+    #[serde(default = "default_max_top_of_book_fraction")]
+    pub max_top_of_book_fraction: Decimal,
+    #[serde(default = "default_depth_surcharge_bps")]
+    pub surcharge_bps: Decimal,
+}
+
+impl Default for DepthSurchargeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_top_of_book_fraction: default_max_top_of_book_fraction(),
+            surcharge_bps: default_depth_surcharge_bps(),
+        }
+    }
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VolatilityFeeConfig {
+    /// Add the expected price move over the quote duration to `delayed_fee_rate`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Number of standard deviations of the expected price move that are charged
+    #[serde(default = "default_volatility_multiplier")]
+    pub multiplier: Decimal,
+    /// Duration assumed for delayed conversions that do not specify one
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_delayed_duration", alias = "duration")]
+    pub default_duration: Duration,
+    #[serde(default = "default_min_delayed_fee_rate")]
+    pub min_delayed_fee_rate: Decimal,
+    #[serde(default = "default_max_delayed_fee_rate")]
+    pub max_delayed_fee_rate: Decimal,
+}
+
+impl Default for VolatilityFeeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            multiplier: default_volatility_multiplier(),
+            default_duration: default_delayed_duration(),
+            min_delayed_fee_rate: default_min_delayed_fee_rate(),
+            max_delayed_fee_rate: default_max_delayed_fee_rate(),
+        }
+    }
+}
+
+fn default_base_fee_rate() -> Decimal {
+    dec!(0.0005)
+}
+
+fn default_immediate_fee_rate() -> Decimal {
+    dec!(0.0005)
+}
+
+fn default_delayed_fee_rate() -> Decimal {
+    dec!(0.0007)
+}
+
+fn default_volatility_multiplier() -> Decimal {
+    dec!(1)
+}
+
+fn default_delayed_duration() -> Duration {
+    Duration::try_hours(1).unwrap()
+}
+
+fn default_min_delayed_fee_rate() -> Decimal {
+    dec!(0.0007)
+}
+
+fn default_max_delayed_fee_rate() -> Decimal {
+    dec!(0.02)
+}
+
+fn default_max_top_of_book_fraction() -> Decimal {
+    dec!(0.5)
+}
+
+fn default_depth_surcharge_bps() -> Decimal {
+    dec!(10)
+}
+
+impl Default for FeeCalculatorConfig {
+    fn default() -> Self {
+        Self {
+            base_fee_rate: default_base_fee_rate(),
+            immediate_fee_rate: default_immediate_fee_rate(),
+            delayed_fee_rate: default_delayed_fee_rate(),
+            volatility: VolatilityFeeConfig::default(),
+            tiers: Vec::new(),
+            depth_surcharge: DepthSurchargeConfig::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        assert_eq!(
+            default_base_fee_rate(),
+            Decimal::from_str_exact("0.0005").unwrap()
+        );
+        assert_eq!(
+            default_immediate_fee_rate(),
+            Decimal::from_str_exact("0.0005").unwrap()
+        );
+        assert_eq!(
+            default_delayed_fee_rate(),
+            Decimal::from_str_exact("0.0007").unwrap()
+        );
+    }
+}
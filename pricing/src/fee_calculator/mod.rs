@@ -0,0 +1,310 @@
+mod config;
+mod volatility;
+
+use chrono::Duration;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use std::ops::Mul;
+
+pub use config::*;
+pub use volatility::*;
+
+const BPS: Decimal = dec!(10_000);
+
+/// Size of a conversion, used to pick the fee tier and depth surcharge
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConversionSize {
+    pub cents: Decimal,
+    pub sats: Decimal,
+    /// Volume at the best level of the side the conversion trades against
+    pub top_of_book_cents: Option<Decimal>,
+}
+
+pub struct FeeCalculator {
+    base_rate: Decimal,
+    immediate_rate: Decimal,
+    delayed_rate: Decimal,
+    delayed_horizon: Option<Duration>,
+    volatility: VolatilityFeeConfig,
+    tiers: Vec<FeeTier>,
+    depth_surcharge: DepthSurchargeConfig,
+}
+
+impl FeeCalculator {
+    pub fn new(
+        FeeCalculatorConfig {
+            base_fee_rate,
+            immediate_fee_rate,
+            delayed_fee_rate,
+            volatility,
+            tiers,
+            depth_surcharge,
+        }: FeeCalculatorConfig,
+    ) -> Self {
+        Self {
+            base_rate: base_fee_rate,
+            immediate_rate: base_fee_rate + immediate_fee_rate,
+            delayed_rate: base_fee_rate + delayed_fee_rate,
+            delayed_horizon: None,
+            volatility,
+            tiers,
+            depth_surcharge,
+        }
+    }
+
+    pub fn immediate_rate(&self) -> Decimal {
+        self.immediate_rate
+    }
+
+    /// Scales the delayed fee of conversions in proportion to their duration, so that
+    /// conversions executed within `horizon` pay the configured delayed rate
+    pub fn with_delayed_horizon(mut self, horizon: Duration) -> Self {
+        self.delayed_horizon = Some(horizon);
+        self
+    }
+
+    /// Rate for conversions executed within `duration`, or within the default delayed
+    /// horizon when `None`. When volatility based fees are enabled the delayed fee grows
+    /// with the expected price move over that duration, bounded by `min_delayed_fee_rate`
+    /// and `max_delayed_fee_rate`. Otherwise, if a delayed horizon is set, the fee on top
+    /// of the immediate rate grows linearly with the duration, reaching the delayed rate
+    /// at the horizon and exceeding it beyond.
+    pub fn delayed_rate_for(
+        &self,
+        duration: Option<Duration>,
+        volatility: Option<Decimal>,
+    ) -> Decimal {
+        if let Some(volatility) = volatility.filter(|_| self.volatility.enabled) {
+            return self.volatility_delayed_rate(
+                duration.unwrap_or(self.volatility.default_duration),
+                volatility,
+            );
+        }
+        match (duration, self.delayed_horizon) {
+            (Some(duration), Some(horizon)) if horizon > Duration::zero() => {
+                let share = Decimal::from(duration.num_milliseconds().max(0))
+                    / Decimal::from(horizon.num_milliseconds());
+                self.immediate_rate + (self.delayed_rate - self.immediate_rate) * share
+            }
+            _ => self.delayed_rate,
+        }
+    }
+
+    fn volatility_delayed_rate(&self, duration: Duration, volatility: Decimal) -> Decimal {
+        let secs = duration.num_milliseconds() as f64 / 1000.0;
+        let expected_move = volatility * Decimal::from_f64(secs.sqrt()).unwrap_or_default();
+        let delayed_fee_rate = (self.delayed_rate - self.base_rate
+            + self.volatility.multiplier * expected_move)
+            .clamp(
+                self.volatility.min_delayed_fee_rate,
+                self.volatility.max_delayed_fee_rate,
+            );
+        self.base_rate + delayed_fee_rate
+    }
+
+    /// Rate added on top of the immediate or delayed rate for the size of a conversion
+    pub fn size_rate(&self, size: &ConversionSize) -> Decimal {
+        let tier_bps = self
+            .tiers
+            .iter()
+            .rev()
+            .find(|tier| {
+                tier.min_amount_in_cents
+                    .is_some_and(|min| size.cents >= min)
+                    || tier.min_amount_in_sats.is_some_and(|min| size.sats >= min)
+            })
+            .map(|tier| tier.fee_bps)
+            .unwrap_or_default();
+        let surcharge_bps = match size.top_of_book_cents {
+            Some(top_of_book_cents)
+                if self.depth_surcharge.enabled
+                    && size.cents
+                        > top_of_book_cents * self.depth_surcharge.max_top_of_book_fraction =>
+            {
+                self.depth_surcharge.surcharge_bps
+            }
+            _ => Decimal::ZERO,
+        };
+        (tier_bps + surcharge_bps) / BPS
+    }
+
+    pub fn increase_by_rate<T: Mul<Decimal>>(
+        currency: T,
+        rate: Decimal,
+    ) -> <T as Mul<Decimal>>::Output {
+        currency * (dec!(1) + rate)
+    }
+
+    pub fn decrease_by_rate<T: Mul<Decimal>>(
+        currency: T,
+        rate: Decimal,
+    ) -> <T as Mul<Decimal>>::Output {
+        currency * (dec!(1) - rate)
+    }
+
+    pub fn increase_by_immediate_fee<T: Mul<Decimal>>(
+        &self,
+        currency: T,
+    ) -> <T as Mul<Decimal>>::Output {
+        Self::increase_by_rate(currency, self.immediate_rate)
+    }
+
+    pub fn increase_by_delayed_fee<T: Mul<Decimal>>(
+        &self,
+        currency: T,
+    ) -> <T as Mul<Decimal>>::Output {
+        Self::increase_by_rate(currency, self.delayed_rate)
+    }
+
+    pub fn decrease_by_immediate_fee<T: Mul<Decimal>>(
+        &self,
+        currency: T,
+    ) -> <T as Mul<Decimal>>::Output {
+        Self::decrease_by_rate(currency, self.immediate_rate)
+    }
+
+    pub fn decrease_by_delayed_fee<T: Mul<Decimal>>(
+        &self,
+        currency: T,
+    ) -> <T as Mul<Decimal>>::Output {
+        Self::decrease_by_rate(currency, self.delayed_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::*;
+
+    #[test]
+    fn fee_calculator() {
+        let fees = FeeCalculator::new(FeeCalculatorConfig {
+            base_fee_rate: dec!(0.001),
+            immediate_fee_rate: dec!(0.01),
+            delayed_fee_rate: dec!(0.1),
+            ..Default::default()
+        });
+
+        let usd_in = UsdCents::from_major(10_000);
+        assert_eq!(
+            fees.decrease_by_immediate_fee(usd_in),
+            UsdCents::from_major(10_000 - 110)
+        );
+        assert_eq!(
+            fees.decrease_by_delayed_fee(usd_in),
+            UsdCents::from_major(10_000 - 1010)
+        );
+        assert_eq!(
+            fees.increase_by_immediate_fee(usd_in),
+            UsdCents::from_major(10_000 + 110)
+        );
+        assert_eq!(
+            fees.increase_by_delayed_fee(usd_in),
+            UsdCents::from_major(10_000 + 1010)
+        );
+    }
+
+    #[test]
+    fn delayed_rate_scales_with_volatility_and_duration() {
+        let fees = FeeCalculator::new(FeeCalculatorConfig {
+            base_fee_rate: dec!(0.001),
+            immediate_fee_rate: dec!(0.01),
+            delayed_fee_rate: dec!(0.002),
+            volatility: VolatilityFeeConfig {
+                enabled: true,
+                multiplier: dec!(2),
+                default_duration: Duration::try_seconds(100).unwrap(),
+                min_delayed_fee_rate: dec!(0.001),
+                max_delayed_fee_rate: dec!(0.05),
+            },
+            ..Default::default()
+        });
+
+        let one_hour = Duration::try_seconds(3600).unwrap();
+        assert_eq!(fees.delayed_rate_for(Some(one_hour), None), dec!(0.003));
+        assert_eq!(
+            fees.delayed_rate_for(Some(one_hour), Some(dec!(0.0001))),
+            dec!(0.015)
+        );
+        assert_eq!(fees.delayed_rate_for(None, Some(dec!(0.0001))), dec!(0.005));
+        assert_eq!(
+            fees.delayed_rate_for(Some(one_hour), Some(dec!(0.01))),
+            dec!(0.051)
+        );
+    }
+
+    #[test]
+    fn delayed_rate_scales_with_duration() {
+        let config = FeeCalculatorConfig {
+            base_fee_rate: dec!(0.001),
+            immediate_fee_rate: dec!(0.001),
+            delayed_fee_rate: dec!(0.011),
+            ..Default::default()
+        };
+        let horizon = Duration::try_seconds(120).unwrap();
+        let fees = FeeCalculator::new(config.clone()).with_delayed_horizon(horizon);
+
+        let rate_for = |secs| fees.delayed_rate_for(Duration::try_seconds(secs), None);
+        assert_eq!(rate_for(0), dec!(0.002));
+        assert_eq!(rate_for(30), dec!(0.0045));
+        assert_eq!(rate_for(120), dec!(0.012));
+        assert_eq!(rate_for(300), dec!(0.027));
+        assert_eq!(fees.delayed_rate_for(None, None), dec!(0.012));
+
+        let flat = FeeCalculator::new(config);
+        assert_eq!(
+            flat.delayed_rate_for(Duration::try_seconds(30), None),
+            dec!(0.012)
+        );
+    }
+
+    #[test]
+    fn size_rate_uses_tier_and_depth_surcharge() {
+        let fees = FeeCalculator::new(FeeCalculatorConfig {
+            tiers: vec![
+                FeeTier {
+                    min_amount_in_cents: Some(dec!(100_000)),
+                    min_amount_in_sats: Some(dec!(5_000_000)),
+                    fee_bps: dec!(5),
+                },
+                FeeTier {
+                    min_amount_in_cents: Some(dec!(1_000_000)),
+                    min_amount_in_sats: None,
+                    fee_bps: dec!(10),
+                },
+            ],
+            depth_surcharge: DepthSurchargeConfig {
+                enabled: true,
+                max_top_of_book_fraction: dec!(0.5),
+                surcharge_bps: dec!(20),
+            },
+            ..Default::default()
+        });
+
+        let size = |cents, sats, top_of_book_cents| ConversionSize {
+            cents,
+            sats,
+            top_of_book_cents,
+        };
+        assert_eq!(
+            fees.size_rate(&size(dec!(1_000), dec!(50_000), None)),
+            dec!(0)
+        );
+        assert_eq!(
+            fees.size_rate(&size(dec!(1_000), dec!(5_000_000), None)),
+            dec!(0.0005)
+        );
+        assert_eq!(
+            fees.size_rate(&size(dec!(2_000_000), dec!(100_000_000), None)),
+            dec!(0.001)
+        );
+        assert_eq!(
+            fees.size_rate(&size(dec!(1_000), dec!(50_000), Some(dec!(1_999)))),
+            dec!(0.002)
+        );
+        assert_eq!(
+            fees.size_rate(&size(dec!(1_000), dec!(50_000), Some(dec!(2_000)))),
+            dec!(0)
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::Duration;
+use rust_decimal::prelude::*;
+use std::{collections::VecDeque, sync::Arc};
+use tokio::sync::RwLock;
+
+use shared::time::TimeStamp;
+
+/// Recent mid prices of a single order book, sampled at most once per second
+#[derive(Clone, Debug)]
+pub struct MidPriceHistory {
+    window: Duration,
+    max_samples: usize,
+    mid_prices: Arc<RwLock<VecDeque<(TimeStamp, Decimal)>>>,
+}
+
+impl MidPriceHistory {
+    pub fn new(window: Duration) -> Self {
+        let max_samples = window.num_seconds().max(0) as usize + 1;
+        Self {
+            window,
+            max_samples,
+            mid_prices: Arc::new(RwLock::new(VecDeque::with_capacity(max_samples))),
+        }
+    }
+
+    /// Updates within a second of the last sample replace its mid price
+    pub async fn record(&self, timestamp: TimeStamp, mid_price: Decimal) {
+        let mut mid_prices = self.mid_prices.write().await;
+        if let Some((last, last_mid_price)) = mid_prices.back_mut() {
+            if *last > timestamp {
+                return;
+            }
+            if timestamp - *last < Duration::try_seconds(1).expect("valid duration") {
+                *last_mid_price = mid_price;
+                return;
+            }
+        }
+        mid_prices.push_back((timestamp, mid_price));
+        while let Some((first, _)) = mid_prices.front() {
+            if timestamp - *first <= self.window && mid_prices.len() <= self.max_samples {
+                break;
+            }
+            mid_prices.pop_front();
+        }
+    }
+
+    /// Standard deviation of mid price returns scaled to one second
+    pub async fn realized_volatility(&self) -> Option<Decimal> {
+        let mid_prices = self.mid_prices.read().await;
+        let (first, _) = mid_prices.front()?;
+        let (last, _) = mid_prices.back()?;
+        let elapsed_secs = (*last - *first).num_milliseconds() as f64 / 1000.0;
+        if elapsed_secs <= 0.0 {
+            return None;
+        }
+        let sum_of_squared_returns = mid_prices
+            .iter()
+            .zip(mid_prices.iter().skip(1))
+            .filter(|((_, prev), _)| !prev.is_zero())
+            .filter_map(|((_, prev), (_, next))| ((next - prev) / prev).to_f64())
+            .map(|r| r * r)
+            .sum::<f64>();
+        Decimal::from_f64((sum_of_squared_returns / elapsed_secs).sqrt())
+    }
+}
+
+/// Estimates volatility across all tracked order books
+#[derive(Clone, Debug, Default)]
+pub struct VolatilityEstimator {
+    histories: Vec<MidPriceHistory>,
+}
+
+impl VolatilityEstimator {
+    pub fn add_history(&mut self, history: MidPriceHistory) {
+        self.histories.push(history);
+    }
+
+    /// The highest realized volatility of any order book, `None` while there is not enough data
+    pub async fn realized_volatility(&self) -> Option<Decimal> {
+        let mut volatilities = Vec::with_capacity(self.histories.len());
+        for history in self.histories.iter() {
+            volatilities.extend(history.realized_volatility().await);
+        }
+        volatilities.into_iter().max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn estimates_volatility_per_second() {
+        let history = MidPriceHistory::new(Duration::try_minutes(15).unwrap());
+        for (secs, mid_price) in [
+            (0, dec!(100)),
+            (1, dec!(101)),
+            (2, dec!(100)),
+            (4, dec!(101)),
+        ] {
+            history.record(TimeStamp::from(secs), mid_price).await;
+        }
+        let volatility = history.realized_volatility().await.unwrap();
+        assert!(volatility > dec!(0.0086) && volatility < dec!(0.0087));
+    }
+
+    #[tokio::test]
+    async fn drops_mid_prices_outside_window() {
+        let history = MidPriceHistory::new(Duration::try_seconds(10).unwrap());
+        history.record(TimeStamp::from(0), dec!(50)).await;
+        history.record(TimeStamp::from(100), dec!(100)).await;
+        assert_eq!(history.realized_volatility().await, None);
+        history.record(TimeStamp::from(101), dec!(100)).await;
+        assert_eq!(history.realized_volatility().await, Some(Decimal::ZERO));
+    }
+
+    #[tokio::test]
+    async fn keeps_one_mid_price_per_second() {
+        let history = MidPriceHistory::new(Duration::try_seconds(10).unwrap());
+        history.record(TimeStamp::from(0), dec!(100)).await;
+        history.record(TimeStamp::from(0), dec!(200)).await;
+        history.record(TimeStamp::from(1), dec!(100)).await;
+        assert_eq!(history.realized_volatility().await, Some(dec!(0.5)));
+    }
+
+    #[tokio::test]
+    async fn bounds_number_of_mid_prices() {
+        let history = MidPriceHistory::new(Duration::try_seconds(10).unwrap());
+        for secs in 0..100 {
+            history.record(TimeStamp::from(secs), dec!(100)).await;
+        }
+        assert_eq!(history.mid_prices.read().await.len(), 11);
+    }
+}
@@ -0,0 +1,23 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![cfg_attr(feature = "fail-on-warnings", deny(clippy::all))]
+
+mod cache_config;
+pub mod currency;
+mod error;
+mod exchange_tick_cache;
+mod exchange_weights;
+pub mod fee_calculator;
+mod order_book_cache;
+mod price_calculator;
+mod price_converter;
+mod price_mixer;
+
+pub use cache_config::*;
+pub use error::*;
+pub use exchange_tick_cache::*;
+pub use exchange_weights::*;
+pub use fee_calculator::FeeCalculatorConfig;
+pub use order_book_cache::*;
+pub use price_calculator::*;
+pub use price_converter::*;
+pub use price_mixer::*;
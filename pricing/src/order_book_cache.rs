@@ -15,7 +15,8 @@ use crate::{
     currency::{UsdCents, VolumePicker},
     error::ExchangePriceCacheError,
     exchange_tick_cache,
-    price_mixer::{PriceProvider, SidePicker},
+    fee_calculator::MidPriceHistory,
+    price_mixer::{BookLevel, BookLevels, PriceProvider, SidePicker, TopOfBook},
     ExchangePriceCacheConfig, VolumeBasedPriceConverter,
 };
 
@@ -34,6 +35,7 @@ pub enum OrderBookCacheError {
 #[derive(Debug, Clone)]
 pub struct OrderBookCache {
     inner: Arc<RwLock<SnapshotInner>>,
+    mid_prices: MidPriceHistory,
     config: ExchangePriceCacheConfig,
 }
 
@@ -52,12 +54,20 @@ impl OrderBookCache {
     pub fn new(config: ExchangePriceCacheConfig) -> Self {
         Self {
             inner: Arc::new(RwLock::new(SnapshotInner::new(config.stale_after))),
+            mid_prices: MidPriceHistory::new(config.volatility_window),
             config,
         }
     }
 
     pub async fn apply_update(&self, snapshot: OrderBookPayload) {
-        self.inner.write().await.update_snapshot(snapshot);
+        let mid_price = self.inner.write().await.update_snapshot(snapshot);
+        if let Some((timestamp, mid_price)) = mid_price {
+            self.mid_prices.record(timestamp, mid_price).await;
+        }
+    }
+
+    pub fn mid_price_history(&self) -> MidPriceHistory {
+        self.mid_prices.clone()
     }
 
     pub async fn latest_snapshot(&self) -> Result<OrderBookView, OrderBookCacheError> {
@@ -79,17 +89,23 @@ impl SnapshotInner {
         }
     }
 
-    fn update_snapshot(&mut self, snap: OrderBookPayload) {
+    /// Returns the mid price of the snapshot if it replaced the current one
+    fn update_snapshot(&mut self, snap: OrderBookPayload) -> Option<(TimeStamp, Decimal)> {
         let payload = snap;
 
         if let Some(ref snap) = self.snapshot {
             if snap.timestamp > payload.timestamp {
-                return;
+                return None;
             }
         }
 
         let snapshot = OrderBookView::from(payload);
+        let mid_price = snapshot
+            .mid_price_of_one_sat()
+            .ok()
+            .map(|mid_price| (snapshot.timestamp, mid_price));
         self.snapshot = Some(snapshot);
+        mid_price
     }
 
     fn current(&self) -> Result<OrderBookView, OrderBookCacheError> {
@@ -190,6 +206,36 @@ impl SidePicker for OrderBookView {
 
         UsdCents::from_decimal(mid_price)
     }
+
+    fn timestamp(&self) -> TimeStamp {
+        self.timestamp
+    }
+
+    fn top_of_book(&self) -> Option<TopOfBook> {
+        let (_, best_bid) = self.bids.iter().next_back()?;
+        let (_, best_ask) = self.asks.iter().next()?;
+        Some(TopOfBook {
+            bid_volume_in_cents: best_bid.inner(),
+            ask_volume_in_cents: best_ask.inner(),
+        })
+    }
+
+    fn levels(&self, exchange_id: &'static str, depth: usize) -> Vec<BookLevels> {
+        let level = |(price, volume): (&QuotePriceCentsForOneSat, &VolumeInCents)| BookLevel {
+            price_of_one_sat_in_cents: price.inner(),
+            volume_in_cents: volume.inner(),
+        };
+        vec![BookLevels {
+            exchange_id,
+            timestamp: self.timestamp,
+            bids: self.bids.iter().rev().take(depth).map(level).collect(),
+            asks: self.asks.iter().take(depth).map(level).collect(),
+        }]
+    }
+
+    fn order_book(&self) -> Option<&OrderBookView> {
+        Some(self)
+    }
 }
 
 impl OrderBookView {
@@ -209,6 +255,36 @@ impl OrderBookView {
         VolumeBasedPriceConverter::new(self.bids.iter().rev())
     }
 
+    /// Merges several order books into one consolidated book, scaling each book's volume
+    /// by its factor and summing the volume of levels that share a price.
+    pub fn combine<'a>(
+        books: impl IntoIterator<Item = (&'a OrderBookView, Decimal)>,
+    ) -> Result<Self, OrderBookCacheError> {
+        let mut combined: Option<Self> = None;
+        for (book, volume_factor) in books {
+            let view = combined.get_or_insert_with(|| Self {
+                asks: BTreeMap::new(),
+                bids: BTreeMap::new(),
+                timestamp: book.timestamp,
+            });
+            if book.timestamp < view.timestamp {
+                view.timestamp = book.timestamp;
+            }
+            for (side, levels) in [(&mut view.asks, &book.asks), (&mut view.bids, &book.bids)] {
+                for (price, volume) in levels {
+                    side.entry(price.clone())
+                        .or_insert(VolumeInCents(Decimal::ZERO))
+                        .0 += volume.0 * volume_factor;
+                }
+            }
+        }
+        let view = combined.ok_or(OrderBookCacheError::NoSnapshotAvailable)?;
+        if view.asks.is_empty() || view.bids.is_empty() {
+            return Err(OrderBookCacheError::EmptySide);
+        }
+        Ok(view)
+    }
+
     pub fn mid_price_of_one_sat(&self) -> Result<Decimal, OrderBookCacheError> {
         let best_ask = self.best_ask_price_of_one_sat()?;
         let best_bid = self.best_bid_price_of_one_sat()?;
@@ -216,6 +292,14 @@ impl OrderBookView {
         Ok((best_ask + best_bid) / dec!(2))
     }
 
+    /// The best bid is at or above the best ask
+    pub fn is_crossed(&self) -> bool {
+        matches!(
+            (self.best_bid_price_of_one_sat(), self.best_ask_price_of_one_sat()),
+            (Ok(bid), Ok(ask)) if bid >= ask
+        )
+    }
+
     fn best_bid_price_of_one_sat(&self) -> Result<Decimal, OrderBookCacheError> {
         let bids_length = self.bids.iter().next_back();
 
@@ -294,4 +378,66 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn levels_start_at_top_of_book() {
+        let mut asks = BTreeMap::new();
+        asks.insert(
+            QuotePriceCentsForOneSat(dec!(10_000)),
+            VolumeInCents(dec!(1)),
+        );
+        asks.insert(
+            QuotePriceCentsForOneSat(dec!(11_000)),
+            VolumeInCents(dec!(2)),
+        );
+        asks.insert(
+            QuotePriceCentsForOneSat(dec!(12_000)),
+            VolumeInCents(dec!(3)),
+        );
+        let mut bids = BTreeMap::new();
+        bids.insert(
+            QuotePriceCentsForOneSat(dec!(8_000)),
+            VolumeInCents(dec!(4)),
+        );
+        bids.insert(
+            QuotePriceCentsForOneSat(dec!(9_000)),
+            VolumeInCents(dec!(5)),
+        );
+
+        let snapshot = OrderBookView {
+            asks,
+            bids,
+            timestamp: TimeStamp::now(),
+        };
+        let levels = snapshot.levels("okex", 2).remove(0);
+        assert_eq!(levels.exchange_id, "okex");
+        assert_eq!(levels.timestamp, snapshot.timestamp);
+
+        assert_eq!(
+            levels.asks,
+            vec![
+                BookLevel {
+                    price_of_one_sat_in_cents: dec!(10_000),
+                    volume_in_cents: dec!(1)
+                },
+                BookLevel {
+                    price_of_one_sat_in_cents: dec!(11_000),
+                    volume_in_cents: dec!(2)
+                }
+            ]
+        );
+        assert_eq!(
+            levels.bids,
+            vec![
+                BookLevel {
+                    price_of_one_sat_in_cents: dec!(9_000),
+                    volume_in_cents: dec!(5)
+                },
+                BookLevel {
+                    price_of_one_sat_in_cents: dec!(8_000),
+                    volume_in_cents: dec!(4)
+                }
+            ]
+        );
+    }
 }
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use shared::time::TimeStamp;
+
+use crate::{currency::*, ExchangePriceCacheError, PriceProvider, SidePicker};
+
+pub struct DummyProvider {
+    ask_price_of_one_sat: Decimal,
+    sell_price_of_one_sat: Decimal,
+}
+impl DummyProvider {
+    pub fn new(ask_price_of_one_sat: UsdCents, sell_price_of_one_sat: UsdCents) -> Self {
+        Self {
+            ask_price_of_one_sat: ask_price_of_one_sat.into(),
+            sell_price_of_one_sat: sell_price_of_one_sat.into(),
+        }
+    }
+}
+#[async_trait]
+impl PriceProvider for DummyProvider {
+    async fn latest(&self) -> Result<Box<dyn SidePicker>, ExchangePriceCacheError> {
+        Ok(Box::new(DummySidePicker {
+            ask_price_of_one_sat: self.ask_price_of_one_sat,
+            sell_price_of_one_sat: self.sell_price_of_one_sat,
+        }))
+    }
+}
+
+pub struct DummySidePicker {
+    ask_price_of_one_sat: Decimal,
+    sell_price_of_one_sat: Decimal,
+}
+impl SidePicker for DummySidePicker {
+    fn buy_usd<'a>(&'a self) -> Box<dyn VolumePicker + 'a> {
+        Box::new(DummyVolumePicker {
+            price_of_one_sat: &self.ask_price_of_one_sat,
+        })
+    }
+    fn sell_usd<'a>(&'a self) -> Box<dyn VolumePicker + 'a> {
+        Box::new(DummyVolumePicker {
+            price_of_one_sat: &self.sell_price_of_one_sat,
+        })
+    }
+    fn mid_price_of_one_sat(&self) -> UsdCents {
+        UsdCents::from((self.ask_price_of_one_sat + self.sell_price_of_one_sat) / Decimal::TWO)
+    }
+    fn timestamp(&self) -> TimeStamp {
+        TimeStamp::now()
+    }
+}
+pub struct DummyVolumePicker<'a> {
+    price_of_one_sat: &'a Decimal,
+}
+impl VolumePicker for DummyVolumePicker<'_> {
+    fn cents_from_sats(&self, volume: Sats) -> UsdCents {
+        (Decimal::from(volume) * self.price_of_one_sat).into()
+    }
+    fn sats_from_cents(&self, volume: UsdCents) -> Sats {
+        (Decimal::from(volume) / self.price_of_one_sat).into()
+    }
+}
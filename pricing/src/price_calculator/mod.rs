@@ -0,0 +1,559 @@
+#[cfg(test)]
+mod dummy;
+
+use async_trait::async_trait;
+use chrono::Duration;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+use crate::{currency::*, fee_calculator::*, ExchangePriceCacheError, PriceMetadata, PriceMixer};
+
+/// When the priced conversion is executed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Execution {
+    Immediate,
+    /// Within the given duration, or within the default delayed horizon when `None`
+    Future(Option<Duration>),
+}
+
+#[derive(Debug)]
+pub struct ConversionResult {
+    pub sats: Sats,
+    pub cents: UsdCents,
+    pub sats_spread: Sats,
+    pub cents_spread: UsdCents,
+    pub pre_fee_sats: Sats,
+    pub pre_fee_cents: UsdCents,
+    pub fee_rate: Decimal,
+    pub metadata: PriceMetadata,
+}
+
+/// Amounts of a conversion through a fiat currency, see [`PriceCalculator::fiat_from_sats`]
+#[derive(Debug)]
+pub struct FxConversionResult {
+    pub amount: Decimal,
+    pub pre_fee_amount: Decimal,
+    pub fee_rate: Decimal,
+    pub metadata: PriceMetadata,
+}
+
+/// Signed rate added to the fee of every conversion priced by a [`PriceCalculator`]
+#[async_trait]
+pub trait FeeAdjustment {
+    async fn rate(&self, buy_usd: bool) -> Decimal;
+}
+
+/// Prices conversions from the mixed order books and applies the configured fees
+pub struct PriceCalculator {
+    fee_calculator: FeeCalculator,
+    price_mixer: Arc<PriceMixer>,
+    volatility: VolatilityEstimator,
+    adjustment: Option<Box<dyn FeeAdjustment + Send + Sync>>,
+    book_levels_depth: usize,
+}
+
+impl PriceCalculator {
+    pub fn new(
+        fee_cfg: FeeCalculatorConfig,
+        price_mixer: Arc<PriceMixer>,
+        volatility: VolatilityEstimator,
+    ) -> Self {
+        Self {
+            fee_calculator: FeeCalculator::new(fee_cfg),
+            price_mixer,
+            volatility,
+            adjustment: None,
+            book_levels_depth: 0,
+        }
+    }
+
+    /// Delayed fees are scaled by the conversion's duration relative to `horizon`
+    pub fn with_delayed_fee_horizon(mut self, horizon: Duration) -> Self {
+        self.fee_calculator = self.fee_calculator.with_delayed_horizon(horizon);
+        self
+    }
+
+    /// Records the `depth` best levels of each order book a conversion is priced from
+    pub fn with_book_levels(mut self, depth: usize) -> Self {
+        self.book_levels_depth = depth;
+        self
+    }
+
+    pub fn with_fee_adjustment(
+        mut self,
+        adjustment: impl FeeAdjustment + Send + Sync + 'static,
+    ) -> Self {
+        self.adjustment = Some(Box::new(adjustment));
+        self
+    }
+
+    /// Fee rate of a conversion, never negative
+    pub async fn fee_rate(
+        &self,
+        execution: Execution,
+        size: ConversionSize,
+        buy_usd: bool,
+    ) -> Decimal {
+        let size_rate = self.fee_calculator.size_rate(&size);
+        let rate = match execution {
+            Execution::Immediate => self.fee_calculator.immediate_rate() + size_rate,
+            Execution::Future(duration) => {
+                self.fee_calculator
+                    .delayed_rate_for(duration, self.volatility.realized_volatility().await)
+                    + size_rate
+            }
+        };
+        match self.adjustment.as_ref() {
+            Some(adjustment) => (rate + adjustment.rate(buy_usd).await).max(Decimal::ZERO),
+            None => rate,
+        }
+    }
+
+    pub async fn cents_from_sats_for_buy(
+        &self,
+        sats: Sats,
+        execution: Execution,
+    ) -> Result<ConversionResult, ExchangePriceCacheError> {
+        let res = self
+            .fiat_from_sats(sats, true, execution, Decimal::ONE)
+            .await?;
+        Ok(cents_conversion(sats, res))
+    }
+
+    pub async fn sats_from_cents_for_buy(
+        &self,
+        cents: UsdCents,
+        execution: Execution,
+    ) -> Result<ConversionResult, ExchangePriceCacheError> {
+        let res = self
+            .sats_from_fiat(*cents.amount(), true, execution, Decimal::ONE)
+            .await?;
+        Ok(sats_conversion(cents, res))
+    }
+
+    pub async fn cents_from_sats_for_sell(
+        &self,
+        sats: Sats,
+        execution: Execution,
+    ) -> Result<ConversionResult, ExchangePriceCacheError> {
+        let res = self
+            .fiat_from_sats(sats, false, execution, Decimal::ONE)
+            .await?;
+        Ok(cents_conversion(sats, res))
+    }
+
+    pub async fn sats_from_cents_for_sell(
+        &self,
+        cents: UsdCents,
+        execution: Execution,
+    ) -> Result<ConversionResult, ExchangePriceCacheError> {
+        let res = self
+            .sats_from_fiat(*cents.amount(), false, execution, Decimal::ONE)
+            .await?;
+        Ok(sats_conversion(cents, res))
+    }
+
+    /// Minor units of a fiat currency worth `fx_factor` of them per USD cent,
+    /// priced and rounded like the USD conversions
+    pub async fn fiat_from_sats(
+        &self,
+        sats: Sats,
+        buy_usd: bool,
+        execution: Execution,
+        fx_factor: Decimal,
+    ) -> Result<FxConversionResult, ExchangePriceCacheError> {
+        let (cents, metadata) = self
+            .price_mixer
+            .apply_with_levels(self.book_levels_depth, |p| {
+                if buy_usd {
+                    p.buy_usd().cents_from_sats(sats)
+                } else {
+                    p.sell_usd().cents_from_sats(sats)
+                }
+            })
+            .await?;
+        let size = conversion_size(sats, cents, top_of_book_cents(&metadata, buy_usd));
+        let fee_rate = self.fee_rate(execution, size, buy_usd).await;
+        let fiat = cents.amount() * fx_factor;
+        let (amount, pre_fee_amount) = if buy_usd {
+            (
+                FeeCalculator::decrease_by_rate(fiat, fee_rate).floor(),
+                fiat.floor(),
+            )
+        } else {
+            (
+                FeeCalculator::increase_by_rate(fiat, fee_rate).ceil(),
+                fiat.ceil(),
+            )
+        };
+        Ok(FxConversionResult {
+            amount,
+            pre_fee_amount,
+            fee_rate,
+            metadata,
+        })
+    }
+
+    /// Sats for `minor_units` of a fiat currency worth `fx_factor` of them per USD cent,
+    /// priced and rounded like the USD conversions
+    pub async fn sats_from_fiat(
+        &self,
+        minor_units: Decimal,
+        buy_usd: bool,
+        execution: Execution,
+        fx_factor: Decimal,
+    ) -> Result<FxConversionResult, ExchangePriceCacheError> {
+        let cents = UsdCents::from(minor_units / fx_factor);
+        let (sats, metadata) = self
+            .price_mixer
+            .apply_with_levels(self.book_levels_depth, |p| {
+                if buy_usd {
+                    p.buy_usd().sats_from_cents(cents)
+                } else {
+                    p.sell_usd().sats_from_cents(cents)
+                }
+            })
+            .await?;
+        let size = conversion_size(sats, cents, top_of_book_cents(&metadata, buy_usd));
+        let fee_rate = self.fee_rate(execution, size, buy_usd).await;
+        let sats = *sats.amount();
+        let (amount, pre_fee_amount) = if buy_usd {
+            (
+                FeeCalculator::increase_by_rate(sats, fee_rate).ceil(),
+                sats.ceil(),
+            )
+        } else {
+            (
+                FeeCalculator::decrease_by_rate(sats, fee_rate).floor(),
+                sats.floor(),
+            )
+        };
+        Ok(FxConversionResult {
+            amount,
+            pre_fee_amount,
+            fee_rate,
+            metadata,
+        })
+    }
+}
+
+fn cents_conversion(sats: Sats, res: FxConversionResult) -> ConversionResult {
+    let cents_after_fee = UsdCents::from(res.amount);
+    let cents = UsdCents::from(res.pre_fee_amount);
+    let cents_spread = cents_after_fee - cents;
+    let sats_spread = sats_spread(sats, cents, cents_after_fee);
+    ConversionResult {
+        sats,
+        cents: cents_after_fee,
+        sats_spread,
+        cents_spread,
+        pre_fee_sats: sats,
+        pre_fee_cents: cents,
+        fee_rate: res.fee_rate,
+        metadata: res.metadata,
+    }
+}
+
+fn sats_conversion(cents: UsdCents, res: FxConversionResult) -> ConversionResult {
+    let sats_after_fee = Sats::from(res.amount);
+    let sats = Sats::from(res.pre_fee_amount);
+    let sats_spread = sats_after_fee - sats;
+    let cents_spread = cents_spread(cents, sats, sats_after_fee);
+    ConversionResult {
+        sats: sats_after_fee,
+        cents,
+        sats_spread,
+        cents_spread,
+        pre_fee_sats: sats,
+        pre_fee_cents: cents,
+        fee_rate: res.fee_rate,
+        metadata: res.metadata,
+    }
+}
+
+fn top_of_book_cents(metadata: &PriceMetadata, buy_usd: bool) -> Option<Decimal> {
+    metadata.top_of_book.map(|book| {
+        if buy_usd {
+            book.bid_volume_in_cents
+        } else {
+            book.ask_volume_in_cents
+        }
+    })
+}
+
+fn conversion_size(
+    sats: Sats,
+    cents: UsdCents,
+    top_of_book_cents: Option<Decimal>,
+) -> ConversionSize {
+    ConversionSize {
+        cents: *cents.amount(),
+        sats: *sats.amount(),
+        top_of_book_cents,
+    }
+}
+
+fn sats_spread(sats: Sats, cents: UsdCents, cents_after_fee: UsdCents) -> Sats {
+    if cents_after_fee == UsdCents::from(Decimal::ZERO) {
+        return Sats::from(Decimal::ZERO);
+    }
+    Sats::from(
+        (sats.amount() * ((cents.amount() - cents_after_fee.amount()) / cents_after_fee.amount()))
+            .floor(),
+    )
+}
+
+fn cents_spread(cents: UsdCents, sats: Sats, sats_after_fee: Sats) -> UsdCents {
+    if sats_after_fee == Sats::from(Decimal::ZERO) {
+        return UsdCents::from(Decimal::ZERO);
+    }
+    UsdCents::from(
+        (cents.amount() * ((sats.amount() - sats_after_fee.amount()) / sats_after_fee.amount()))
+            .floor(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use super::{dummy::DummyProvider, *};
+    use crate::{PriceMixerConfig, PriceSource};
+
+    fn mixer() -> Arc<PriceMixer> {
+        let mut price_mixer = PriceMixer::new(PriceMixerConfig::default());
+        let dummy = DummyProvider::new(
+            UsdCents::from(Decimal::new(1_000_000_000, 12)),
+            UsdCents::from(Decimal::new(10_000_000_000, 12)),
+        );
+        price_mixer.add_provider("dummy", dummy, Decimal::ONE);
+        Arc::new(price_mixer)
+    }
+
+    fn fee_config() -> FeeCalculatorConfig {
+        FeeCalculatorConfig {
+            base_fee_rate: dec!(0.001),
+            immediate_fee_rate: dec!(0.01),
+            delayed_fee_rate: dec!(0.1),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn usd_buy() -> anyhow::Result<()> {
+        let calc = PriceCalculator::new(fee_config(), mixer(), VolatilityEstimator::default());
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(dec!(100_000_000)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(dec!(98_900)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-1_100)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(1_112_234)));
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(Decimal::ONE), Execution::Immediate)
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(Decimal::ZERO));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(0)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(0)));
+
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(dec!(100_000_000)), Execution::Future(None))
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(dec!(89_900)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-10_100)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(11_234_705)));
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(Decimal::ONE), Execution::Future(None))
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(Decimal::ZERO));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(0)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(0)));
+
+        let res = calc
+            .sats_from_cents_for_buy(UsdCents::from(dec!(1_000_000)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(1_011_000_000)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-10_881)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(11_000_000)));
+        let res = calc
+            .sats_from_cents_for_buy(UsdCents::from(Decimal::ONE), Execution::Immediate)
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(1_011)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-1)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(11)));
+
+        let res = calc
+            .sats_from_cents_for_buy(UsdCents::from(dec!(1_000_000)), Execution::Future(None))
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(1_101_000_000)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-91_735)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(101_000_000)));
+        let res = calc
+            .sats_from_cents_for_buy(UsdCents::from(Decimal::ONE), Execution::Future(None))
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(1_101)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-1)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(101)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spread_reflects_fee_tier() -> anyhow::Result<()> {
+        let fee_cfg = FeeCalculatorConfig {
+            tiers: vec![FeeTier {
+                min_amount_in_cents: None,
+                min_amount_in_sats: Some(dec!(100_000_000)),
+                fee_bps: dec!(10),
+            }],
+            ..fee_config()
+        };
+        let calc = PriceCalculator::new(fee_cfg, mixer(), VolatilityEstimator::default());
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(dec!(100_000_000)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.fee_rate, dec!(0.012));
+        assert_eq!(res.cents, UsdCents::from(dec!(98_800)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(-1_200)));
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(dec!(99_999_999)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.fee_rate, dec!(0.011));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn usd_sell() -> anyhow::Result<()> {
+        let calc = PriceCalculator::new(fee_config(), mixer(), VolatilityEstimator::default());
+        let res = calc
+            .cents_from_sats_for_sell(Sats::from(dec!(100_000_000)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(dec!(1_011_000)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(11_000)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(-1_088_032)));
+        let res = calc
+            .cents_from_sats_for_sell(Sats::from(Decimal::ONE), Execution::Immediate)
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(dec!(1)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(0)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(0)));
+
+        let res = calc
+            .cents_from_sats_for_sell(Sats::from(dec!(100_000_000)), Execution::Future(None))
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(dec!(1_101_000)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(101_000)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(-9_173_479)));
+        let res = calc
+            .cents_from_sats_for_sell(Sats::from(Decimal::ONE), Execution::Future(None))
+            .await?;
+        assert_eq!(res.cents, UsdCents::from(dec!(1)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(0)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(0)));
+
+        let res = calc
+            .sats_from_cents_for_sell(UsdCents::from(dec!(1_000_000)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(98_900_000)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(11_122)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(-1_100_000)));
+        let res = calc
+            .sats_from_cents_for_sell(UsdCents::from(Decimal::ONE), Execution::Immediate)
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(98)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(0)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(-2)));
+
+        let res = calc
+            .sats_from_cents_for_sell(UsdCents::from(dec!(1_000_000)), Execution::Future(None))
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(89_900_000)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(112_347)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(-10_100_000)));
+        let res = calc
+            .sats_from_cents_for_sell(UsdCents::from(Decimal::ONE), Execution::Future(None))
+            .await?;
+        assert_eq!(res.sats, Sats::from(dec!(89)));
+        assert_eq!(res.cents_spread, UsdCents::from(dec!(0)));
+        assert_eq!(res.sats_spread, Sats::from(dec!(-11)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sats_spread() {
+        let sats = Sats::from(dec!(1_000));
+        let cents = UsdCents::from(dec!(50));
+        let cents_after_fee = UsdCents::from(dec!(45));
+        let res = sats_spread(sats, cents, cents_after_fee);
+        assert_eq!(res, Sats::from(dec!(111)));
+
+        let sats = Sats::from(dec!(10));
+        let cents = UsdCents::from(Decimal::ONE);
+        let cents_after_fee = UsdCents::from(Decimal::ZERO);
+        let res = sats_spread(sats, cents, cents_after_fee);
+        assert_eq!(res, Sats::from(dec!(0)));
+    }
+
+    #[test]
+    fn test_cents_spread() {
+        let cents = UsdCents::from(dec!(50));
+        let sats = Sats::from(dec!(1_000));
+        let sats_after_fee = Sats::from(dec!(1_111));
+        let res = cents_spread(cents, sats, sats_after_fee);
+        assert_eq!(res, UsdCents::from(dec!(-5)));
+
+        let cents = UsdCents::from(Decimal::ONE);
+        let sats = Sats::from(Decimal::ONE);
+        let sats_after_fee = Sats::from(Decimal::ZERO);
+        let res = cents_spread(cents, sats, sats_after_fee);
+        assert_eq!(res, UsdCents::from(dec!(0)));
+    }
+
+    #[tokio::test]
+    async fn reports_pre_fee_amounts_and_sources() -> anyhow::Result<()> {
+        let calc = PriceCalculator::new(fee_config(), mixer(), VolatilityEstimator::default());
+        let res = calc
+            .cents_from_sats_for_buy(Sats::from(dec!(100_000_000)), Execution::Immediate)
+            .await?;
+        assert_eq!(res.pre_fee_cents, UsdCents::from(dec!(100_000)));
+        assert_eq!(res.pre_fee_sats, Sats::from(dec!(100_000_000)));
+        assert_eq!(res.fee_rate, dec!(0.011));
+        assert_eq!(
+            res.metadata.sources,
+            vec![PriceSource {
+                exchange_id: "dummy",
+                weight: Decimal::ONE,
+                timestamp: res.metadata.timestamp,
+                levels: Vec::new(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fiat_conversions_apply_fx_factor() -> anyhow::Result<()> {
+        let calc = PriceCalculator::new(fee_config(), mixer(), VolatilityEstimator::default());
+        let res = calc
+            .fiat_from_sats(
+                Sats::from(dec!(100_000_000)),
+                true,
+                Execution::Immediate,
+                dec!(0.9),
+            )
+            .await?;
+        assert_eq!(res.pre_fee_amount, dec!(90_000));
+        assert_eq!(res.amount, dec!(89_010));
+
+        let usd = calc
+            .sats_from_cents_for_sell(UsdCents::from(dec!(100_000)), Execution::Immediate)
+            .await?;
+        let res = calc
+            .sats_from_fiat(dec!(90_000), false, Execution::Immediate, dec!(0.9))
+            .await?;
+        assert_eq!(Sats::from(res.amount), usd.sats);
+        assert_eq!(Sats::from(res.pre_fee_amount), usd.pre_fee_sats);
+        Ok(())
+    }
+}
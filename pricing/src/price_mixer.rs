@@ -0,0 +1,884 @@
+use async_trait::async_trait;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    currency::*, error::ExchangePriceCacheError, OrderBookAggregationConfig, OrderBookView,
+};
+use shared::{
+    health::{HealthCheckResponse, HealthDetails},
+    payload::{BINANCE_EXCHANGE_ID, BITFINEX_EXCHANGE_ID, KRAKEN_EXCHANGE_ID, OKEX_EXCHANGE_ID},
+    time::TimeStamp,
+};
+use std::{collections::HashMap, sync::Arc};
+
+const BPS: Decimal = dec!(10_000);
+
+pub trait SidePicker {
+    fn buy_usd<'a>(&'a self) -> Box<dyn VolumePicker + 'a>;
+    fn sell_usd<'a>(&'a self) -> Box<dyn VolumePicker + 'a>;
+    fn mid_price_of_one_sat(&self) -> UsdCents;
+    fn timestamp(&self) -> TimeStamp;
+    fn top_of_book(&self) -> Option<TopOfBook> {
+        None
+    }
+    /// Best levels of each order book the prices are walked on, reported under
+    /// `exchange_id` unless the books belong to other venues. Empty for tick based prices.
+    fn levels(&self, _exchange_id: &'static str, _depth: usize) -> Vec<BookLevels> {
+        Vec::new()
+    }
+    /// The order book the prices are walked on, if any
+    fn order_book(&self) -> Option<&OrderBookView> {
+        None
+    }
+}
+
+/// Volume available at the best bid and ask
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TopOfBook {
+    pub bid_volume_in_cents: Decimal,
+    pub ask_volume_in_cents: Decimal,
+}
+
+/// Best price levels of one exchange's order book, ordered from the top of each side
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BookLevels {
+    pub exchange_id: &'static str,
+    pub timestamp: TimeStamp,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookLevel {
+    pub price_of_one_sat_in_cents: Decimal,
+    pub volume_in_cents: Decimal,
+}
+
+#[async_trait]
+pub trait PriceProvider {
+    async fn latest(&self) -> Result<Box<dyn SidePicker>, ExchangePriceCacheError>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixingMode {
+    /// Weighted average of the agreeing providers
+    #[default]
+    WeightedAverage,
+    /// Weighted median of the agreeing providers
+    Median,
+    /// Weighted average of the agreeing providers left after `trimmed_providers`
+    /// are dropped from each end
+    TrimmedMean,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceMixerConfig {
+    #[serde(default)]
+    pub mode: MixingMode,
+    /// Providers whose mid price deviates further from the weighted median of all
+    /// mid prices are excluded in every mode. With two providers the median is their
+    /// midpoint, so both are excluded once they drift twice this far apart.
+    #[serde(default = "default_max_divergence_bps")]
+    pub max_divergence_bps: Decimal,
+    /// Quoting halts when fewer providers agree with the consensus price, and always when none do
+    #[serde(default = "default_min_agreeing_providers")]
+    pub min_agreeing_providers: usize,
+    /// Agreeing providers with the highest and with the lowest mid prices that
+    /// `trimmed_mean` drops, as long as at least one provider remains
+    #[serde(default = "default_trimmed_providers")]
+    pub trimmed_providers: usize,
+}
+
+impl Default for PriceMixerConfig {
+    fn default() -> Self {
+        Self {
+            mode: MixingMode::default(),
+            max_divergence_bps: default_max_divergence_bps(),
+            min_agreeing_providers: default_min_agreeing_providers(),
+            trimmed_providers: default_trimmed_providers(),
+        }
+    }
+}
+
+fn default_max_divergence_bps() -> Decimal {
+    dec!(50)
+}
+fn default_min_agreeing_providers() -> usize {
+    1
+}
+fn default_trimmed_providers() -> usize {
+    1
+}
+
+struct ProviderPrice {
+    exchange_id: &'static str,
+    weight: Decimal,
+    mid_price: Decimal,
+    values: Vec<Decimal>,
+    timestamp: TimeStamp,
+    top_of_book: Option<TopOfBook>,
+    levels: Vec<BookLevels>,
+    /// Only kept when order books are aggregated, `values` are then left empty
+    book: Option<OrderBookView>,
+}
+
+/// Describes the snapshots a mixed price was derived from
+#[derive(Clone, Debug)]
+pub struct PriceMetadata {
+    /// Timestamp of the oldest contributing snapshot
+    pub timestamp: TimeStamp,
+    pub sources: Vec<PriceSource>,
+    /// Weighted average of the contributing order books' best levels
+    pub top_of_book: Option<TopOfBook>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriceSource {
+    pub exchange_id: &'static str,
+    pub weight: Decimal,
+    pub timestamp: TimeStamp,
+    /// Levels of each order book the price was derived from, only recorded by
+    /// `PriceMixer::apply_with_levels`
+    pub levels: Vec<BookLevels>,
+}
+
+impl PriceMetadata {
+    fn from_prices(prices: &[ProviderPrice]) -> Self {
+        let mut timestamp = prices[0].timestamp;
+        for price in prices.iter() {
+            if price.timestamp < timestamp {
+                timestamp = price.timestamp;
+            }
+        }
+        let mut sources = prices
+            .iter()
+            .map(|p| PriceSource {
+                exchange_id: p.exchange_id,
+                weight: p.weight,
+                timestamp: p.timestamp,
+                levels: p.levels.clone(),
+            })
+            .collect::<Vec<_>>();
+        sources.sort_by_key(|s| s.exchange_id);
+        Self {
+            timestamp,
+            sources,
+            top_of_book: weighted_top_of_book(prices),
+        }
+    }
+}
+
+/// Reports the price mixer as unhealthy while the divergence circuit breaker is tripped
+/// and details the providers the last mixed price excluded
+#[derive(Clone, Default)]
+pub struct PriceMixerHealth {
+    halted: Arc<RwLock<Option<String>>>,
+    excluded: Arc<RwLock<HealthDetails>>,
+}
+
+impl PriceMixerHealth {
+    pub async fn healthy(&self) -> HealthCheckResponse {
+        match self.halted.read().await.as_ref() {
+            Some(reason) => Err(reason.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Divergence in bps of each excluded provider, e.g. `excluded_kraken_bps`
+    pub async fn details(&self) -> HealthDetails {
+        self.excluded.read().await.clone()
+    }
+
+    async fn record(&self, halted: Option<String>, excluded: HealthDetails) {
+        *self.halted.write().await = halted;
+        *self.excluded.write().await = excluded;
+    }
+}
+
+fn excluded_key(exchange_id: &str) -> &'static str {
+    match exchange_id {
+        OKEX_EXCHANGE_ID => "excluded_okex_bps",
+        BITFINEX_EXCHANGE_ID => "excluded_bitfinex_bps",
+        BINANCE_EXCHANGE_ID => "excluded_binance_bps",
+        KRAKEN_EXCHANGE_ID => "excluded_kraken_bps",
+        _ => "excluded_other_bps",
+    }
+}
+
+pub struct PriceMixer {
+    providers: HashMap<&'static str, (Box<dyn PriceProvider + Sync + Send>, Decimal)>,
+    config: PriceMixerConfig,
+    health: PriceMixerHealth,
+    aggregation: Option<OrderBookAggregationConfig>,
+}
+
+impl PriceMixer {
+    pub fn new(config: PriceMixerConfig) -> Self {
+        Self {
+            providers: HashMap::new(),
+            config,
+            health: PriceMixerHealth::default(),
+            aggregation: None,
+        }
+    }
+
+    /// Walks prices on the union of the agreeing providers' order books instead of
+    /// mixing the prices of each provider. Provider weights scale the volume each
+    /// book contributes on top of the configured haircuts.
+    pub fn aggregate_order_books(&mut self, config: OrderBookAggregationConfig) {
+        self.aggregation = Some(config);
+    }
+
+    pub fn add_provider(
+        &mut self,
+        exchange_id: &'static str,
+        provider: impl PriceProvider + Sync + Send + 'static,
+        weight: Decimal,
+    ) {
+        self.providers
+            .insert(exchange_id, (Box::new(provider), weight));
+    }
+
+    pub fn health(&self) -> PriceMixerHealth {
+        self.health.clone()
+    }
+
+    pub async fn apply<R: ExchangeableCurrency>(
+        &self,
+        f: impl Fn(&Box<dyn SidePicker>) -> R,
+    ) -> Result<R, ExchangePriceCacheError> {
+        Ok(self.apply_with_metadata(f).await?.0)
+    }
+
+    pub async fn apply_with_metadata<R: ExchangeableCurrency>(
+        &self,
+        f: impl Fn(&Box<dyn SidePicker>) -> R,
+    ) -> Result<(R, PriceMetadata), ExchangePriceCacheError> {
+        let ([value], metadata) = self.apply_many(|p| [f(p).into()]).await?;
+        Ok((R::from(value), metadata))
+    }
+
+    /// Also records the `depth` best levels per side of every contributing order book
+    pub async fn apply_with_levels<R: ExchangeableCurrency>(
+        &self,
+        depth: usize,
+        f: impl Fn(&Box<dyn SidePicker>) -> R,
+    ) -> Result<(R, PriceMetadata), ExchangePriceCacheError> {
+        let ([value], metadata) = self.mix(|p| [f(p).into()], depth).await?;
+        Ok((R::from(value), metadata))
+    }
+
+    /// Mixes several values that are all derived from the same snapshot of each provider
+    pub async fn apply_many<const N: usize>(
+        &self,
+        f: impl Fn(&Box<dyn SidePicker>) -> [Decimal; N],
+    ) -> Result<([Decimal; N], PriceMetadata), ExchangePriceCacheError> {
+        self.mix(f, 0).await
+    }
+
+    async fn mix<const N: usize>(
+        &self,
+        f: impl Fn(&Box<dyn SidePicker>) -> [Decimal; N],
+        levels_depth: usize,
+    ) -> Result<([Decimal; N], PriceMetadata), ExchangePriceCacheError> {
+        let mut prices = Vec::new();
+        let mut prev_error: Option<ExchangePriceCacheError> = None;
+        for (exchange_id, (provider, weight)) in self.providers.iter() {
+            let side_picker = match provider.latest().await {
+                Ok(side_picker) => side_picker,
+                Err(err) => {
+                    prev_error = Some(err);
+                    continue;
+                }
+            };
+            let book = self
+                .aggregation
+                .as_ref()
+                .and_then(|_| side_picker.order_book().cloned());
+            prices.push(ProviderPrice {
+                exchange_id,
+                weight: *weight,
+                mid_price: *side_picker.mid_price_of_one_sat().amount(),
+                values: match book {
+                    Some(_) => Vec::new(),
+                    None => f(&side_picker).to_vec(),
+                },
+                timestamp: side_picker.timestamp(),
+                top_of_book: side_picker.top_of_book(),
+                levels: match levels_depth {
+                    0 => Vec::new(),
+                    depth => side_picker.levels(exchange_id, depth),
+                },
+                book,
+            });
+        }
+
+        if prices.iter().map(|p| p.weight).sum::<Decimal>() <= Decimal::ZERO {
+            return Err(prev_error.unwrap_or(ExchangePriceCacheError::NoPriceAvailable));
+        }
+
+        let agreeing = self.exclude_outliers(exclude_crossed(prices)).await?;
+        if let Some(config) = self.aggregation.as_ref() {
+            if agreeing.iter().any(|p| p.book.is_some()) {
+                return aggregate(config, agreeing, f);
+            }
+        }
+        let mixed = match self.config.mode {
+            MixingMode::TrimmedMean => trim(agreeing, self.config.trimmed_providers),
+            _ => agreeing,
+        };
+        let metadata = PriceMetadata::from_prices(&mixed);
+        let values = std::array::from_fn(|idx| match self.config.mode {
+            MixingMode::Median => weighted_median(mixed.iter().map(|p| (p.values[idx], p.weight))),
+            _ => weighted_average(&mixed, idx),
+        });
+        Ok((values, metadata))
+    }
+
+    async fn exclude_outliers(
+        &self,
+        prices: Vec<ProviderPrice>,
+    ) -> Result<Vec<ProviderPrice>, ExchangePriceCacheError> {
+        let consensus = weighted_median(prices.iter().map(|p| (p.mid_price, p.weight)));
+        let (agreeing, excluded): (Vec<_>, Vec<_>) = prices.into_iter().partition(|p| {
+            divergence_bps(p.mid_price, consensus) <= self.config.max_divergence_bps
+        });
+        let mut details = HealthDetails::new();
+        for price in excluded.iter() {
+            let divergence = divergence_bps(price.mid_price, consensus);
+            tracing::warn!(
+                exchange_id = price.exchange_id,
+                mid_price = %price.mid_price,
+                consensus = %consensus,
+                divergence_bps = %divergence,
+                "price_mixer.provider_excluded"
+            );
+            details.insert(
+                excluded_key(price.exchange_id),
+                divergence.ceil().to_u64().unwrap_or(u64::MAX),
+            );
+        }
+
+        // Mixing needs at least one agreeing provider even when none are required
+        let required = self.config.min_agreeing_providers.max(1);
+        if agreeing.len() < required {
+            let err = ExchangePriceCacheError::InsufficientPriceConsensus {
+                agreeing: agreeing.len(),
+                required,
+            };
+            let excluded_ids = excluded.iter().map(|p| p.exchange_id).collect::<Vec<_>>();
+            tracing::error!(error = %err, excluded = ?excluded_ids, "price_mixer.halted");
+            self.health
+                .record(
+                    Some(format!("{} - excluded: {:?}", err, excluded_ids)),
+                    details,
+                )
+                .await;
+            return Err(err);
+        }
+        self.health.record(None, details).await;
+        Ok(agreeing)
+    }
+}
+
+impl Default for PriceMixer {
+    fn default() -> Self {
+        Self::new(PriceMixerConfig::default())
+    }
+}
+
+fn exclude_crossed(prices: Vec<ProviderPrice>) -> Vec<ProviderPrice> {
+    prices
+        .into_iter()
+        .filter(|p| {
+            let crossed = p.book.as_ref().map(|b| b.is_crossed()).unwrap_or(false);
+            if crossed {
+                tracing::warn!(
+                    exchange_id = p.exchange_id,
+                    "price_mixer.crossed_book_excluded"
+                );
+            }
+            !crossed
+        })
+        .collect()
+}
+
+/// Walks `f` on the combined order book of the providers that have one
+fn aggregate<const N: usize>(
+    config: &OrderBookAggregationConfig,
+    prices: Vec<ProviderPrice>,
+    f: impl Fn(&Box<dyn SidePicker>) -> [Decimal; N],
+) -> Result<([Decimal; N], PriceMetadata), ExchangePriceCacheError> {
+    let prices = prices
+        .into_iter()
+        .filter(|p| p.book.is_some())
+        .collect::<Vec<_>>();
+    let max_weight = prices.iter().map(|p| p.weight).max().unwrap_or_default();
+    if max_weight <= Decimal::ZERO {
+        return Err(ExchangePriceCacheError::NoPriceAvailable);
+    }
+    let combined = OrderBookView::combine(prices.iter().filter_map(|p| {
+        let volume_factor = config.volume_factor(p.exchange_id) * p.weight / max_weight;
+        p.book.as_ref().map(|book| (book, volume_factor))
+    }))?;
+    let mut metadata = PriceMetadata::from_prices(&prices);
+    metadata.top_of_book = SidePicker::top_of_book(&combined);
+    let side_picker: Box<dyn SidePicker> = Box::new(combined);
+    Ok((f(&side_picker), metadata))
+}
+
+fn weighted_top_of_book(prices: &[ProviderPrice]) -> Option<TopOfBook> {
+    let books = prices
+        .iter()
+        .filter_map(|p| p.top_of_book.map(|book| (book, p.weight)))
+        .collect::<Vec<_>>();
+    let total_weights = books.iter().map(|(_, weight)| *weight).sum::<Decimal>();
+    if total_weights <= Decimal::ZERO {
+        return None;
+    }
+    Some(TopOfBook {
+        bid_volume_in_cents: books
+            .iter()
+            .map(|(book, weight)| book.bid_volume_in_cents * weight)
+            .sum::<Decimal>()
+            / total_weights,
+        ask_volume_in_cents: books
+            .iter()
+            .map(|(book, weight)| book.ask_volume_in_cents * weight)
+            .sum::<Decimal>()
+            / total_weights,
+    })
+}
+
+/// Drops the `count` providers with the highest and the `count` with the lowest mid price
+fn trim(mut prices: Vec<ProviderPrice>, count: usize) -> Vec<ProviderPrice> {
+    if prices.len() <= count * 2 {
+        return prices;
+    }
+    prices.sort_by_key(|p| p.mid_price);
+    prices.truncate(prices.len() - count);
+    prices.split_off(count)
+}
+
+fn weighted_average(prices: &[ProviderPrice], idx: usize) -> Decimal {
+    let total_weights = prices.iter().map(|p| p.weight).sum::<Decimal>();
+    prices
+        .iter()
+        .map(|p| p.values[idx] * p.weight)
+        .sum::<Decimal>()
+        / total_weights
+}
+
+/// Averages the two middle values when the cumulative weight splits exactly in half
+fn weighted_median(values: impl Iterator<Item = (Decimal, Decimal)>) -> Decimal {
+    let mut values = values.collect::<Vec<_>>();
+    values.sort_by_key(|(value, _)| *value);
+    let half = values.iter().map(|(_, weight)| weight).sum::<Decimal>() / dec!(2);
+    let mut cumulative = Decimal::ZERO;
+    for (idx, (value, weight)) in values.iter().enumerate() {
+        cumulative += weight;
+        if cumulative > half {
+            return *value;
+        }
+        if cumulative == half {
+            if let Some((next, _)) = values.get(idx + 1) {
+                return (value + next) / dec!(2);
+            }
+            return *value;
+        }
+    }
+    Decimal::ZERO
+}
+
+fn divergence_bps(price: Decimal, consensus: Decimal) -> Decimal {
+    if consensus.is_zero() {
+        return Decimal::ZERO;
+    }
+    ((price - consensus) / consensus).abs() * BPS
+}
+
+#[cfg(test)]
+mod tests {
+    pub use rust_decimal::Decimal;
+    use shared::payload::{
+        ExchangeIdRaw, OrderBookPayload, PriceMessagePayload, PriceRaw, VolumeInCentsRaw,
+    };
+    use shared::pubsub::CorrelationId;
+    use shared::time::TimeStamp;
+
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+
+    pub use super::*;
+    pub use crate::currency::UsdCents;
+    use crate::exchange_tick_cache::mock_price_tick;
+    pub use crate::{
+        cache_config::ExchangePriceCacheConfig, currency::VolumePicker,
+        exchange_tick_cache::ExchangeTickCache, OrderBookCache,
+    };
+    use serde_json::Error;
+
+    #[tokio::test]
+    async fn test_price_mixer() -> anyhow::Result<(), Error> {
+        let cache = ExchangeTickCache::new(ExchangePriceCacheConfig::default());
+        let mut price_mixer = PriceMixer::new(PriceMixerConfig::default());
+        price_mixer.add_provider("okex", cache.clone(), Decimal::from(1));
+
+        cache
+            .apply_update(get_payload(), CorrelationId::new())
+            .await;
+
+        let price = price_mixer
+            .apply(|p| {
+                *p.sell_usd()
+                    .sats_from_cents(UsdCents::from_decimal(Decimal::ONE))
+                    .amount()
+            })
+            .await
+            .expect("Price should be available");
+        assert_ne!(Decimal::ZERO, price);
+        Ok(())
+    }
+
+    struct MockProvider(Decimal);
+
+    #[async_trait]
+    impl PriceProvider for MockProvider {
+        async fn latest(&self) -> Result<Box<dyn SidePicker>, ExchangePriceCacheError> {
+            Ok(Box::new(mock_price_tick(self.0)))
+        }
+    }
+
+    fn mixer(mode: MixingMode, min_agreeing_providers: usize, prices: &[Decimal]) -> PriceMixer {
+        let mut price_mixer = PriceMixer::new(PriceMixerConfig {
+            mode,
+            max_divergence_bps: dec!(50),
+            min_agreeing_providers,
+            ..Default::default()
+        });
+        for (exchange_id, price) in ["okex", "bitfinex", "binance", "kraken"].iter().zip(prices) {
+            price_mixer.add_provider(exchange_id, MockProvider(*price), Decimal::ONE);
+        }
+        price_mixer
+    }
+
+    #[tokio::test]
+    async fn trimmed_mean_excludes_diverging_provider() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::TrimmedMean,
+            2,
+            &[dec!(10_000), dec!(10_010), dec!(9_990), dec!(12_000)],
+        );
+        let price = price_mixer
+            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        assert_eq!(price, dec!(0.01));
+        assert!(price_mixer.health().healthy().await.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trimmed_mean_drops_highest_and_lowest_provider() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::TrimmedMean,
+            2,
+            &[dec!(10_000), dec!(10_010), dec!(9_990), dec!(10_040)],
+        );
+        let (price, metadata) = price_mixer
+            .apply_with_metadata(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        assert_eq!(price, dec!(0.010005));
+        assert_eq!(
+            metadata
+                .sources
+                .iter()
+                .map(|source| source.exchange_id)
+                .collect::<Vec<_>>(),
+            vec!["bitfinex", "okex"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn weighted_average_excludes_diverging_provider() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::WeightedAverage,
+            3,
+            &[dec!(10_000), dec!(10_010), dec!(9_990), dec!(12_000)],
+        );
+        let price = price_mixer
+            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        assert_eq!(price, dec!(0.01));
+        assert!(price_mixer.health().healthy().await.is_ok());
+        assert_eq!(
+            price_mixer.health().details().await,
+            HealthDetails::from([("excluded_kraken_bps", 1995)])
+        );
+
+        let price_mixer = mixer(
+            MixingMode::WeightedAverage,
+            4,
+            &[dec!(10_000), dec!(10_010), dec!(9_990), dec!(12_000)],
+        );
+        assert!(price_mixer
+            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .await
+            .is_err());
+        assert!(price_mixer.health().healthy().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn metadata_lists_agreeing_providers() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::WeightedAverage,
+            2,
+            &[dec!(10_000), dec!(10_010), dec!(9_990), dec!(12_000)],
+        );
+        let (_, metadata) = price_mixer
+            .apply_with_metadata(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        assert_eq!(
+            metadata
+                .sources
+                .iter()
+                .map(|source| source.exchange_id)
+                .collect::<Vec<_>>(),
+            vec!["binance", "bitfinex", "okex"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn apply_many_mixes_each_value() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::Median,
+            1,
+            &[dec!(10_000), dec!(10_020), dec!(5_000)],
+        );
+        let ([mid, double_mid], metadata) = price_mixer
+            .apply_many(|p| {
+                let mid = *p.mid_price_of_one_sat().amount();
+                [mid, mid * dec!(2)]
+            })
+            .await?;
+        assert_eq!(mid, dec!(0.01001));
+        assert_eq!(double_mid, dec!(0.02002));
+        assert_eq!(metadata.sources.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn median_ignores_diverging_provider() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::Median,
+            1,
+            &[dec!(10_000), dec!(10_020), dec!(5_000)],
+        );
+        let price = price_mixer
+            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        assert_eq!(price, dec!(0.01001));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn halts_when_no_provider_agrees() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::WeightedAverage,
+            0,
+            &[dec!(10_000), dec!(11_000)],
+        );
+        let result = price_mixer
+            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExchangePriceCacheError::InsufficientPriceConsensus {
+                agreeing: 0,
+                required: 1
+            })
+        ));
+        assert_eq!(price_mixer.health().details().await.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn halts_without_price_consensus() -> anyhow::Result<()> {
+        let price_mixer = mixer(
+            MixingMode::TrimmedMean,
+            2,
+            &[dec!(10_000), dec!(11_000), dec!(12_000)],
+        );
+        let result = price_mixer
+            .apply(|p| *p.mid_price_of_one_sat().amount())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExchangePriceCacheError::InsufficientPriceConsensus {
+                agreeing: 1,
+                required: 2
+            })
+        ));
+        assert!(price_mixer.health().healthy().await.is_err());
+        Ok(())
+    }
+
+    fn order_book(bid: Decimal, ask: Decimal) -> OrderBookPayload {
+        OrderBookPayload {
+            bids: [(PriceRaw::from(bid), VolumeInCentsRaw::from(dec!(100)))]
+                .into_iter()
+                .collect(),
+            asks: [(PriceRaw::from(ask), VolumeInCentsRaw::from(dec!(100)))]
+                .into_iter()
+                .collect(),
+            timestamp: TimeStamp::now(),
+            exchange: ExchangeIdRaw::from("okex".to_string()),
+        }
+    }
+
+    async fn aggregating_mixer(
+        min_agreeing_providers: usize,
+        books: &[(&'static str, Decimal, Decimal, Decimal)],
+    ) -> PriceMixer {
+        let mut price_mixer = PriceMixer::new(PriceMixerConfig {
+            min_agreeing_providers,
+            ..Default::default()
+        });
+        let mut aggregation = OrderBookAggregationConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        aggregation
+            .haircut_by_exchange
+            .insert("kraken".to_string(), dec!(0.5));
+        price_mixer.aggregate_order_books(aggregation);
+        for (exchange_id, bid, ask, weight) in books {
+            let cache = OrderBookCache::new(ExchangePriceCacheConfig::default());
+            cache.apply_update(order_book(*bid, *ask)).await;
+            price_mixer.add_provider(exchange_id, cache, *weight);
+        }
+        price_mixer
+    }
+
+    #[tokio::test]
+    async fn aggregation_walks_combined_depth() -> anyhow::Result<()> {
+        let price_mixer = aggregating_mixer(
+            2,
+            &[
+                ("okex", dec!(0.99), dec!(1.01), dec!(1)),
+                ("kraken", dec!(0.98), dec!(1.02), dec!(1)),
+            ],
+        )
+        .await;
+        let (sats, metadata) = price_mixer
+            .apply_with_levels(1, |p| {
+                p.sell_usd()
+                    .sats_from_cents(UsdCents::from_decimal(dec!(151)))
+            })
+            .await?;
+        assert_eq!(
+            sats.amount().round_dp(6),
+            (dec!(100) / dec!(1.01) + dec!(51) / dec!(1.02)).round_dp(6)
+        );
+        assert_eq!(
+            metadata.top_of_book.map(|book| book.ask_volume_in_cents),
+            Some(dec!(100))
+        );
+        let kraken = &metadata.sources[0];
+        assert_eq!(kraken.exchange_id, "kraken");
+        assert_eq!(
+            kraken.levels[0].asks[0].price_of_one_sat_in_cents,
+            dec!(1.02)
+        );
+        assert_eq!(kraken.levels[0].asks[0].volume_in_cents, dec!(100));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn aggregation_scales_depth_by_weight() -> anyhow::Result<()> {
+        let price_mixer = aggregating_mixer(
+            2,
+            &[
+                ("okex", dec!(0.99), dec!(1.01), dec!(1)),
+                ("binance", dec!(0.98), dec!(1.02), dec!(0.5)),
+            ],
+        )
+        .await;
+        let sats = price_mixer
+            .apply(|p| {
+                p.sell_usd()
+                    .sats_from_cents(UsdCents::from_decimal(dec!(150)))
+            })
+            .await?;
+        assert_eq!(
+            sats.amount().round_dp(6),
+            (dec!(100) / dec!(1.01) + dec!(50) / dec!(1.02)).round_dp(6)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn aggregation_excludes_diverging_and_crossed_books() -> anyhow::Result<()> {
+        let books = [
+            ("okex", dec!(0.99), dec!(1.01), dec!(1)),
+            ("bitfinex", dec!(0.991), dec!(1.011), dec!(1)),
+            ("kraken", dec!(1.98), dec!(2.02), dec!(1)),
+            ("binance", dec!(1.01), dec!(0.99), dec!(1)),
+        ];
+        let price_mixer = aggregating_mixer(2, &books).await;
+        let (_, metadata) = price_mixer
+            .apply_with_metadata(|p| *p.mid_price_of_one_sat().amount())
+            .await?;
+        assert_eq!(
+            metadata
+                .sources
+                .iter()
+                .map(|source| source.exchange_id)
+                .collect::<Vec<_>>(),
+            vec!["bitfinex", "okex"]
+        );
+
+        let price_mixer = aggregating_mixer(3, &books).await;
+        assert!(matches!(
+            price_mixer
+                .apply(|p| *p.mid_price_of_one_sat().amount())
+                .await,
+            Err(ExchangePriceCacheError::InsufficientPriceConsensus {
+                agreeing: 2,
+                required: 3
+            })
+        ));
+        assert!(price_mixer.health().healthy().await.is_err());
+        Ok(())
+    }
+
+    fn get_payload() -> PriceMessagePayload {
+        let raw = r#"{
+            "exchange": "okex",
+            "instrumentId": "BTC-USD-SWAP",
+            "timestamp": 1,
+            "bidPrice": {
+                "numeratorUnit": "USD_CENT",
+                "denominatorUnit": "BTC_SAT",
+                "offset": 12,
+                "base": "1000000000"
+            },
+            "askPrice": {
+                "numeratorUnit": "USD_CENT",
+                "denominatorUnit": "BTC_SAT",
+                "offset": 12,
+                "base": "10000000000"
+            }
+            }"#;
+        let mut price_message_payload =
+            serde_json::from_str::<PriceMessagePayload>(raw).expect("Could not parse payload");
+        price_message_payload.timestamp = TimeStamp::now();
+        price_message_payload
+    }
+}
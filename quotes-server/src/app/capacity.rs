@@ -0,0 +1,170 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::error::QuotesAppError;
+
+#[serde_with::serde_as]
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct HedgingCapacityConfig {
+    /// Check buy quotes against the liability the OKX account can still hedge
+    #[serde(default)]
+    pub enabled: bool,
+    /// Capacity is unknown once the hedging engine has not reported for this long
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_stale_after")]
+    pub stale_after: Duration,
+    /// Reduce quotes above the remaining capacity to it instead of rejecting them
+    #[serde(default)]
+    pub shrink_oversized_quotes: bool,
+}
+
+impl Default for HedgingCapacityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stale_after: default_stale_after(),
+            shrink_oversized_quotes: false,
+        }
+    }
+}
+
+fn default_stale_after() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(60)).expect("bad default stale_after")
+}
+
+/// Estimate of the USD liability the OKX account can still hedge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HedgingCapacity {
+    /// Notional the OKX collateral supports at the leverage high bound
+    pub max_cents: Decimal,
+    /// Liability currently allocated to OKX
+    pub allocated_cents: Decimal,
+    /// Open buy quotes that will be allocated once accepted
+    pub reserved_cents: Decimal,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The hedging engine reported its balances within `stale_after`
+    pub hedging_healthy: bool,
+}
+
+impl HedgingCapacity {
+    pub fn remaining_cents(&self) -> Decimal {
+        (self.max_cents - self.allocated_cents - self.reserved_cents).max(Decimal::ZERO)
+    }
+
+    pub(super) fn estimate(
+        snapshot: Option<HedgingCapacitySnapshot>,
+        allocated_cents: Decimal,
+        reserved_cents: Decimal,
+        stale_after: Duration,
+        now: DateTime<Utc>,
+    ) -> Self {
+        match snapshot {
+            Some(snapshot) => Self {
+                max_cents: snapshot.btc_balance
+                    * snapshot.usd_cents_per_btc
+                    * snapshot.high_bound_ratio_leverage,
+                allocated_cents,
+                reserved_cents,
+                updated_at: Some(snapshot.updated_at),
+                hedging_healthy: now - snapshot.updated_at <= stale_after,
+            },
+            None => Self {
+                max_cents: Decimal::ZERO,
+                allocated_cents,
+                reserved_cents,
+                updated_at: None,
+                hedging_healthy: false,
+            },
+        }
+    }
+}
+
+/// OKX balances last recorded by the hedging engine
+pub(super) struct HedgingCapacitySnapshot {
+    btc_balance: Decimal,
+    usd_cents_per_btc: Decimal,
+    high_bound_ratio_leverage: Decimal,
+    updated_at: DateTime<Utc>,
+}
+
+impl HedgingCapacitySnapshot {
+    pub(super) async fn load(pool: &sqlx::PgPool) -> Result<Option<Self>, QuotesAppError> {
+        let snapshot = sqlx::query_as!(
+            Self,
+            r#"SELECT trading_btc_total_balance + funding_btc_total_balance AS "btc_balance!",
+                 usd_cents_per_btc, high_bound_ratio_leverage, updated_at
+               FROM okex_hedging_capacity"#
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn snapshot(updated_at: DateTime<Utc>) -> HedgingCapacitySnapshot {
+        HedgingCapacitySnapshot {
+            btc_balance: dec!(0.5),
+            usd_cents_per_btc: dec!(2_000_000),
+            high_bound_ratio_leverage: dec!(3),
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn remaining_capacity() {
+        let now = Utc::now();
+        let capacity = HedgingCapacity::estimate(
+            Some(snapshot(now)),
+            dec!(1_000_000),
+            dec!(500_000),
+            Duration::try_seconds(60).unwrap(),
+            now,
+        );
+        assert_eq!(capacity.max_cents, dec!(3_000_000));
+        assert_eq!(capacity.remaining_cents(), dec!(1_500_000));
+        assert!(capacity.hedging_healthy);
+    }
+
+    #[test]
+    fn remaining_capacity_is_never_negative() {
+        let now = Utc::now();
+        let capacity = HedgingCapacity::estimate(
+            Some(snapshot(now)),
+            dec!(3_000_000),
+            dec!(1),
+            Duration::try_seconds(60).unwrap(),
+            now,
+        );
+        assert_eq!(capacity.remaining_cents(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn stale_or_missing_snapshot_is_unhealthy() {
+        let now = Utc::now();
+        let stale = HedgingCapacity::estimate(
+            Some(snapshot(now - Duration::try_seconds(61).unwrap())),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Duration::try_seconds(60).unwrap(),
+            now,
+        );
+        assert!(!stale.hedging_healthy);
+
+        let missing = HedgingCapacity::estimate(
+            None,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Duration::try_seconds(60).unwrap(),
+            now,
+        );
+        assert!(!missing.hedging_healthy);
+        assert_eq!(missing.remaining_cents(), Decimal::ZERO);
+    }
+}
@@ -1,10 +1,8 @@
 use chrono::Duration;
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-pub struct ExchangeWeights {
-    pub okex: Option<Decimal>,
-}
+use super::{AccountLimitsConfig, HedgingCapacityConfig};
+use crate::price::InventorySkewConfig;
 
 #[serde_with::serde_as]
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -12,12 +10,17 @@ pub struct QuotesServerHealthCheckConfig {
     #[serde_as(as = "serde_with::DurationSeconds<i64>")]
     #[serde(default = "default_unhealthy_msg_interval_price")]
     pub unhealthy_msg_interval_price: Duration,
+    /// Reported unhealthy when no expiry sweep completed for this long
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_unhealthy_expiry_sweep_interval")]
+    pub unhealthy_expiry_sweep_interval: Duration,
 }
 
 impl Default for QuotesServerHealthCheckConfig {
     fn default() -> Self {
         Self {
             unhealthy_msg_interval_price: default_unhealthy_msg_interval_price(),
+            unhealthy_expiry_sweep_interval: default_unhealthy_expiry_sweep_interval(),
         }
     }
 }
@@ -27,22 +30,84 @@ fn default_unhealthy_msg_interval_price() -> Duration {
         .expect("bad default unhealthy_after_msg_delay")
 }
 
+fn default_unhealthy_expiry_sweep_interval() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(300))
+        .expect("bad default unhealthy_expiry_sweep_interval")
+}
+
 #[serde_with::serde_as]
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct QuotesConfig {
+    /// Lifetime of quotes that don't request one. Quotes living this long pay the
+    /// configured delayed fee, shorter and longer lifetimes pay proportionally.
     #[serde_as(as = "serde_with::DurationSeconds<i64>")]
     #[serde(default = "default_expiration_interval")]
     pub expiration_interval: Duration,
+    /// Shortest lifetime a quote request may ask for
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_min_expiration_interval")]
+    pub min_expiration_interval: Duration,
+    /// Longest lifetime a quote request may ask for
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_max_expiration_interval")]
+    pub max_expiration_interval: Duration,
+    /// How often overdue quotes are marked expired
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_expiry_sweep_interval")]
+    pub expiry_sweep_interval: Duration,
+    /// Upper bound and default for the page size of `ListQuotes`
+    #[serde(default = "default_max_quotes_per_page")]
+    pub max_quotes_per_page: u32,
+    /// Levels per side recorded from each order book a quote is priced from, 0 disables
+    #[serde(default = "default_book_levels_depth")]
+    pub book_levels_depth: usize,
+    #[serde(default)]
+    pub account_limits: AccountLimitsConfig,
+    #[serde(default)]
+    pub capacity: HedgingCapacityConfig,
+    #[serde(default)]
+    pub inventory_skew: InventorySkewConfig,
 }
 
 impl Default for QuotesConfig {
     fn default() -> Self {
         Self {
             expiration_interval: default_expiration_interval(),
+            min_expiration_interval: default_min_expiration_interval(),
+            max_expiration_interval: default_max_expiration_interval(),
+            expiry_sweep_interval: default_expiry_sweep_interval(),
+            max_quotes_per_page: default_max_quotes_per_page(),
+            book_levels_depth: default_book_levels_depth(),
+            account_limits: AccountLimitsConfig::default(),
+            capacity: HedgingCapacityConfig::default(),
+            inventory_skew: InventorySkewConfig::default(),
         }
     }
 }
 
+fn default_expiry_sweep_interval() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(30))
+        .expect("bad default expiry_sweep_interval")
+}
+
+fn default_max_quotes_per_page() -> u32 {
+    100
+}
+
+fn default_book_levels_depth() -> usize {
+    5
+}
+
+fn default_min_expiration_interval() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(5))
+        .expect("bad default min_expiration_interval")
+}
+
+fn default_max_expiration_interval() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(300))
+        .expect("bad default max_expiration_interval")
+}
+
 fn default_expiration_interval() -> Duration {
     Duration::from_std(std::time::Duration::from_secs(120)) // 2 minutes = 120 seconds
         .expect("bad default expiration_interval")
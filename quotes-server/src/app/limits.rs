@@ -0,0 +1,129 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::QuotesAppError, quote::AccountUsage};
+
+/// Caps applied to the quotes of a single account. Unset limits are not enforced
+/// and quotes requested without an account are never limited.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct AccountLimitsConfig {
+    /// Largest notional of a single quote
+    #[serde(default)]
+    pub max_cents_per_quote: Option<Decimal>,
+    /// Largest notional of the accepted and open quotes created in the last 24 hours
+    #[serde(default)]
+    pub max_cents_per_day: Option<Decimal>,
+    /// Most quotes waiting to be accepted at the same time
+    #[serde(default)]
+    pub max_open_quotes: Option<u64>,
+}
+
+impl AccountLimitsConfig {
+    pub(super) fn check_quote_size(
+        &self,
+        account_id: &str,
+        cents: Decimal,
+    ) -> Result<(), QuotesAppError> {
+        if let Some(max) = self.max_cents_per_quote {
+            if cents > max {
+                return Err(QuotesAppError::AccountLimitExceeded(format!(
+                    "{account_id}: quote of {cents} cents exceeds {max} cents per quote"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn check_usage(
+        &self,
+        account_id: &str,
+        usage: &AccountUsage,
+        cents: Decimal,
+        immediate_execution: bool,
+    ) -> Result<(), QuotesAppError> {
+        if let Some(max) = self.max_cents_per_day {
+            if usage.cents + cents > max {
+                return Err(QuotesAppError::AccountLimitExceeded(format!(
+                    "{account_id}: {} cents in the last 24h exceeds {max} cents per day",
+                    usage.cents + cents
+                )));
+            }
+        }
+        if let Some(max) = self.max_open_quotes {
+            if !immediate_execution && usage.open_quotes >= max {
+                return Err(QuotesAppError::AccountLimitExceeded(format!(
+                    "{account_id}: {} open quotes reached the limit of {max}",
+                    usage.open_quotes
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn limits() -> AccountLimitsConfig {
+        AccountLimitsConfig {
+            max_cents_per_quote: Some(dec!(10_000)),
+            max_cents_per_day: Some(dec!(50_000)),
+            max_open_quotes: Some(2),
+        }
+    }
+
+    fn usage(open_quotes: u64, cents: Decimal) -> AccountUsage {
+        AccountUsage { open_quotes, cents }
+    }
+
+    #[test]
+    fn unset_limits_allow_everything() {
+        let limits = AccountLimitsConfig::default();
+        assert!(limits.check_quote_size("account", dec!(1_000_000)).is_ok());
+        assert!(limits
+            .check_usage(
+                "account",
+                &usage(100, dec!(1_000_000)),
+                dec!(1_000_000),
+                false
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn max_cents_per_quote() {
+        assert!(limits().check_quote_size("account", dec!(10_000)).is_ok());
+        assert!(matches!(
+            limits().check_quote_size("account", dec!(10_001)),
+            Err(QuotesAppError::AccountLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn max_cents_per_day() {
+        assert!(limits()
+            .check_usage("account", &usage(0, dec!(40_000)), dec!(10_000), true)
+            .is_ok());
+        assert!(matches!(
+            limits().check_usage("account", &usage(0, dec!(40_001)), dec!(10_000), true),
+            Err(QuotesAppError::AccountLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn max_open_quotes() {
+        assert!(limits()
+            .check_usage("account", &usage(1, dec!(0)), dec!(100), false)
+            .is_ok());
+        assert!(matches!(
+            limits().check_usage("account", &usage(2, dec!(0)), dec!(100), false),
+            Err(QuotesAppError::AccountLimitExceeded(_))
+        ));
+        assert!(limits()
+            .check_usage("account", &usage(2, dec!(0)), dec!(100), true)
+            .is_ok());
+    }
+}
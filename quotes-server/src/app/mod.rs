@@ -1,21 +1,47 @@
+mod capacity;
 mod config;
+mod limits;
 
 use chrono::{DateTime, Duration, Utc};
 use futures::stream::StreamExt;
 use rust_decimal::Decimal;
 use sqlx::{Postgres, Transaction};
-use tracing::{info_span, Instrument};
+use sqlxmq::JobRunnerHandle;
+use std::sync::Arc;
 
 use shared::{
-    health::HealthCheckTrigger,
-    payload::{PriceStreamPayload, OKEX_EXCHANGE_ID},
+    health::{FeedHealthCheck, HealthCheckTrigger, HealthDetailsTrigger},
+    payload::PriceStreamPayload,
     pubsub::*,
 };
 
 use ledger::*;
+use pricing::{currency::*, *};
 
-use crate::{cache::*, currency::*, error::*, price::*, quote::*};
+use crate::{error::*, job, price::*, quote::*};
+pub use capacity::*;
 pub use config::*;
+pub use limits::*;
+
+/// One page of `QuotesApp::list_quotes`. `next_after` is set when more quotes match.
+#[derive(Debug)]
+pub struct QuotesPage {
+    pub quotes: Vec<Quote>,
+    pub next_after: Option<QuoteId>,
+}
+
+/// Optional terms of a quote request
+#[derive(Debug, Clone, Default)]
+pub struct QuoteRequest {
+    /// Retrying with the key of an earlier identical request returns its quote
+    pub idempotency_key: Option<String>,
+    /// Account whose limits the quote counts against
+    pub account_id: Option<String>,
+    /// Lifetime of the quote, `expiration_interval` when `None`
+    pub lifetime: Option<Duration>,
+    /// Priced like any other quote but neither persisted nor acceptable
+    pub indicative: bool,
+}
 
 pub struct QuotesApp {
     price_calculator: PriceCalculator,
@@ -23,6 +49,7 @@ pub struct QuotesApp {
     ledger: Ledger,
     pool: sqlx::PgPool,
     config: QuotesConfig,
+    _runner: JobRunnerHandle,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -30,187 +57,511 @@ impl QuotesApp {
     pub async fn run(
         pool: sqlx::PgPool,
         mut health_check_trigger: HealthCheckTrigger,
+        mut health_details_trigger: HealthDetailsTrigger,
         health_check_cfg: QuotesServerHealthCheckConfig,
-        fee_calc_cfg: QuotesFeeCalculatorConfig,
+        fee_calc_cfg: FeeCalculatorConfig,
         subscriber: memory::Subscriber<PriceStreamPayload>,
-        price_cache_config: QuotesExchangePriceCacheConfig,
+        price_cache_config: ExchangePriceCacheConfig,
+        mixer_config: PriceMixerConfig,
         exchange_weights: ExchangeWeights,
         config: QuotesConfig,
         ledger: ledger::Ledger,
     ) -> Result<Self, QuotesAppError> {
+        let (price_mixer, volatility) = subscribe_price_mixer(
+            &subscriber,
+            &price_cache_config,
+            mixer_config,
+            &exchange_weights,
+            None,
+        );
+
+        let quotes = Quotes::new(&pool);
+        let sweeper_health = FeedHealthCheck::new(
+            "quote expiry sweeper",
+            health_check_cfg.unhealthy_expiry_sweep_interval,
+        );
+        sweeper_health.record_update().await;
+        let metrics = QuoteMetrics::new();
+        let runner = job::start_job_runner(
+            &pool,
+            quotes.clone(),
+            sweeper_health.clone(),
+            metrics.clone(),
+        )
+        .await?;
+        Self::spawn_expire_quotes(pool.clone(), config.expiry_sweep_interval);
+        let mixer_health = price_mixer.health();
+        tokio::spawn(async move {
+            while let Some(request) = health_details_trigger.next().await {
+                let mut details = metrics.health_details().await;
+                details.extend(mixer_health.details().await);
+                let _ = request.send(details);
+            }
+        });
+
         let health_subscriber = subscriber.resubscribe();
+        let mixer_health = price_mixer.health();
         tokio::spawn(async move {
             while let Some(check) = health_check_trigger.next().await {
-                let _ = check.send(
-                    health_subscriber
-                        .healthy(health_check_cfg.unhealthy_msg_interval_price)
-                        .await,
-                );
+                let res = match health_subscriber
+                    .healthy(health_check_cfg.unhealthy_msg_interval_price)
+                    .await
+                {
+                    Ok(()) => mixer_health.healthy().await,
+                    err => err,
+                };
+                let res = match res {
+                    Ok(()) => sweeper_health.healthy().await,
+                    err => err,
+                };
+                let _ = check.send(res);
             }
         });
 
-        let mut price_mixer = PriceMixer::new();
-
-        if let Some(weight) = exchange_weights.okex {
-            if weight > Decimal::ZERO {
-                let okex_order_book_cache = OrderBookCache::new(price_cache_config.clone());
-                Self::subscribe_okex(subscriber.resubscribe(), okex_order_book_cache.clone())
-                    .await?;
-                price_mixer.add_provider(OKEX_EXCHANGE_ID, okex_order_book_cache, weight);
-            }
+        let mut price_calculator =
+            PriceCalculator::new(fee_calc_cfg, Arc::new(price_mixer), volatility)
+                .with_delayed_fee_horizon(config.expiration_interval)
+                .with_book_levels(config.book_levels_depth);
+        if config.inventory_skew.enabled {
+            price_calculator = price_calculator.with_fee_adjustment(InventorySkew::run(
+                ledger.clone(),
+                config.inventory_skew.clone(),
+            ));
         }
 
-        let quotes = Quotes::new(&pool);
-
         Ok(Self {
-            price_calculator: PriceCalculator::new(fee_calc_cfg, price_mixer),
+            price_calculator,
             quotes,
             ledger,
             pool,
             config,
+            _runner: runner,
         })
     }
 
+    fn spawn_expire_quotes(pool: sqlx::PgPool, interval: Duration) {
+        let interval = interval.to_std().expect("Failed to convert duration");
+        tokio::spawn(async move {
+            loop {
+                let _ = job::spawn_expire_quotes(&pool, std::time::Duration::from_secs(1)).await;
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
     pub async fn quote_cents_from_sats_for_buy(
         &self,
         sats: Decimal,
         immediate_execution: bool,
+        request: QuoteRequest,
     ) -> Result<Quote, QuotesAppError> {
-        let sats = Satoshis::from(sats);
-        let res = self
-            .price_calculator
-            .cents_from_sats_for_buy(sats, immediate_execution)
-            .await?;
-        let expiry_time = expiration_time_from_duration(self.config.expiration_interval);
-        let new_quote = NewQuote::builder()
-            .direction(Direction::BuyCents)
-            .immediate_execution(immediate_execution)
-            .cent_amount(res.cents)
-            .sat_amount(res.sats)
-            .cents_spread(res.cents_spread)
-            .sats_spread(res.sats_spread)
-            .expires_at(expiry_time)
-            .build()
-            .expect("Could not build quote");
-        let mut tx = self.pool.begin().await?;
-        let mut quote = self.quotes.create(&mut tx, new_quote).await?;
-        if immediate_execution {
-            self.accept_quote_in_tx(tx, &mut quote).await?;
-        } else {
-            tx.commit().await?;
-        }
-
-        Ok(quote)
+        self.quote(
+            Direction::BuyCents,
+            RequestedAmount::Sats(Sats::from(sats)),
+            immediate_execution,
+            request,
+            |amount, execution| {
+                self.price_calculator
+                    .cents_from_sats_for_buy(Sats::from(amount), execution)
+            },
+        )
+        .await
     }
 
     pub async fn quote_cents_from_sats_for_sell(
         &self,
         sats: Decimal,
         immediate_execution: bool,
+        request: QuoteRequest,
     ) -> Result<Quote, QuotesAppError> {
-        let sats = Satoshis::from(sats);
-        let res = self
-            .price_calculator
-            .cents_from_sats_for_sell(sats, immediate_execution)
-            .await?;
-        let expiry_time = expiration_time_from_duration(self.config.expiration_interval);
-        let new_quote = NewQuote::builder()
-            .direction(Direction::SellCents)
-            .immediate_execution(immediate_execution)
-            .cent_amount(res.cents)
-            .sat_amount(res.sats)
-            .cents_spread(res.cents_spread)
-            .sats_spread(res.sats_spread)
-            .expires_at(expiry_time)
-            .build()
-            .expect("Could not build quote");
-        let mut tx = self.pool.begin().await?;
-        let mut quote = self.quotes.create(&mut tx, new_quote).await?;
-        if immediate_execution {
-            self.accept_quote_in_tx(tx, &mut quote).await?;
-        } else {
-            tx.commit().await?;
-        }
-
-        Ok(quote)
+        self.quote(
+            Direction::SellCents,
+            RequestedAmount::Sats(Sats::from(sats)),
+            immediate_execution,
+            request,
+            |amount, execution| {
+                self.price_calculator
+                    .cents_from_sats_for_sell(Sats::from(amount), execution)
+            },
+        )
+        .await
     }
 
     pub async fn quote_sats_from_cents_for_sell(
         &self,
         cents: Decimal,
         immediate_execution: bool,
+        request: QuoteRequest,
     ) -> Result<Quote, QuotesAppError> {
-        let cents = UsdCents::from(cents);
-        let res = self
-            .price_calculator
-            .sats_from_cents_for_sell(cents, immediate_execution)
-            .await?;
-        let expiry_time = expiration_time_from_duration(self.config.expiration_interval);
-        let new_quote = NewQuote::builder()
-            .direction(Direction::SellCents)
-            .immediate_execution(immediate_execution)
-            .cent_amount(res.cents)
-            .sat_amount(res.sats)
-            .cents_spread(res.cents_spread)
-            .sats_spread(res.sats_spread)
-            .expires_at(expiry_time)
-            .build()
-            .expect("Could not build quote");
-        let mut tx = self.pool.begin().await?;
-        let mut quote = self.quotes.create(&mut tx, new_quote).await?;
-        if immediate_execution {
-            self.accept_quote_in_tx(tx, &mut quote).await?;
-        } else {
-            tx.commit().await?;
-        }
-
-        Ok(quote)
+        self.quote(
+            Direction::SellCents,
+            RequestedAmount::Cents(UsdCents::from(cents)),
+            immediate_execution,
+            request,
+            |amount, execution| {
+                self.price_calculator
+                    .sats_from_cents_for_sell(UsdCents::from(amount), execution)
+            },
+        )
+        .await
     }
 
     pub async fn quote_sats_from_cents_for_buy(
         &self,
         cents: Decimal,
         immediate_execution: bool,
+        request: QuoteRequest,
     ) -> Result<Quote, QuotesAppError> {
-        let cents = UsdCents::from(cents);
-        let res = self
-            .price_calculator
-            .sats_from_cents_for_buy(cents, immediate_execution)
-            .await?;
-        let expiry_time = expiration_time_from_duration(self.config.expiration_interval);
+        self.quote(
+            Direction::BuyCents,
+            RequestedAmount::Cents(UsdCents::from(cents)),
+            immediate_execution,
+            request,
+            |amount, execution| {
+                self.price_calculator
+                    .sats_from_cents_for_buy(UsdCents::from(amount), execution)
+            },
+        )
+        .await
+    }
+
+    /// Prices `amount` with `price` and persists the quote unless it is indicative.
+    /// A retry with the idempotency key of an earlier identical request returns its quote.
+    async fn quote<F, Fut>(
+        &self,
+        direction: Direction,
+        amount: RequestedAmount,
+        immediate_execution: bool,
+        request: QuoteRequest,
+        price: F,
+    ) -> Result<Quote, QuotesAppError>
+    where
+        F: Fn(Decimal, Execution) -> Fut,
+        Fut: std::future::Future<Output = Result<ConversionResult, ExchangePriceCacheError>>,
+    {
+        let QuoteRequest {
+            idempotency_key,
+            account_id,
+            lifetime,
+            indicative,
+        } = request;
+        let (expiry_time, execution) =
+            self.quote_terms(immediate_execution, indicative, lifetime)?;
+        let idempotency_key = idempotency_key.filter(|_| !indicative);
+        let same_request = |quote: &Quote| {
+            quote.direction == direction
+                && quote.immediate_execution == immediate_execution
+                && quote.account_id == account_id
+                && quote.was_requested_for(&amount)
+        };
+        if let Some(quote) = self
+            .find_retried_quote(idempotency_key.as_deref(), same_request)
+            .await?
+        {
+            return Ok(quote);
+        }
+        let limited_account = account_id.as_deref().filter(|_| !indicative);
+        if let (Some(account_id), RequestedAmount::Cents(cents)) = (limited_account, &amount) {
+            self.config
+                .account_limits
+                .check_quote_size(account_id, *cents.amount())?;
+        }
+        let res = price(amount.value(), execution).await?;
+        let mut tx = self.pool.begin().await?;
+        let (res, capacity_limited) = match direction {
+            Direction::BuyCents => {
+                self.limit_to_hedging_capacity(&mut tx, res, amount, execution, &price)
+                    .await?
+            }
+            Direction::SellCents => (res, false),
+        };
+        if let (Some(account_id), RequestedAmount::Sats(_)) = (limited_account, &amount) {
+            self.config
+                .account_limits
+                .check_quote_size(account_id, *res.cents.amount())?;
+        }
         let new_quote = NewQuote::builder()
-            .direction(Direction::BuyCents)
+            .direction(direction.clone())
             .immediate_execution(immediate_execution)
             .cent_amount(res.cents)
             .sat_amount(res.sats)
             .cents_spread(res.cents_spread)
             .sats_spread(res.sats_spread)
             .expires_at(expiry_time)
+            .price_details(price_details(&res))
+            .idempotency_key(idempotency_key.clone())
+            .account_id(account_id.clone())
+            .capacity_limited(capacity_limited)
+            .requested_amount(amount)
             .build()
             .expect("Could not build quote");
-        let mut tx = self.pool.begin().await?;
-        let mut quote = self.quotes.create(&mut tx, new_quote).await?;
-        if immediate_execution {
-            self.accept_quote_in_tx(tx, &mut quote).await?;
-        } else {
-            tx.commit().await?;
+        if indicative {
+            return Ok(new_quote.into_indicative());
         }
-        Ok(quote)
+        self.create_quote(
+            tx,
+            new_quote,
+            idempotency_key.as_deref(),
+            account_id.as_deref(),
+            same_request,
+        )
+        .await
     }
 
-    pub async fn accept_quote(&self, id: QuoteId) -> Result<(), QuotesAppError> {
+    pub async fn get_quote(&self, id: QuoteId) -> Result<Quote, QuotesAppError> {
+        Ok(self.quotes.find_by_id(id).await?)
+    }
+
+    /// A `page_size` of 0 or above `max_quotes_per_page` returns `max_quotes_per_page` quotes
+    pub async fn list_quotes(
+        &self,
+        filter: QuotesFilter,
+        after: Option<QuoteId>,
+        page_size: u32,
+    ) -> Result<QuotesPage, QuotesAppError> {
+        let max = self.config.max_quotes_per_page;
+        let page_size = if page_size == 0 || page_size > max {
+            max
+        } else {
+            page_size
+        } as usize;
+        let mut quotes = self
+            .quotes
+            .list(&filter, after, page_size as i64 + 1)
+            .await?;
+        let next_after = if quotes.len() > page_size {
+            quotes.truncate(page_size);
+            quotes.last().map(|quote| quote.id)
+        } else {
+            None
+        };
+        Ok(QuotesPage { quotes, next_after })
+    }
+
+    /// Accepting again with the key of the original acceptance is a no-op
+    pub async fn accept_quote(
+        &self,
+        id: QuoteId,
+        idempotency_key: Option<String>,
+    ) -> Result<(), QuotesAppError> {
         let mut quote = self.quotes.find_by_id(id).await?;
+        if idempotency_key.is_some() && quote.accept_idempotency_key() == idempotency_key.as_deref()
+        {
+            return Ok(());
+        }
         let tx = self.pool.begin().await?;
-        self.accept_quote_in_tx(tx, &mut quote).await?;
+        match self
+            .accept_quote_in_tx(tx, &mut quote, idempotency_key.clone())
+            .await
+        {
+            Err(QuotesAppError::QuoteError(QuoteError::DuplicateIdempotencyKey)) => {
+                Err(QuoteError::IdempotencyKeyReused(idempotency_key.unwrap_or_default()).into())
+            }
+            res => res,
+        }
+    }
+
+    pub async fn cancel_quote(&self, id: QuoteId) -> Result<(), QuotesAppError> {
+        let mut quote = self.quotes.find_by_id(id).await?;
+        quote.cancel()?;
+        let mut tx = self.pool.begin().await?;
+        self.quotes.update(&mut tx, &mut quote).await?;
+        tx.commit().await?;
         Ok(())
     }
 
+    pub async fn hedging_capacity(&self) -> Result<HedgingCapacity, QuotesAppError> {
+        let reserved = self
+            .quotes
+            .open_cents(&self.pool, Direction::BuyCents)
+            .await?;
+        self.estimate_hedging_capacity(reserved).await
+    }
+
+    async fn estimate_hedging_capacity(
+        &self,
+        reserved: Decimal,
+    ) -> Result<HedgingCapacity, QuotesAppError> {
+        let snapshot = HedgingCapacitySnapshot::load(&self.pool).await?;
+        let allocated = self
+            .ledger
+            .balances()
+            .usd_liability_balances()
+            .await?
+            .okex_allocation;
+        Ok(HedgingCapacity::estimate(
+            snapshot,
+            Decimal::from(allocated),
+            reserved,
+            self.config.capacity.stale_after,
+            Utc::now(),
+        ))
+    }
+
+    /// Rejects a buy quote the OKX account could not hedge, or re-prices the requested
+    /// amount down to the remaining capacity when `shrink_oversized_quotes` is set.
+    /// Holds the capacity lock until `tx` ends so the quote is reserved before the
+    /// next one is checked.
+    async fn limit_to_hedging_capacity<F, Fut>(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        res: ConversionResult,
+        amount: RequestedAmount,
+        execution: Execution,
+        price: &F,
+    ) -> Result<(ConversionResult, bool), QuotesAppError>
+    where
+        F: Fn(Decimal, Execution) -> Fut,
+        Fut: std::future::Future<Output = Result<ConversionResult, ExchangePriceCacheError>>,
+    {
+        if !self.config.capacity.enabled {
+            return Ok((res, false));
+        }
+        self.quotes.lock_hedging_capacity(tx).await?;
+        let reserved = self
+            .quotes
+            .open_cents(&mut **tx, Direction::BuyCents)
+            .await?;
+        let capacity = self.estimate_hedging_capacity(reserved).await?;
+        if !capacity.hedging_healthy {
+            return Err(QuotesAppError::HedgingCapacityUnavailable(
+                match capacity.updated_at {
+                    Some(updated_at) => format!("hedging capacity last reported at {updated_at}"),
+                    None => "hedging capacity was never reported".to_string(),
+                },
+            ));
+        }
+        let remaining = capacity.remaining_cents().floor();
+        let cents = *res.cents.amount();
+        if cents <= remaining {
+            return Ok((res, false));
+        }
+        let insufficient = || {
+            QuotesAppError::InsufficientHedgingCapacity(format!(
+                "quote of {cents} cents exceeds the remaining {remaining} cents"
+            ))
+        };
+        let shrunk = match amount {
+            RequestedAmount::Cents(_) => remaining,
+            RequestedAmount::Sats(sats) => (*sats.amount() * remaining / cents).floor(),
+        };
+        if !self.config.capacity.shrink_oversized_quotes || shrunk.is_zero() {
+            return Err(insufficient());
+        }
+        let res = price(shrunk, execution).await?;
+        if *res.cents.amount() > remaining {
+            return Err(insufficient());
+        }
+        Ok((res, true))
+    }
+
+    /// Expiry and fee horizon of a new quote. Quotes without a requested lifetime
+    /// live for `expiration_interval` and pay the default delayed fee.
+    fn quote_terms(
+        &self,
+        immediate_execution: bool,
+        indicative: bool,
+        lifetime: Option<Duration>,
+    ) -> Result<(DateTime<Utc>, Execution), QuotesAppError> {
+        if indicative && immediate_execution {
+            return Err(QuotesAppError::InvalidQuoteRequest(
+                "indicative quotes cannot be executed immediately".to_string(),
+            ));
+        }
+        let (min, max) = (
+            self.config.min_expiration_interval,
+            self.config.max_expiration_interval,
+        );
+        if let Some(lifetime) = lifetime.filter(|lifetime| *lifetime < min || *lifetime > max) {
+            return Err(QuotesAppError::InvalidQuoteRequest(format!(
+                "lifetime of {}s is outside {}s to {}s",
+                lifetime.num_seconds(),
+                min.num_seconds(),
+                max.num_seconds()
+            )));
+        }
+        let expiry_time =
+            expiration_time_from_duration(lifetime.unwrap_or(self.config.expiration_interval));
+        let execution = if immediate_execution {
+            Execution::Immediate
+        } else {
+            Execution::Future(lifetime)
+        };
+        Ok((expiry_time, execution))
+    }
+
+    async fn find_retried_quote(
+        &self,
+        idempotency_key: Option<&str>,
+        same_request: impl Fn(&Quote) -> bool,
+    ) -> Result<Option<Quote>, QuotesAppError> {
+        let key = match idempotency_key {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        match self.quotes.find_by_idempotency_key(key).await? {
+            Some(quote) if same_request(&quote) => Ok(Some(quote)),
+            Some(_) => Err(QuoteError::IdempotencyKeyReused(key.to_string()).into()),
+            None => Ok(None),
+        }
+    }
+
+    async fn create_quote(
+        &self,
+        mut tx: Transaction<'_, Postgres>,
+        new_quote: NewQuote,
+        idempotency_key: Option<&str>,
+        account_id: Option<&str>,
+        same_request: impl Fn(&Quote) -> bool,
+    ) -> Result<Quote, QuotesAppError> {
+        if let Some(account_id) = account_id {
+            let since = Utc::now() - Duration::try_hours(24).expect("valid duration");
+            let usage = self
+                .quotes
+                .account_usage(&mut tx, account_id, since)
+                .await?;
+            if let Err(err) = self.config.account_limits.check_usage(
+                account_id,
+                &usage,
+                *new_quote.cent_amount().amount(),
+                new_quote.immediate_execution(),
+            ) {
+                // A concurrent retry may have committed first and used up the limit
+                return match self
+                    .find_retried_quote(idempotency_key, &same_request)
+                    .await?
+                {
+                    Some(quote) => Ok(quote),
+                    None => Err(err),
+                };
+            }
+        }
+        let mut quote = match self.quotes.create(&mut tx, new_quote).await {
+            Ok(quote) => quote,
+            Err(QuoteError::DuplicateIdempotencyKey) => {
+                // A concurrent retry committed first, so return its quote
+                return self
+                    .find_retried_quote(idempotency_key, same_request)
+                    .await?
+                    .ok_or_else(|| QuoteError::DuplicateIdempotencyKey.into());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if quote.immediate_execution {
+            self.accept_quote_in_tx(tx, &mut quote, None).await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(quote)
+    }
+
     async fn accept_quote_in_tx(
         &self,
         mut tx: Transaction<'_, Postgres>,
         quote: &mut Quote,
+        idempotency_key: Option<String>,
     ) -> Result<(), QuotesAppError> {
-        quote.accept()?;
+        quote.accept(idempotency_key)?;
         if quote.direction == Direction::SellCents {
             let params = SellUsdQuoteAcceptedParams {
                 usd_cents_amount: *quote.cent_amount.amount(),
@@ -239,31 +590,6 @@ impl QuotesApp {
 
         Ok(())
     }
-
-    async fn subscribe_okex(
-        mut subscriber: memory::Subscriber<PriceStreamPayload>,
-        order_book_cache: OrderBookCache,
-    ) -> Result<(), QuotesAppError> {
-        tokio::spawn(async move {
-            while let Some(msg) = subscriber.next().await {
-                if let PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(price_msg) = msg.payload {
-                    let span = info_span!(
-                        "quotes_server.okex_order_book_received",
-                        message_type = %msg.payload_type,
-                        correlation_id = %msg.meta.correlation_id
-                    );
-                    shared::tracing::inject_tracing_data(&span, &msg.meta.tracing_data);
-                    async {
-                        order_book_cache.apply_update(price_msg).await;
-                    }
-                    .instrument(span)
-                    .await;
-                }
-            }
-        });
-
-        Ok(())
-    }
 }
 
 fn expiration_time_from_duration(duration: Duration) -> DateTime<Utc> {
@@ -271,3 +597,36 @@ fn expiration_time_from_duration(duration: Duration) -> DateTime<Utc> {
         + chrono::Duration::from_std(duration.to_std().expect("Failed to convert duration"))
             .expect("Failed to create chrono::Duration")
 }
+
+fn price_details(res: &ConversionResult) -> QuotePriceDetails {
+    QuotePriceDetails {
+        timestamp: res.metadata.timestamp,
+        exchanges: res
+            .metadata
+            .sources
+            .iter()
+            .map(|source| QuoteExchangeWeight {
+                exchange_id: source.exchange_id.to_string(),
+                weight: source.weight,
+                timestamp: Some(source.timestamp),
+                books: source.levels.iter().map(order_book).collect(),
+            })
+            .collect(),
+        pre_fee_sat_amount: res.pre_fee_sats,
+        pre_fee_cent_amount: res.pre_fee_cents,
+        fee_rate: res.fee_rate,
+    }
+}
+
+fn order_book(levels: &BookLevels) -> QuoteOrderBook {
+    let book_level = |level: &BookLevel| QuoteBookLevel {
+        price_of_one_sat_in_cents: level.price_of_one_sat_in_cents,
+        volume_in_cents: level.volume_in_cents,
+    };
+    QuoteOrderBook {
+        exchange_id: levels.exchange_id.to_string(),
+        timestamp: levels.timestamp,
+        bids: levels.bids.iter().map(book_level).collect(),
+        asks: levels.asks.iter().map(book_level).collect(),
+    }
+}
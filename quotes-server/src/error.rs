@@ -1,6 +1,9 @@
 use thiserror::Error;
 
-use crate::{price::ExchangePriceCacheError, quote::QuoteError};
+use pricing::ExchangePriceCacheError;
+use shared::sqlxmq::JobExecutionError;
+
+use crate::quote::QuoteError;
 
 #[derive(Error, Debug)]
 pub enum QuotesAppError {
@@ -12,6 +15,20 @@ pub enum QuotesAppError {
     LedgerError(#[from] ledger::LedgerError),
     #[error("{0}")]
     Sqlx(#[from] sqlx::Error),
+    #[error("QuotesAppError - SerdeJson: {0}")]
+    SerdeJson(#[from] serde_json::Error),
     #[error("QuotesServerError - CouldNotParseIncomingUuid: {0}")]
     CouldNotParseIncomingUuid(uuid::Error),
+    #[error("QuotesServerError - InvalidQuoteFilter: {0}")]
+    InvalidQuoteFilter(String),
+    #[error("QuotesAppError - InvalidQuoteRequest: {0}")]
+    InvalidQuoteRequest(String),
+    #[error("QuotesAppError - AccountLimitExceeded: {0}")]
+    AccountLimitExceeded(String),
+    #[error("QuotesAppError - InsufficientHedgingCapacity: {0}")]
+    InsufficientHedgingCapacity(String),
+    #[error("QuotesAppError - HedgingCapacityUnavailable: {0}")]
+    HedgingCapacityUnavailable(String),
 }
+
+impl JobExecutionError for QuotesAppError {}
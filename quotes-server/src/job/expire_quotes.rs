@@ -0,0 +1,51 @@
+use tracing::instrument;
+
+use crate::{
+    error::QuotesAppError,
+    quote::{QuoteMetrics, Quotes},
+};
+
+const BATCH_SIZE: i64 = 100;
+
+#[instrument(
+    name = "quotes_server.job.expire_quotes",
+    skip_all,
+    fields(
+        n_expired,
+        open_quotes,
+        accepted_quotes,
+        expired_quotes,
+        cancelled_quotes
+    ),
+    err
+)]
+pub(super) async fn execute(
+    pool: &sqlx::PgPool,
+    quotes: &Quotes,
+    metrics: &QuoteMetrics,
+) -> Result<(), QuotesAppError> {
+    let span = tracing::Span::current();
+    let mut n_expired = 0;
+    loop {
+        let mut overdue = quotes.list_overdue(BATCH_SIZE).await?;
+        if overdue.is_empty() {
+            break;
+        }
+        let mut tx = pool.begin().await?;
+        for quote in overdue.iter_mut() {
+            quote.expire()?;
+            quotes.update(&mut tx, quote).await?;
+        }
+        tx.commit().await?;
+        n_expired += overdue.len();
+    }
+    span.record("n_expired", n_expired);
+
+    let counts = quotes.count_by_state().await?;
+    span.record("open_quotes", counts.open);
+    span.record("accepted_quotes", counts.accepted);
+    span.record("expired_quotes", counts.expired);
+    span.record("cancelled_quotes", counts.cancelled);
+    metrics.record(counts).await;
+    Ok(())
+}
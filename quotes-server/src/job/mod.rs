@@ -0,0 +1,73 @@
+mod expire_quotes;
+
+use sqlxmq::{job, CurrentJob, JobBuilder, JobRegistry, JobRunnerHandle};
+use tracing::instrument;
+use uuid::{uuid, Uuid};
+
+use shared::{health::FeedHealthCheck, sqlxmq::JobExecutor};
+
+use crate::{
+    error::QuotesAppError,
+    quote::{QuoteMetrics, Quotes},
+};
+
+pub const EXPIRE_QUOTES_ID: Uuid = uuid!("20000000-0000-0000-0000-000000000001");
+
+pub async fn start_job_runner(
+    pool: &sqlx::PgPool,
+    quotes: Quotes,
+    sweeper_health: FeedHealthCheck,
+    metrics: QuoteMetrics,
+) -> Result<JobRunnerHandle, QuotesAppError> {
+    let mut registry = JobRegistry::new(&[expire_quotes]);
+    registry.set_context(quotes);
+    registry.set_context(sweeper_health);
+    registry.set_context(metrics);
+
+    Ok(registry
+        .runner(pool)
+        .set_channel_names(&["quotes"])
+        .run()
+        .await?)
+}
+
+#[instrument(name = "quotes_server.job.spawn_expire_quotes", skip_all, fields(error, error.level, error.message), err)]
+pub async fn spawn_expire_quotes(
+    pool: &sqlx::PgPool,
+    duration: std::time::Duration,
+) -> Result<(), QuotesAppError> {
+    match JobBuilder::new_with_id(EXPIRE_QUOTES_ID, "expire_quotes")
+        .set_channel_name("quotes")
+        .set_channel_args("expire_quotes")
+        .set_delay(duration)
+        .spawn(pool)
+        .await
+    {
+        Err(sqlx::Error::Database(err)) if err.message().contains("duplicate key") => Ok(()),
+        Err(e) => {
+            shared::tracing::insert_error_fields(tracing::Level::ERROR, &e);
+            Err(e.into())
+        }
+        Ok(_) => Ok(()),
+    }
+}
+
+#[job(name = "expire_quotes")]
+async fn expire_quotes(
+    mut current_job: CurrentJob,
+    quotes: Quotes,
+    sweeper_health: FeedHealthCheck,
+    metrics: QuoteMetrics,
+) -> Result<(), QuotesAppError> {
+    let pool = current_job.pool().clone();
+    JobExecutor::builder(&mut current_job)
+        .build()
+        .expect("couldn't build JobExecutor")
+        .execute(|_| async move {
+            expire_quotes::execute(&pool, &quotes, &metrics).await?;
+            sweeper_health.record_update().await;
+            Ok::<_, QuotesAppError>(())
+        })
+        .await?;
+    Ok(())
+}
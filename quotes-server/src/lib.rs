@@ -2,31 +2,39 @@
 #![cfg_attr(feature = "fail-on-warnings", deny(clippy::all))]
 
 pub mod app;
-pub mod cache;
-pub mod currency;
 pub mod entity;
 pub mod error;
+pub mod job;
 pub mod price;
 pub mod quote;
 pub mod server;
 
-use shared::{health::HealthCheckTrigger, payload::*, pubsub::memory};
+use shared::{
+    health::{HealthCheckTrigger, HealthDetailsTrigger},
+    payload::*,
+    pubsub::memory,
+};
 
 pub use app::*;
-pub use cache::QuotesExchangePriceCacheConfig;
 pub use entity::*;
 pub use price::*;
+pub use pricing::{
+    ExchangePriceCacheConfig, ExchangePriceCacheError, ExchangeWeights, FeeCalculatorConfig,
+    PriceMixerConfig,
+};
 pub use server::*;
 
 #[allow(clippy::too_many_arguments)]
 pub async fn run(
     pool: sqlx::PgPool,
     health_check_trigger: HealthCheckTrigger,
+    health_details_trigger: HealthDetailsTrigger,
     health_check_cfg: QuotesServerHealthCheckConfig,
     server_config: QuotesServerConfig,
-    fee_calc_cfg: QuotesFeeCalculatorConfig,
+    fee_calc_cfg: FeeCalculatorConfig,
     subscriber: memory::Subscriber<PriceStreamPayload>,
-    price_cache_config: QuotesExchangePriceCacheConfig,
+    price_cache_config: ExchangePriceCacheConfig,
+    mixer_config: PriceMixerConfig,
     exchange_weights: ExchangeWeights,
     quotes_config: QuotesConfig,
     ledger: ledger::Ledger,
@@ -34,10 +42,12 @@ pub async fn run(
     let app = QuotesApp::run(
         pool,
         health_check_trigger,
+        health_details_trigger,
         health_check_cfg,
         fee_calc_cfg,
         subscriber,
         price_cache_config,
+        mixer_config,
         exchange_weights,
         quotes_config,
         ledger,
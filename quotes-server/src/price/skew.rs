@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use chrono::Duration;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use ledger::{constants::CENTS_PER_USD, Ledger, LedgerError};
+use pricing::FeeAdjustment;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InventorySkewCurve {
+    #[default]
+    Linear,
+    Quadratic,
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct InventorySkewConfig {
+    /// Shade prices against trades that move the hedge further from its target
+    #[serde(default)]
+    pub enabled: bool,
+    /// Ratio of the OKX short position to the allocated liability the hedge aims for
+    #[serde(default = "default_target_ratio")]
+    pub target_ratio: Decimal,
+    /// Distance from `target_ratio` that is tolerated without skew
+    #[serde(default = "default_dead_band")]
+    pub dead_band: Decimal,
+    /// Rate added per unit of distance beyond `dead_band`, after applying `curve`
+    #[serde(default = "default_multiplier")]
+    pub multiplier: Decimal,
+    #[serde(default)]
+    pub curve: InventorySkewCurve,
+    #[serde(default = "default_max_skew_rate")]
+    pub max_skew_rate: Decimal,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(default = "default_refresh_interval")]
+    pub refresh_interval: Duration,
+}
+
+impl Default for InventorySkewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_ratio: default_target_ratio(),
+            dead_band: default_dead_band(),
+            multiplier: default_multiplier(),
+            curve: InventorySkewCurve::default(),
+            max_skew_rate: default_max_skew_rate(),
+            refresh_interval: default_refresh_interval(),
+        }
+    }
+}
+
+fn default_target_ratio() -> Decimal {
+    Decimal::ONE
+}
+
+fn default_dead_band() -> Decimal {
+    Decimal::new(5, 2)
+}
+
+fn default_multiplier() -> Decimal {
+    Decimal::new(2, 2)
+}
+
+fn default_max_skew_rate() -> Decimal {
+    Decimal::new(5, 3)
+}
+
+fn default_refresh_interval() -> Duration {
+    Duration::from_std(std::time::Duration::from_secs(5)).expect("bad default refresh_interval")
+}
+
+/// Fee adjustment derived from how far the OKX hedge sits from its target.
+/// The imbalance is refreshed from the ledger in the background.
+#[derive(Clone)]
+pub struct InventorySkew {
+    config: InventorySkewConfig,
+    imbalance: Arc<RwLock<Option<Decimal>>>,
+}
+
+impl InventorySkew {
+    pub fn run(ledger: Ledger, config: InventorySkewConfig) -> Self {
+        let skew = Self::new(config);
+        let imbalance = Arc::clone(&skew.imbalance);
+        let target_ratio = skew.config.target_ratio;
+        let interval = skew
+            .config
+            .refresh_interval
+            .to_std()
+            .expect("Failed to convert duration");
+        tokio::spawn(async move {
+            loop {
+                if let Ok(res) = current_imbalance(&ledger, target_ratio).await {
+                    *imbalance.write().await = res;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        skew
+    }
+
+    fn new(config: InventorySkewConfig) -> Self {
+        Self {
+            config,
+            imbalance: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    fn rate_for(&self, imbalance: Decimal, buy_usd: bool) -> Decimal {
+        let distance = (imbalance.abs() - self.config.dead_band).max(Decimal::ZERO);
+        let shaped = match self.config.curve {
+            InventorySkewCurve::Linear => distance,
+            InventorySkewCurve::Quadratic => distance * distance,
+        };
+        let rate = (self.config.multiplier * shaped).min(self.config.max_skew_rate);
+        // Users buying USD grow the liability, which widens an under-hedged book
+        if imbalance.is_sign_positive() == buy_usd {
+            rate
+        } else {
+            -rate
+        }
+    }
+}
+
+#[async_trait]
+impl FeeAdjustment for InventorySkew {
+    /// Positive when the conversion would move the hedge further from its target
+    async fn rate(&self, buy_usd: bool) -> Decimal {
+        match *self.imbalance.read().await {
+            Some(imbalance) => self.rate_for(imbalance, buy_usd),
+            None => Decimal::ZERO,
+        }
+    }
+}
+
+/// Shortfall of the OKX short position relative to `target_ratio` of the allocated
+/// liability, positive when under-hedged. `None` while nothing is allocated.
+async fn current_imbalance(
+    ledger: &Ledger,
+    target_ratio: Decimal,
+) -> Result<Option<Decimal>, LedgerError> {
+    let balances = ledger.balances();
+    let liability = Decimal::from(balances.usd_liability_balances().await?.okex_allocation);
+    if liability.is_zero() {
+        return Ok(None);
+    }
+    let short_position = balances
+        .okex_position_account_balance()
+        .await?
+        .map(|b| b.settled() * CENTS_PER_USD)
+        .unwrap_or(Decimal::ZERO);
+    Ok(Some(target_ratio - short_position / liability))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn no_skew_within_dead_band() {
+        let skew = InventorySkew::new(InventorySkewConfig::default());
+        assert_eq!(skew.rate_for(dec!(0.05), true), Decimal::ZERO);
+        assert_eq!(skew.rate_for(dec!(-0.03), false), Decimal::ZERO);
+    }
+
+    #[test]
+    fn penalizes_trades_widening_the_imbalance() {
+        let skew = InventorySkew::new(InventorySkewConfig::default());
+        assert_eq!(skew.rate_for(dec!(0.15), true), dec!(0.002));
+        assert_eq!(skew.rate_for(dec!(0.15), false), dec!(-0.002));
+        assert_eq!(skew.rate_for(dec!(-0.15), true), dec!(-0.002));
+        assert_eq!(skew.rate_for(dec!(-0.15), false), dec!(0.002));
+    }
+
+    #[tokio::test]
+    async fn no_skew_before_first_refresh() {
+        let skew = InventorySkew::new(InventorySkewConfig::default());
+        assert_eq!(skew.rate(true).await, Decimal::ZERO);
+    }
+
+    #[test]
+    fn skew_is_shaped_and_capped() {
+        let skew = InventorySkew::new(InventorySkewConfig {
+            curve: InventorySkewCurve::Quadratic,
+            multiplier: dec!(0.1),
+            ..Default::default()
+        });
+        assert_eq!(skew.rate_for(dec!(0.15), true), dec!(0.001));
+        assert_eq!(skew.rate_for(dec!(0.55), true), dec!(0.005));
+    }
+}
@@ -1,8 +1,12 @@
 use chrono::{DateTime, Utc};
 use derive_builder::Builder;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared::time::TimeStamp;
 
-use crate::{currency::*, entity::*};
+use pricing::currency::*;
+
+use crate::entity::*;
 
 use super::QuoteError;
 
@@ -15,6 +19,67 @@ pub enum Direction {
     SellCents,
 }
 
+/// Amount a quote was requested for, the other side being priced from it
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestedAmount {
+    Sats(Sats),
+    Cents(UsdCents),
+}
+
+impl RequestedAmount {
+    pub fn value(&self) -> Decimal {
+        match self {
+            RequestedAmount::Sats(sats) => *sats.amount(),
+            RequestedAmount::Cents(cents) => *cents.amount(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteState {
+    Pending,
+    Accepted,
+    Expired,
+    Cancelled,
+}
+
+/// Snapshot timestamp, contributing order books and fee behind the quoted amounts
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QuotePriceDetails {
+    pub timestamp: TimeStamp,
+    pub exchanges: Vec<QuoteExchangeWeight>,
+    pub pre_fee_sat_amount: Sats,
+    pub pre_fee_cent_amount: UsdCents,
+    pub fee_rate: Decimal,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QuoteExchangeWeight {
+    pub exchange_id: String,
+    pub weight: Decimal,
+    #[serde(default)]
+    pub timestamp: Option<TimeStamp>,
+    /// Order books of each venue behind the exchange's price, empty for tick based prices
+    #[serde(default)]
+    pub books: Vec<QuoteOrderBook>,
+}
+
+/// Best levels of a venue's order book, ordered from the top of each side
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QuoteOrderBook {
+    pub exchange_id: String,
+    pub timestamp: TimeStamp,
+    pub bids: Vec<QuoteBookLevel>,
+    pub asks: Vec<QuoteBookLevel>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QuoteBookLevel {
+    pub price_of_one_sat_in_cents: Decimal,
+    pub volume_in_cents: Decimal,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum QuoteEvent {
@@ -22,14 +87,32 @@ pub enum QuoteEvent {
         id: QuoteId,
         direction: Direction,
         immediate_execution: bool,
-        sat_amount: Satoshis,
+        sat_amount: Sats,
         cent_amount: UsdCents,
-        sats_spread: Satoshis,
+        sats_spread: Sats,
         cents_spread: UsdCents,
         expires_at: DateTime<Utc>,
+        #[serde(default)]
+        price_details: Option<QuotePriceDetails>,
+        #[serde(default)]
+        idempotency_key: Option<String>,
+        #[serde(default)]
+        account_id: Option<String>,
+        #[serde(default)]
+        capacity_limited: bool,
+        #[serde(default)]
+        requested_amount: Option<RequestedAmount>,
     },
     Accepted {
         accepted_at: DateTime<Utc>,
+        #[serde(default)]
+        idempotency_key: Option<String>,
+    },
+    Expired {
+        expired_at: DateTime<Utc>,
+    },
+    Cancelled {
+        cancelled_at: DateTime<Utc>,
     },
 }
 
@@ -38,12 +121,21 @@ pub enum QuoteEvent {
 pub struct Quote {
     pub id: QuoteId,
     pub direction: Direction,
-    pub sat_amount: Satoshis,
+    pub sat_amount: Sats,
     pub cent_amount: UsdCents,
-    pub sats_spread: Satoshis,
+    pub sats_spread: Sats,
     pub cents_spread: UsdCents,
     pub immediate_execution: bool,
     pub expires_at: DateTime<Utc>,
+    pub price_details: Option<QuotePriceDetails>,
+    pub idempotency_key: Option<String>,
+    pub account_id: Option<String>,
+    pub capacity_limited: bool,
+    /// Unset for quotes stored before requested amounts were recorded
+    pub requested_amount: Option<RequestedAmount>,
+    /// Priced but never stored, so it cannot be accepted
+    #[builder(default)]
+    pub indicative: bool,
 
     pub(super) events: EntityEvents<QuoteEvent>,
 }
@@ -58,31 +150,121 @@ impl Quote {
         false
     }
 
-    pub fn accept(&mut self) -> Result<(), QuoteError> {
+    pub fn is_cancelled(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, QuoteEvent::Cancelled { .. }))
+    }
+
+    pub fn accept(&mut self, idempotency_key: Option<String>) -> Result<(), QuoteError> {
         if self.is_accepted() {
             return Err(QuoteError::QuoteAlreadyAccepted);
         }
+        if self.is_cancelled() {
+            return Err(QuoteError::QuoteCancelled);
+        }
         if self.is_expired() {
             return Err(QuoteError::QuoteExpiredError);
         }
         self.events.push(QuoteEvent::Accepted {
             accepted_at: Utc::now(),
+            idempotency_key,
         });
         Ok(())
     }
 
-    fn is_expired(&self) -> bool {
-        self.expires_at < Utc::now()
+    /// Records the expiry of a quote that was neither accepted nor cancelled in time
+    pub fn expire(&mut self) -> Result<(), QuoteError> {
+        if self.is_accepted() {
+            return Err(QuoteError::QuoteAlreadyAccepted);
+        }
+        if self.is_cancelled() {
+            return Err(QuoteError::QuoteCancelled);
+        }
+        if !self.is_expired() {
+            return Err(QuoteError::QuoteNotExpired);
+        }
+        if !self.has_expired_event() {
+            self.events.push(QuoteEvent::Expired {
+                expired_at: Utc::now(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Cancelling a quote that is already cancelled is a no-op
+    pub fn cancel(&mut self) -> Result<(), QuoteError> {
+        if self.is_accepted() {
+            return Err(QuoteError::QuoteAlreadyAccepted);
+        }
+        if self.is_cancelled() {
+            return Ok(());
+        }
+        if self.is_expired() {
+            return Err(QuoteError::QuoteExpiredError);
+        }
+        self.events.push(QuoteEvent::Cancelled {
+            cancelled_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.has_expired_event() || self.expires_at < Utc::now()
+    }
+
+    fn has_expired_event(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, QuoteEvent::Expired { .. }))
+    }
+
+    pub fn state(&self) -> QuoteState {
+        if self.is_accepted() {
+            QuoteState::Accepted
+        } else if self.is_cancelled() {
+            QuoteState::Cancelled
+        } else if self.is_expired() {
+            QuoteState::Expired
+        } else {
+            QuoteState::Pending
+        }
+    }
+
+    /// Falls back to the quoted amounts for quotes without a recorded request
+    pub fn was_requested_for(&self, amount: &RequestedAmount) -> bool {
+        match (&self.requested_amount, amount) {
+            (Some(requested), _) => requested == amount,
+            (None, RequestedAmount::Sats(sats)) => self.sat_amount == *sats,
+            (None, RequestedAmount::Cents(cents)) => self.cent_amount == *cents,
+        }
+    }
+
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = &QuoteEvent> {
+        self.events.iter()
     }
 
     pub fn accepted_at(&self) -> Option<DateTime<Utc>> {
         for event in self.events.iter() {
-            if let QuoteEvent::Accepted { accepted_at } = event {
+            if let QuoteEvent::Accepted { accepted_at, .. } = event {
                 return Some(*accepted_at);
             }
         }
         None
     }
+
+    /// Key the client supplied when accepting the quote
+    pub fn accept_idempotency_key(&self) -> Option<&str> {
+        for event in self.events.iter() {
+            if let QuoteEvent::Accepted {
+                idempotency_key, ..
+            } = event
+            {
+                return idempotency_key.as_deref();
+            }
+        }
+        None
+    }
 }
 
 #[derive(Builder, Clone, Debug)]
@@ -91,11 +273,21 @@ pub struct NewQuote {
     pub(super) id: QuoteId,
     pub(super) direction: Direction,
     pub(super) immediate_execution: bool,
-    pub(super) sat_amount: Satoshis,
+    pub(super) sat_amount: Sats,
     pub(super) cent_amount: UsdCents,
-    pub(super) sats_spread: Satoshis,
+    pub(super) sats_spread: Sats,
     pub(super) cents_spread: UsdCents,
     pub(super) expires_at: DateTime<Utc>,
+    pub(super) price_details: QuotePriceDetails,
+    #[builder(default)]
+    pub(super) idempotency_key: Option<String>,
+    /// Galoy account the quote was requested for
+    #[builder(default)]
+    pub(super) account_id: Option<String>,
+    /// Amount was reduced to the remaining hedging capacity
+    #[builder(default)]
+    pub(super) capacity_limited: bool,
+    pub(super) requested_amount: RequestedAmount,
 }
 
 impl NewQuote {
@@ -105,6 +297,22 @@ impl NewQuote {
         builder
     }
 
+    pub fn cent_amount(&self) -> UsdCents {
+        self.cent_amount
+    }
+
+    pub fn immediate_execution(&self) -> bool {
+        self.immediate_execution
+    }
+
+    /// The quote as it would be stored, for requests that only want a price
+    pub fn into_indicative(self) -> Quote {
+        let mut quote =
+            Quote::try_from(self.initial_events()).expect("Could not build indicative quote");
+        quote.indicative = true;
+        quote
+    }
+
     pub(super) fn initial_events(self) -> EntityEvents<QuoteEvent> {
         EntityEvents::init([QuoteEvent::Initialized {
             id: self.id,
@@ -115,6 +323,11 @@ impl NewQuote {
             sats_spread: self.sats_spread,
             cents_spread: self.cents_spread,
             expires_at: self.expires_at,
+            price_details: Some(self.price_details),
+            idempotency_key: self.idempotency_key,
+            account_id: self.account_id,
+            capacity_limited: self.capacity_limited,
+            requested_amount: Some(self.requested_amount),
         }])
     }
 }
@@ -135,6 +348,11 @@ impl TryFrom<EntityEvents<QuoteEvent>> for Quote {
                 sats_spread,
                 cents_spread,
                 expires_at,
+                price_details,
+                idempotency_key,
+                account_id,
+                capacity_limited,
+                requested_amount,
             } = event
             {
                 builder = builder
@@ -145,7 +363,12 @@ impl TryFrom<EntityEvents<QuoteEvent>> for Quote {
                     .cent_amount(*cent_amount)
                     .sats_spread(*sats_spread)
                     .cents_spread(*cents_spread)
-                    .expires_at(*expires_at);
+                    .expires_at(*expires_at)
+                    .price_details(price_details.clone())
+                    .idempotency_key(idempotency_key.clone())
+                    .account_id(account_id.clone())
+                    .capacity_limited(*capacity_limited)
+                    .requested_amount(*requested_amount);
             }
         }
         builder.events(events).build()
@@ -197,11 +420,16 @@ mod tests {
             id: QuoteId::new(),
             direction: Direction::BuyCents,
             immediate_execution: false,
-            sat_amount: Satoshis::from(Decimal::from(1000)),
+            sat_amount: Sats::from(Decimal::from(1000)),
             cent_amount: UsdCents::from(Decimal::from(100)),
-            sats_spread: Satoshis::from(Decimal::from(10)),
+            sats_spread: Sats::from(Decimal::from(10)),
             cents_spread: UsdCents::from(Decimal::from(1)),
             expires_at: expiration_time,
+            price_details: None,
+            idempotency_key: None,
+            account_id: None,
+            capacity_limited: false,
+            requested_amount: Some(RequestedAmount::Cents(UsdCents::from(Decimal::from(100)))),
         }])
     }
 
@@ -209,7 +437,7 @@ mod tests {
     fn accept_quote() {
         let events = init_events(false);
         let mut quote = Quote::try_from(events).unwrap();
-        assert!(quote.accept().is_ok());
+        assert!(quote.accept(None).is_ok());
         assert!(matches!(
             quote.events.last(1)[0],
             QuoteEvent::Accepted { .. }
@@ -221,10 +449,11 @@ mod tests {
         let mut events = init_events(false);
         events.push(QuoteEvent::Accepted {
             accepted_at: Utc::now(),
+            idempotency_key: None,
         });
         let mut quote = Quote::try_from(events).unwrap();
         assert!(matches!(
-            quote.accept(),
+            quote.accept(None),
             Err(QuoteError::QuoteAlreadyAccepted)
         ));
     }
@@ -233,6 +462,124 @@ mod tests {
     fn cannot_accept_expired_quote() {
         let events = init_events(true);
         let mut quote = Quote::try_from(events).unwrap();
-        assert!(matches!(quote.accept(), Err(QuoteError::QuoteExpiredError)));
+        assert!(matches!(
+            quote.accept(None),
+            Err(QuoteError::QuoteExpiredError)
+        ));
+    }
+
+    #[test]
+    fn expire_quote() {
+        let mut quote = Quote::try_from(init_events(false)).unwrap();
+        assert!(matches!(quote.expire(), Err(QuoteError::QuoteNotExpired)));
+
+        let mut quote = Quote::try_from(init_events(true)).unwrap();
+        quote.expire().unwrap();
+        quote.expire().unwrap();
+        assert_eq!(quote.events().count(), 2);
+        assert!(matches!(
+            quote.events.last(1)[0],
+            QuoteEvent::Expired { .. }
+        ));
+        assert_eq!(quote.state(), QuoteState::Expired);
+        assert!(matches!(quote.cancel(), Err(QuoteError::QuoteExpiredError)));
+    }
+
+    #[test]
+    fn cancel_quote() {
+        let mut quote = Quote::try_from(init_events(false)).unwrap();
+        quote.cancel().unwrap();
+        quote.cancel().unwrap();
+        assert_eq!(quote.events().count(), 2);
+        assert_eq!(quote.state(), QuoteState::Cancelled);
+        assert!(matches!(
+            quote.accept(None),
+            Err(QuoteError::QuoteCancelled)
+        ));
+        assert!(matches!(quote.expire(), Err(QuoteError::QuoteCancelled)));
+
+        let mut quote = Quote::try_from(init_events(false)).unwrap();
+        quote.accept(None).unwrap();
+        assert!(matches!(
+            quote.cancel(),
+            Err(QuoteError::QuoteAlreadyAccepted)
+        ));
+    }
+
+    #[test]
+    fn quote_state() {
+        let mut quote = Quote::try_from(init_events(false)).unwrap();
+        assert_eq!(quote.state(), QuoteState::Pending);
+        quote.accept(Some("accept-key".to_string())).unwrap();
+        assert_eq!(quote.state(), QuoteState::Accepted);
+        assert_eq!(quote.accept_idempotency_key(), Some("accept-key"));
+        assert_eq!(quote.events().count(), 2);
+
+        let quote = Quote::try_from(init_events(true)).unwrap();
+        assert_eq!(quote.state(), QuoteState::Expired);
+    }
+
+    #[test]
+    fn capacity_limited_quote_matches_the_requested_amount() {
+        let events = EntityEvents::init([QuoteEvent::Initialized {
+            id: QuoteId::new(),
+            direction: Direction::BuyCents,
+            immediate_execution: false,
+            sat_amount: Sats::from(Decimal::from(1000)),
+            cent_amount: UsdCents::from(Decimal::from(100)),
+            sats_spread: Sats::from(Decimal::from(10)),
+            cents_spread: UsdCents::from(Decimal::from(1)),
+            expires_at: Utc::now(),
+            price_details: None,
+            idempotency_key: None,
+            account_id: None,
+            capacity_limited: true,
+            requested_amount: Some(RequestedAmount::Cents(UsdCents::from(Decimal::from(500)))),
+        }]);
+        let quote = Quote::try_from(events).unwrap();
+        assert!(
+            quote.was_requested_for(&RequestedAmount::Cents(UsdCents::from(Decimal::from(500))))
+        );
+        assert!(
+            !quote.was_requested_for(&RequestedAmount::Cents(UsdCents::from(Decimal::from(100))))
+        );
+    }
+
+    #[test]
+    fn loads_quotes_initialized_without_price_details() {
+        let event: QuoteEvent = serde_json::from_value(serde_json::json!({
+            "type": "initialized",
+            "id": QuoteId::new(),
+            "direction": "buy_cents",
+            "immediate_execution": false,
+            "sat_amount": "1000",
+            "cent_amount": "100",
+            "sats_spread": "10",
+            "cents_spread": "1",
+            "expires_at": Utc::now(),
+        }))
+        .unwrap();
+        let quote = Quote::try_from(EntityEvents::init([event])).unwrap();
+        assert!(quote.price_details.is_none());
+        assert!(quote.idempotency_key.is_none());
+        assert!(quote.account_id.is_none());
+        assert!(!quote.capacity_limited);
+        assert!(quote.requested_amount.is_none());
+        assert!(quote.was_requested_for(&RequestedAmount::Sats(Sats::from(Decimal::from(1000)))));
+    }
+
+    #[test]
+    fn loads_price_details_without_book_snapshots() {
+        let details: QuotePriceDetails = serde_json::from_value(serde_json::json!({
+            "timestamp": TimeStamp::now(),
+            "exchanges": [{ "exchange_id": "okex", "weight": "1" }],
+            "pre_fee_sat_amount": "1000",
+            "pre_fee_cent_amount": "100",
+            "fee_rate": "0.001",
+        }))
+        .unwrap();
+        let exchange = &details.exchanges[0];
+        assert!(exchange.timestamp.is_none());
+        assert!(exchange.books.is_empty());
     }
 }
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::QuoteId;
+
 #[derive(Error, Debug)]
 pub enum QuoteError {
     #[error("QuotesError - Sqlx: {0}")]
@@ -10,4 +12,14 @@ pub enum QuoteError {
     QuoteAlreadyAccepted,
     #[error("QuotesError - Quote has expired")]
     QuoteExpiredError,
+    #[error("QuotesError - Quote has not expired yet")]
+    QuoteNotExpired,
+    #[error("QuotesError - Quote was cancelled")]
+    QuoteCancelled,
+    #[error("QuotesError - Quote not found: {0}")]
+    QuoteNotFound(QuoteId),
+    #[error("QuotesError - Idempotency key already used for a different request: {0}")]
+    IdempotencyKeyReused(String),
+    #[error("QuotesError - Idempotency key is being used concurrently")]
+    DuplicateIdempotencyKey,
 }
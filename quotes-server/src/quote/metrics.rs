@@ -0,0 +1,59 @@
+use opentelemetry::{metrics::Gauge, KeyValue};
+use tokio::sync::RwLock;
+
+use std::sync::Arc;
+
+use shared::health::HealthDetails;
+
+use super::QuoteCounts;
+
+/// Quote counts last recorded by the expiry sweeper, exported as the
+/// `stablesats.quotes` gauge and reported with the quotes-server health
+#[derive(Clone)]
+pub struct QuoteMetrics {
+    counts: Arc<RwLock<Option<QuoteCounts>>>,
+    gauge: Gauge<u64>,
+}
+
+impl QuoteMetrics {
+    pub fn new() -> Self {
+        Self {
+            counts: Arc::new(RwLock::new(None)),
+            gauge: opentelemetry::global::meter("quotes_server")
+                .u64_gauge("stablesats.quotes")
+                .with_description("Quotes in each state")
+                .build(),
+        }
+    }
+
+    pub async fn record(&self, counts: QuoteCounts) {
+        for (state, n) in [
+            ("open", counts.open),
+            ("accepted", counts.accepted),
+            ("expired", counts.expired),
+            ("cancelled", counts.cancelled),
+        ] {
+            self.gauge.record(n, &[KeyValue::new("state", state)]);
+        }
+        *self.counts.write().await = Some(counts);
+    }
+
+    /// Empty until the sweeper has run once
+    pub async fn health_details(&self) -> HealthDetails {
+        match *self.counts.read().await {
+            Some(counts) => HealthDetails::from([
+                ("open_quotes", counts.open),
+                ("accepted_quotes", counts.accepted),
+                ("expired_quotes", counts.expired),
+                ("cancelled_quotes", counts.cancelled),
+            ]),
+            None => HealthDetails::new(),
+        }
+    }
+}
+
+impl Default for QuoteMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
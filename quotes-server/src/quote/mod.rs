@@ -1,7 +1,9 @@
 mod entity;
 mod error;
+mod metrics;
 mod repo;
 
 pub use entity::*;
 pub use error::*;
+pub use metrics::*;
 pub use repo::*;
@@ -1,10 +1,54 @@
-use sqlx::{Pool, Postgres, Transaction};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{Executor, Pool, Postgres, QueryBuilder, Row, Transaction};
+use std::collections::HashMap;
 use tracing::instrument;
 
 use crate::entity::*;
 
 use super::{entity::*, error::QuoteError};
 
+/// Restricts `Quotes::list` to matching quotes. Unset fields match every quote.
+#[derive(Debug, Clone, Default)]
+pub struct QuotesFilter {
+    pub direction: Option<Direction>,
+    pub state: Option<QuoteState>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+/// Number of quotes in each `QuoteState`, `open` counting pending quotes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuoteCounts {
+    pub open: u64,
+    pub accepted: u64,
+    pub expired: u64,
+    pub cancelled: u64,
+}
+
+/// Quotes of a single account, see `Quotes::account_usage`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccountUsage {
+    pub open_quotes: u64,
+    pub cents: Decimal,
+}
+
+/// State of the quote `q` whose initialized event is `i`, mirroring `Quote::state`.
+/// The stored state lags for quotes that expired before the sweeper recorded it.
+const STATE_SQL: &str = r#"(CASE
+    WHEN q.state = 'open' AND (i.event->>'expires_at')::timestamptz < NOW() THEN 'expired'
+    ELSE q.state
+END)"#;
+
+fn state_name(state: QuoteState) -> &'static str {
+    match state {
+        QuoteState::Pending => "open",
+        QuoteState::Accepted => "accepted",
+        QuoteState::Expired => "expired",
+        QuoteState::Cancelled => "cancelled",
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Quotes {
     pool: Pool<Postgres>,
@@ -22,12 +66,15 @@ impl Quotes {
         quote: NewQuote,
     ) -> Result<Quote, QuoteError> {
         sqlx::query!(
-            r#"INSERT INTO stablesats_quotes (id)
-               VALUES ($1)"#,
-            quote.id as QuoteId
+            r#"INSERT INTO stablesats_quotes (id, idempotency_key, account_id)
+               VALUES ($1, $2, $3)"#,
+            quote.id as QuoteId,
+            quote.idempotency_key.as_deref(),
+            quote.account_id.as_deref()
         )
         .execute(&mut **tx)
-        .await?;
+        .await
+        .map_err(idempotency_key_conflict)?;
 
         let id = quote.id;
         let mut initial_events = quote.initial_events();
@@ -59,6 +106,9 @@ impl Quotes {
         .fetch_all(&self.pool)
         .await?;
 
+        if rows.is_empty() {
+            return Err(QuoteError::QuoteNotFound(id));
+        }
         let mut entity_events = EntityEvents::new();
         for row in rows {
             entity_events.load_event(row.sequence as usize, row.event)?;
@@ -67,6 +117,229 @@ impl Quotes {
         Ok(Quote::try_from(entity_events)?)
     }
 
+    pub async fn find_by_idempotency_key(&self, key: &str) -> Result<Option<Quote>, QuoteError> {
+        let id = sqlx::query_scalar!(
+            r#"SELECT id AS "id: QuoteId" FROM stablesats_quotes WHERE idempotency_key = $1"#,
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        match id {
+            Some(id) => Ok(Some(self.find_by_id(id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Quotes matching `filter` ordered by creation, starting after the quote `after`
+    #[instrument(name = "quotes.list", skip(self))]
+    pub async fn list(
+        &self,
+        filter: &QuotesFilter,
+        after: Option<QuoteId>,
+        limit: i64,
+    ) -> Result<Vec<Quote>, QuoteError> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            r#"SELECT q.id
+               FROM stablesats_quotes q
+               JOIN stablesats_quote_events i ON i.id = q.id AND i.sequence = 1
+               WHERE TRUE"#,
+        );
+        if let Some(direction) = filter.direction.as_ref() {
+            let direction = match direction {
+                Direction::BuyCents => "buy_cents",
+                Direction::SellCents => "sell_cents",
+            };
+            query
+                .push(" AND i.event->>'direction' = ")
+                .push_bind(direction);
+        }
+        if let Some(state) = filter.state {
+            query
+                .push(" AND ")
+                .push(STATE_SQL)
+                .push(" = ")
+                .push_bind(state_name(state));
+        }
+        if let Some(from) = filter.created_from {
+            query.push(" AND q.created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.created_to {
+            query.push(" AND q.created_at < ").push_bind(to);
+        }
+        if let Some(after) = after {
+            query
+                .push(" AND (q.created_at, q.id) > (SELECT created_at, id FROM stablesats_quotes WHERE id = ")
+                .push_bind(after)
+                .push(")");
+        }
+        query
+            .push(" ORDER BY q.created_at, q.id LIMIT ")
+            .push_bind(limit);
+        let ids: Vec<QuoteId> = query
+            .build()
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| row.try_get("id"))
+            .collect::<Result<_, _>>()?;
+        self.load_all(ids).await
+    }
+
+    /// Pending quotes whose expiry has passed without an `Expired` event
+    pub async fn list_overdue(&self, limit: i64) -> Result<Vec<Quote>, QuoteError> {
+        let ids = sqlx::query_scalar!(
+            r#"SELECT q.id AS "id: QuoteId"
+               FROM stablesats_quotes q
+               JOIN stablesats_quote_events i ON i.id = q.id AND i.sequence = 1
+               WHERE q.state = 'open' AND (i.event->>'expires_at')::timestamptz < NOW()
+               ORDER BY q.created_at, q.id
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        self.load_all(ids).await
+    }
+
+    /// Open quotes and cents committed by `account_id` since `since`, counting
+    /// accepted and still open quotes. Holds a per-account lock until `tx` ends
+    /// so concurrent quotes for the same account are checked one at a time.
+    pub async fn account_usage(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        account_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<AccountUsage, QuoteError> {
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", account_id)
+            .execute(&mut **tx)
+            .await?;
+        // Same expression as `STATE_SQL`, which query macros cannot interpolate
+        let row = sqlx::query!(
+            r#"WITH states AS (
+                 SELECT
+                   (CASE
+                     WHEN q.state = 'open' AND (i.event->>'expires_at')::timestamptz < NOW() THEN 'expired'
+                     ELSE q.state
+                   END) AS state,
+                   q.created_at,
+                   (i.event->>'cent_amount')::NUMERIC AS cents
+                 FROM stablesats_quotes q
+                 JOIN stablesats_quote_events i ON i.id = q.id AND i.sequence = 1
+                 WHERE q.account_id = $1
+               )
+               SELECT
+                 COUNT(*) FILTER (WHERE state = 'open') AS "open_quotes!",
+                 COALESCE(SUM(cents) FILTER (
+                   WHERE state IN ('open', 'accepted') AND created_at >= $2
+                 ), 0) AS "cents!"
+               FROM states"#,
+            account_id,
+            since
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(AccountUsage {
+            open_quotes: row.open_quotes as u64,
+            cents: row.cents,
+        })
+    }
+
+    /// Holds the hedging capacity lock until `tx` ends so concurrent buy quotes
+    /// cannot reserve the same remaining capacity.
+    pub async fn lock_hedging_capacity(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> Result<(), QuoteError> {
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext('stablesats_hedging_capacity'))")
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    /// Total cents of the open quotes in `direction`
+    pub async fn open_cents<'a>(
+        &self,
+        executor: impl Executor<'a, Database = Postgres>,
+        direction: Direction,
+    ) -> Result<Decimal, QuoteError> {
+        let direction = match direction {
+            Direction::BuyCents => "buy_cents",
+            Direction::SellCents => "sell_cents",
+        };
+        // Quotes `STATE_SQL` reports as open
+        let cents = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM((i.event->>'cent_amount')::NUMERIC), 0) AS "cents!"
+               FROM stablesats_quotes q
+               JOIN stablesats_quote_events i ON i.id = q.id AND i.sequence = 1
+               WHERE i.event->>'direction' = $1
+                 AND q.state = 'open'
+                 AND (i.event->>'expires_at')::timestamptz >= NOW()"#,
+            direction
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(cents)
+    }
+
+    pub async fn count_by_state(&self) -> Result<QuoteCounts, QuoteError> {
+        // Same expression as `STATE_SQL`, which query macros cannot interpolate
+        let rows = sqlx::query!(
+            r#"SELECT
+                 (CASE
+                   WHEN q.state = 'open' AND (i.event->>'expires_at')::timestamptz < NOW() THEN 'expired'
+                   ELSE q.state
+                 END) AS "state!",
+                 COUNT(*) AS "n!"
+               FROM stablesats_quotes q
+               JOIN stablesats_quote_events i ON i.id = q.id AND i.sequence = 1
+               GROUP BY 1"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut counts = QuoteCounts::default();
+        for row in rows {
+            let n = row.n as u64;
+            match row.state.as_str() {
+                "open" => counts.open = n,
+                "accepted" => counts.accepted = n,
+                "expired" => counts.expired = n,
+                _ => counts.cancelled = n,
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn load_all(&self, ids: Vec<QuoteId>) -> Result<Vec<Quote>, QuoteError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query!(
+            r#"SELECT id AS "id: QuoteId", sequence, event
+               FROM stablesats_quote_events
+               WHERE id = ANY($1)
+               ORDER BY id, sequence"#,
+            &ids as &[QuoteId]
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut events: HashMap<QuoteId, EntityEvents<QuoteEvent>> = HashMap::new();
+        for row in rows {
+            events
+                .entry(row.id)
+                .or_insert_with(EntityEvents::new)
+                .load_event(row.sequence as usize, row.event)?;
+        }
+
+        let mut quotes = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(events) = events.remove(&id) {
+                quotes.push(Quote::try_from(events)?);
+            }
+        }
+        Ok(quotes)
+    }
+
     pub async fn update(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -82,6 +355,33 @@ impl Quotes {
         )
         .await?;
         quote.events.mark_persisted();
+        sqlx::query!(
+            "UPDATE stablesats_quotes SET state = $2 WHERE id = $1",
+            quote.id as QuoteId,
+            state_name(quote.state())
+        )
+        .execute(&mut **tx)
+        .await?;
+        if let Some(key) = quote.accept_idempotency_key() {
+            sqlx::query!(
+                r#"UPDATE stablesats_quotes SET accept_idempotency_key = $2
+                   WHERE id = $1 AND accept_idempotency_key IS NULL"#,
+                quote.id as QuoteId,
+                key
+            )
+            .execute(&mut **tx)
+            .await
+            .map_err(idempotency_key_conflict)?;
+        }
         Ok(())
     }
 }
+
+fn idempotency_key_conflict(err: sqlx::Error) -> QuoteError {
+    match err {
+        sqlx::Error::Database(err) if err.is_unique_violation() => {
+            QuoteError::DuplicateIdempotencyKey
+        }
+        err => QuoteError::Sqlx(err),
+    }
+}
@@ -4,11 +4,14 @@ use serde::{Deserialize, Serialize};
 pub struct QuotesServerConfig {
     #[serde(default = "default_port")]
     pub listen_port: u16,
+    #[serde(default)]
+    pub gateway: QuotesGatewayConfig,
 }
 impl Default for QuotesServerConfig {
     fn default() -> Self {
         Self {
             listen_port: default_port(),
+            gateway: QuotesGatewayConfig::default(),
         }
     }
 }
@@ -16,3 +19,24 @@ impl Default for QuotesServerConfig {
 fn default_port() -> u16 {
     3326
 }
+
+/// HTTP/JSON gateway mirroring the gRPC service on its own port
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuotesGatewayConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_gateway_port")]
+    pub listen_port: u16,
+}
+impl Default for QuotesGatewayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_port: default_gateway_port(),
+        }
+    }
+}
+
+fn default_gateway_port() -> u16 {
+    3336
+}
@@ -1,11 +1,69 @@
+use chrono::{DateTime, Duration, Utc};
 use rust_decimal::prelude::ToPrimitive;
 
 use crate::{
+    app::HedgingCapacity,
     error::QuotesAppError,
-    proto::{GetQuoteToBuyUsdResponse, GetQuoteToSellUsdResponse},
-    quote::Quote,
+    proto::{self, GetCapacityResponse, GetQuoteToBuyUsdResponse, GetQuoteToSellUsdResponse},
+    quote::*,
 };
 
+impl From<QuotePriceDetails> for proto::QuotePriceDetails {
+    fn from(details: QuotePriceDetails) -> Self {
+        Self {
+            timestamp: details.timestamp.timestamp(),
+            exchanges: details
+                .exchanges
+                .into_iter()
+                .map(|exchange| proto::ExchangeWeight {
+                    exchange_id: exchange.exchange_id,
+                    weight: exchange.weight.to_f64().unwrap_or_default(),
+                    timestamp: exchange
+                        .timestamp
+                        .map(|timestamp| timestamp.timestamp())
+                        .unwrap_or_default(),
+                    books: exchange
+                        .books
+                        .into_iter()
+                        .map(proto::OrderBook::from)
+                        .collect(),
+                })
+                .collect(),
+            pre_fee_amount_in_sats: details
+                .pre_fee_sat_amount
+                .amount()
+                .to_u64()
+                .expect("pre_fee_sat_amount should always parse to u64"),
+            pre_fee_amount_in_cents: details
+                .pre_fee_cent_amount
+                .amount()
+                .to_u64()
+                .expect("pre_fee_cent_amount should always parse to u64"),
+            fee_rate: details.fee_rate.to_f64().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<QuoteOrderBook> for proto::OrderBook {
+    fn from(book: QuoteOrderBook) -> Self {
+        Self {
+            exchange_id: book.exchange_id,
+            timestamp: book.timestamp.timestamp(),
+            bids: book.bids.into_iter().map(proto::BookLevel::from).collect(),
+            asks: book.asks.into_iter().map(proto::BookLevel::from).collect(),
+        }
+    }
+}
+
+impl From<QuoteBookLevel> for proto::BookLevel {
+    fn from(level: QuoteBookLevel) -> Self {
+        Self {
+            price_of_one_sat_in_cents: level.price_of_one_sat_in_cents.to_f64().unwrap_or_default(),
+            volume_in_cents: level.volume_in_cents.to_f64().unwrap_or_default(),
+        }
+    }
+}
+
 impl From<Quote> for GetQuoteToBuyUsdResponse {
     fn from(quote: Quote) -> Self {
         Self {
@@ -26,6 +84,9 @@ impl From<Quote> for GetQuoteToBuyUsdResponse {
                 .to_u32()
                 .expect("timestamp should always parse to u32"),
             executed: quote.is_accepted(),
+            price_details: quote.price_details.map(proto::QuotePriceDetails::from),
+            capacity_limited: quote.capacity_limited,
+            indicative: quote.indicative,
         }
     }
 }
@@ -50,16 +111,224 @@ impl From<Quote> for GetQuoteToSellUsdResponse {
                 .to_u32()
                 .expect("timestamp should always parse to u32"),
             executed: quote.is_accepted(),
+            price_details: quote.price_details.map(proto::QuotePriceDetails::from),
+            indicative: quote.indicative,
+        }
+    }
+}
+
+impl From<Direction> for proto::QuoteDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::BuyCents => Self::BuyCents,
+            Direction::SellCents => Self::SellCents,
+        }
+    }
+}
+
+impl From<QuoteState> for proto::QuoteState {
+    fn from(state: QuoteState) -> Self {
+        match state {
+            QuoteState::Pending => Self::Pending,
+            QuoteState::Accepted => Self::Accepted,
+            QuoteState::Expired => Self::Expired,
+            QuoteState::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+impl From<&QuoteEvent> for proto::quote_event::Event {
+    fn from(event: &QuoteEvent) -> Self {
+        match event {
+            QuoteEvent::Initialized { .. } => Self::Initialized(proto::QuoteInitialized {}),
+            QuoteEvent::Accepted { accepted_at, .. } => Self::Accepted(proto::QuoteAccepted {
+                accepted_at: accepted_at.timestamp(),
+            }),
+            QuoteEvent::Expired { expired_at } => Self::Expired(proto::QuoteExpired {
+                expired_at: expired_at.timestamp(),
+            }),
+            QuoteEvent::Cancelled { cancelled_at } => Self::Cancelled(proto::QuoteCancelled {
+                cancelled_at: cancelled_at.timestamp(),
+            }),
+        }
+    }
+}
+
+impl From<Quote> for proto::Quote {
+    fn from(quote: Quote) -> Self {
+        Self {
+            quote_id: quote.id.to_string(),
+            direction: proto::QuoteDirection::from(quote.direction.clone()).into(),
+            state: proto::QuoteState::from(quote.state()).into(),
+            immediate_execution: quote.immediate_execution,
+            amount_in_sats: quote
+                .sat_amount
+                .amount()
+                .to_u64()
+                .expect("sat_amount should always parse to u64"),
+            amount_in_cents: quote
+                .cent_amount
+                .amount()
+                .to_u64()
+                .expect("cent_amount should always parse to u64"),
+            sats_spread: quote
+                .sats_spread
+                .amount()
+                .to_i64()
+                .expect("sats_spread should always parse to i64"),
+            cents_spread: quote
+                .cents_spread
+                .amount()
+                .to_i64()
+                .expect("cents_spread should always parse to i64"),
+            expires_at: quote
+                .expires_at
+                .timestamp()
+                .to_u32()
+                .expect("timestamp should always parse to u32"),
+            events: quote
+                .events()
+                .enumerate()
+                .map(|(i, event)| proto::QuoteEvent {
+                    sequence: i as u32 + 1,
+                    event: Some(event.into()),
+                })
+                .collect(),
+            price_details: quote.price_details.map(proto::QuotePriceDetails::from),
+            account_id: quote.account_id.unwrap_or_default(),
+            capacity_limited: quote.capacity_limited,
         }
     }
 }
 
+impl From<HedgingCapacity> for GetCapacityResponse {
+    fn from(capacity: HedgingCapacity) -> Self {
+        Self {
+            remaining_buy_usd_in_cents: capacity.remaining_cents().to_u64().unwrap_or_default(),
+            max_in_cents: capacity.max_cents.to_u64().unwrap_or_default(),
+            allocated_in_cents: capacity.allocated_cents.to_u64().unwrap_or_default(),
+            reserved_in_cents: capacity.reserved_cents.to_u64().unwrap_or_default(),
+            updated_at: capacity
+                .updated_at
+                .map(|updated_at| updated_at.timestamp())
+                .unwrap_or_default(),
+            hedging_healthy: capacity.hedging_healthy,
+        }
+    }
+}
+
+pub(super) fn quote_id(id: &str) -> Result<QuoteId, QuotesAppError> {
+    id.parse()
+        .map_err(QuotesAppError::CouldNotParseIncomingUuid)
+}
+
+pub(super) fn after_quote_id(id: &str) -> Result<Option<QuoteId>, QuotesAppError> {
+    if id.is_empty() {
+        return Ok(None);
+    }
+    quote_id(id).map(Some)
+}
+
+/// Interprets an empty key as no key
+pub(super) fn idempotency_key(key: String) -> Option<String> {
+    if key.is_empty() {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+/// Interprets an empty id as no account
+pub(super) fn account_id(id: String) -> Option<String> {
+    if id.is_empty() {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+/// Interprets 0 as the default lifetime
+pub(super) fn lifetime(seconds: u32) -> Option<Duration> {
+    if seconds == 0 {
+        None
+    } else {
+        Duration::try_seconds(seconds.into())
+    }
+}
+
+/// Interprets 0 as an open bound
+pub(super) fn created_bound(timestamp: i64) -> Result<Option<DateTime<Utc>>, QuotesAppError> {
+    if timestamp == 0 {
+        return Ok(None);
+    }
+    DateTime::from_timestamp(timestamp, 0)
+        .map(Some)
+        .ok_or_else(|| QuotesAppError::InvalidQuoteFilter(format!("timestamp {timestamp}")))
+}
+
+impl TryFrom<&proto::ListQuotesRequest> for QuotesFilter {
+    type Error = QuotesAppError;
+
+    fn try_from(req: &proto::ListQuotesRequest) -> Result<Self, Self::Error> {
+        let direction = match proto::QuoteDirection::try_from(req.direction) {
+            Ok(proto::QuoteDirection::Unspecified) => None,
+            Ok(proto::QuoteDirection::BuyCents) => Some(Direction::BuyCents),
+            Ok(proto::QuoteDirection::SellCents) => Some(Direction::SellCents),
+            Err(_) => {
+                return Err(QuotesAppError::InvalidQuoteFilter(format!(
+                    "direction {}",
+                    req.direction
+                )))
+            }
+        };
+        let state = match proto::QuoteState::try_from(req.state) {
+            Ok(proto::QuoteState::Unspecified) => None,
+            Ok(proto::QuoteState::Pending) => Some(QuoteState::Pending),
+            Ok(proto::QuoteState::Accepted) => Some(QuoteState::Accepted),
+            Ok(proto::QuoteState::Expired) => Some(QuoteState::Expired),
+            Ok(proto::QuoteState::Cancelled) => Some(QuoteState::Cancelled),
+            Err(_) => {
+                return Err(QuotesAppError::InvalidQuoteFilter(format!(
+                    "state {}",
+                    req.state
+                )))
+            }
+        };
+        Ok(Self {
+            direction,
+            state,
+            created_from: created_bound(req.created_from)?,
+            created_to: created_bound(req.created_to)?,
+        })
+    }
+}
+
 impl From<QuotesAppError> for tonic::Status {
     fn from(err: QuotesAppError) -> Self {
         match err {
-            QuotesAppError::CouldNotParseIncomingUuid(_) => {
+            QuotesAppError::CouldNotParseIncomingUuid(_)
+            | QuotesAppError::InvalidQuoteFilter(_)
+            | QuotesAppError::InvalidQuoteRequest(_) => {
                 tonic::Status::invalid_argument(err.to_string())
             }
+            QuotesAppError::QuoteError(QuoteError::QuoteNotFound(_)) => {
+                tonic::Status::not_found(err.to_string())
+            }
+            QuotesAppError::QuoteError(
+                QuoteError::IdempotencyKeyReused(_) | QuoteError::DuplicateIdempotencyKey,
+            ) => tonic::Status::already_exists(err.to_string()),
+            QuotesAppError::QuoteError(
+                QuoteError::QuoteAlreadyAccepted
+                | QuoteError::QuoteExpiredError
+                | QuoteError::QuoteCancelled,
+            ) => tonic::Status::failed_precondition(err.to_string()),
+            QuotesAppError::AccountLimitExceeded(_)
+            | QuotesAppError::InsufficientHedgingCapacity(_) => {
+                tonic::Status::resource_exhausted(err.to_string())
+            }
+            QuotesAppError::HedgingCapacityUnavailable(_) => {
+                tonic::Status::unavailable(err.to_string())
+            }
             _ => tonic::Status::internal(err.to_string()),
         }
     }
@@ -7,6 +7,8 @@ use crate::error::QuotesAppError;
 pub enum QuotesServerError {
     #[error("QuotesServerError - TonicError: {0}")]
     TonicError(#[from] tonic::transport::Error),
+    #[error("QuotesServerError - GatewayError: {0}")]
+    GatewayError(#[from] std::io::Error),
     #[error("QuotesServerError - AppError: {0}")]
     AppError(#[from] QuotesAppError),
 }
@@ -0,0 +1,664 @@
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    routing::{get, post},
+    Router,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::gateway::{GatewayError, GatewayErrorBody, Json, Path, Query};
+use std::sync::Arc;
+use tonic::Status;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use super::{
+    config::QuotesGatewayConfig,
+    convert::{account_id, after_quote_id, created_bound, idempotency_key, lifetime, quote_id},
+    proto::{self, GetCapacityResponse, GetQuoteToBuyUsdResponse, GetQuoteToSellUsdResponse},
+};
+use crate::{
+    app::*,
+    error::QuotesAppError,
+    quote::{Direction, QuoteState, QuotesFilter},
+};
+
+#[derive(Deserialize, ToSchema)]
+pub struct GetQuoteToBuyUsdBody {
+    /// Exactly one of `amount_to_sell_in_sats` and `amount_to_buy_in_cents` must be set
+    amount_to_sell_in_sats: Option<u64>,
+    amount_to_buy_in_cents: Option<u64>,
+    #[serde(default)]
+    immediate_execution: bool,
+    /// Galoy account the quote is requested for
+    account_id: Option<String>,
+    /// Seconds the quote stays valid, defaults to the server lifetime
+    lifetime_in_seconds: Option<u32>,
+    /// Only price the request, the returned quote cannot be accepted
+    #[serde(default)]
+    indicative: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GetQuoteToSellUsdBody {
+    /// Exactly one of `amount_to_buy_in_sats` and `amount_to_sell_in_cents` must be set
+    amount_to_buy_in_sats: Option<u64>,
+    amount_to_sell_in_cents: Option<u64>,
+    #[serde(default)]
+    immediate_execution: bool,
+    /// Galoy account the quote is requested for
+    account_id: Option<String>,
+    /// Seconds the quote stays valid, defaults to the server lifetime
+    lifetime_in_seconds: Option<u32>,
+    /// Only price the request, the returned quote cannot be accepted
+    #[serde(default)]
+    indicative: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct QuoteToBuyUsd {
+    quote_id: String,
+    amount_to_sell_in_sats: u64,
+    amount_to_buy_in_cents: u64,
+    expires_at: u32,
+    executed: bool,
+    price_details: Option<PriceDetails>,
+    /// The amounts were reduced to the remaining hedging capacity
+    capacity_limited: bool,
+    indicative: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct QuoteToSellUsd {
+    quote_id: String,
+    amount_to_buy_in_sats: u64,
+    amount_to_sell_in_cents: u64,
+    expires_at: u32,
+    executed: bool,
+    price_details: Option<PriceDetails>,
+    indicative: bool,
+}
+
+/// The exchange snapshots and fee the quoted amounts were derived from
+#[derive(Serialize, ToSchema)]
+pub struct PriceDetails {
+    /// Timestamp of the oldest contributing snapshot
+    timestamp: i64,
+    exchanges: Vec<ExchangeWeight>,
+    pre_fee_amount_in_sats: u64,
+    pre_fee_amount_in_cents: u64,
+    fee_rate: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExchangeWeight {
+    exchange_id: String,
+    weight: f64,
+    /// Unix timestamp of the exchange snapshot the price was taken from
+    timestamp: Option<i64>,
+    /// Order books of each venue behind the exchange's price, empty for tick based prices
+    books: Vec<OrderBook>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OrderBook {
+    exchange_id: String,
+    /// Unix timestamp of the venue snapshot
+    timestamp: i64,
+    /// Best levels from the top of each side
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+}
+
+impl From<proto::OrderBook> for OrderBook {
+    fn from(book: proto::OrderBook) -> Self {
+        Self {
+            exchange_id: book.exchange_id,
+            timestamp: book.timestamp,
+            bids: book.bids.into_iter().map(BookLevel::from).collect(),
+            asks: book.asks.into_iter().map(BookLevel::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BookLevel {
+    price_of_one_sat_in_cents: f64,
+    volume_in_cents: f64,
+}
+
+impl From<proto::BookLevel> for BookLevel {
+    fn from(level: proto::BookLevel) -> Self {
+        Self {
+            price_of_one_sat_in_cents: level.price_of_one_sat_in_cents,
+            volume_in_cents: level.volume_in_cents,
+        }
+    }
+}
+
+impl From<proto::QuotePriceDetails> for PriceDetails {
+    fn from(details: proto::QuotePriceDetails) -> Self {
+        Self {
+            timestamp: details.timestamp,
+            exchanges: details
+                .exchanges
+                .into_iter()
+                .map(|exchange| ExchangeWeight {
+                    exchange_id: exchange.exchange_id,
+                    weight: exchange.weight,
+                    timestamp: Some(exchange.timestamp).filter(|timestamp| *timestamp != 0),
+                    books: exchange.books.into_iter().map(OrderBook::from).collect(),
+                })
+                .collect(),
+            pre_fee_amount_in_sats: details.pre_fee_amount_in_sats,
+            pre_fee_amount_in_cents: details.pre_fee_amount_in_cents,
+            fee_rate: details.fee_rate,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AcceptedQuote {}
+
+#[derive(Serialize, ToSchema)]
+pub struct CancelledQuote {}
+
+/// Estimate of how much more USD can be sold to users while staying hedged on OKX
+#[derive(Serialize, ToSchema)]
+pub struct Capacity {
+    remaining_buy_usd_in_cents: u64,
+    /// Notional the OKX collateral supports at the leverage high bound
+    max_in_cents: u64,
+    /// Liability currently allocated to OKX
+    allocated_in_cents: u64,
+    /// Open quotes to buy USD
+    reserved_in_cents: u64,
+    /// Unix timestamp of the last balances reported by hedging
+    updated_at: Option<i64>,
+    hedging_healthy: bool,
+}
+
+impl From<GetCapacityResponse> for Capacity {
+    fn from(capacity: GetCapacityResponse) -> Self {
+        Self {
+            remaining_buy_usd_in_cents: capacity.remaining_buy_usd_in_cents,
+            max_in_cents: capacity.max_in_cents,
+            allocated_in_cents: capacity.allocated_in_cents,
+            reserved_in_cents: capacity.reserved_in_cents,
+            updated_at: Some(capacity.updated_at).filter(|updated_at| *updated_at != 0),
+            hedging_healthy: capacity.hedging_healthy,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ListQuotesQuery {
+    /// `buy_cents` or `sell_cents`
+    direction: Option<String>,
+    /// `pending`, `accepted`, `expired` or `cancelled`
+    state: Option<String>,
+    /// Unix timestamp of the earliest creation time
+    #[serde(default)]
+    created_from: i64,
+    /// Unix timestamp after the latest creation time
+    #[serde(default)]
+    created_to: i64,
+    /// Defaults to the server maximum
+    #[serde(default)]
+    page_size: u32,
+    /// `next_after_quote_id` of the previous page
+    after_quote_id: Option<String>,
+}
+
+/// A quote with its current state and the events it went through
+#[derive(Serialize, ToSchema)]
+pub struct QuoteDetails {
+    quote_id: String,
+    /// `buy_cents` or `sell_cents`
+    direction: String,
+    /// `pending`, `accepted`, `expired` or `cancelled`
+    state: String,
+    immediate_execution: bool,
+    amount_in_sats: u64,
+    amount_in_cents: u64,
+    sats_spread: i64,
+    cents_spread: i64,
+    expires_at: u32,
+    price_details: Option<PriceDetails>,
+    events: Vec<QuoteEventDetails>,
+    account_id: Option<String>,
+    capacity_limited: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct QuoteEventDetails {
+    sequence: u32,
+    /// `initialized`, `accepted`, `expired` or `cancelled`
+    #[serde(rename = "type")]
+    event_type: String,
+    accepted_at: Option<i64>,
+    expired_at: Option<i64>,
+    cancelled_at: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct QuotesList {
+    quotes: Vec<QuoteDetails>,
+    /// Absent on the last page
+    next_after_quote_id: Option<String>,
+}
+
+impl From<proto::Quote> for QuoteDetails {
+    fn from(quote: proto::Quote) -> Self {
+        let direction = match quote.direction() {
+            proto::QuoteDirection::SellCents => "sell_cents",
+            _ => "buy_cents",
+        };
+        let state = match quote.state() {
+            proto::QuoteState::Accepted => "accepted",
+            proto::QuoteState::Expired => "expired",
+            proto::QuoteState::Cancelled => "cancelled",
+            _ => "pending",
+        };
+        Self {
+            quote_id: quote.quote_id,
+            direction: direction.to_string(),
+            state: state.to_string(),
+            immediate_execution: quote.immediate_execution,
+            amount_in_sats: quote.amount_in_sats,
+            amount_in_cents: quote.amount_in_cents,
+            sats_spread: quote.sats_spread,
+            cents_spread: quote.cents_spread,
+            expires_at: quote.expires_at,
+            price_details: quote.price_details.map(PriceDetails::from),
+            events: quote
+                .events
+                .into_iter()
+                .map(QuoteEventDetails::from)
+                .collect(),
+            account_id: account_id(quote.account_id),
+            capacity_limited: quote.capacity_limited,
+        }
+    }
+}
+
+impl From<proto::QuoteEvent> for QuoteEventDetails {
+    fn from(event: proto::QuoteEvent) -> Self {
+        let mut details = Self {
+            sequence: event.sequence,
+            event_type: "initialized".to_string(),
+            accepted_at: None,
+            expired_at: None,
+            cancelled_at: None,
+        };
+        match event.event {
+            Some(proto::quote_event::Event::Accepted(accepted)) => {
+                details.event_type = "accepted".to_string();
+                details.accepted_at = Some(accepted.accepted_at);
+            }
+            Some(proto::quote_event::Event::Expired(expired)) => {
+                details.event_type = "expired".to_string();
+                details.expired_at = Some(expired.expired_at);
+            }
+            Some(proto::quote_event::Event::Cancelled(cancelled)) => {
+                details.event_type = "cancelled".to_string();
+                details.cancelled_at = Some(cancelled.cancelled_at);
+            }
+            _ => (),
+        }
+        details
+    }
+}
+
+impl From<QuotesAppError> for GatewayError {
+    fn from(err: QuotesAppError) -> Self {
+        Status::from(err).into()
+    }
+}
+
+fn invalid_quote_argument() -> GatewayError {
+    GatewayError::invalid_argument("invalid argument for quote")
+}
+
+/// Reads the optional `Idempotency-Key` header used to deduplicate retries
+fn idempotency_key_header(headers: &HeaderMap) -> Result<Option<String>, GatewayError> {
+    match headers.get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map(|key| idempotency_key(key.to_string()))
+            .map_err(|_| GatewayError::invalid_argument("invalid idempotency key")),
+        None => Ok(None),
+    }
+}
+
+fn quotes_filter(query: &ListQuotesQuery) -> Result<QuotesFilter, QuotesAppError> {
+    let direction = match query.direction.as_deref() {
+        None => None,
+        Some("buy_cents") => Some(Direction::BuyCents),
+        Some("sell_cents") => Some(Direction::SellCents),
+        Some(direction) => {
+            return Err(QuotesAppError::InvalidQuoteFilter(format!(
+                "direction {direction}"
+            )))
+        }
+    };
+    let state = match query.state.as_deref() {
+        None => None,
+        Some("pending") => Some(QuoteState::Pending),
+        Some("accepted") => Some(QuoteState::Accepted),
+        Some("expired") => Some(QuoteState::Expired),
+        Some("cancelled") => Some(QuoteState::Cancelled),
+        Some(state) => return Err(QuotesAppError::InvalidQuoteFilter(format!("state {state}"))),
+    };
+    Ok(QuotesFilter {
+        direction,
+        state,
+        created_from: created_bound(query.created_from)?,
+        created_to: created_bound(query.created_to)?,
+    })
+}
+
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+#[utoipa::path(post, path = "/v1/quotes/buy-usd", request_body = GetQuoteToBuyUsdBody,
+    params(("Idempotency-Key" = Option<String>, Header,)),
+    responses((status = 200, body = QuoteToBuyUsd), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_quote_to_buy_usd(
+    State(app): State<Arc<QuotesApp>>,
+    headers: HeaderMap,
+    Json(body): Json<GetQuoteToBuyUsdBody>,
+) -> Result<Json<QuoteToBuyUsd>, GatewayError> {
+    let quote_request = QuoteRequest {
+        idempotency_key: idempotency_key_header(&headers)?,
+        account_id: body.account_id.and_then(account_id),
+        lifetime: body.lifetime_in_seconds.and_then(lifetime),
+        indicative: body.indicative,
+    };
+    let quote = match (body.amount_to_sell_in_sats, body.amount_to_buy_in_cents) {
+        (Some(amount), None) => {
+            app.quote_cents_from_sats_for_buy(
+                Decimal::from(amount),
+                body.immediate_execution,
+                quote_request,
+            )
+            .await?
+        }
+        (None, Some(amount)) => {
+            app.quote_sats_from_cents_for_buy(
+                Decimal::from(amount),
+                body.immediate_execution,
+                quote_request,
+            )
+            .await?
+        }
+        _ => return Err(invalid_quote_argument()),
+    };
+    let response = GetQuoteToBuyUsdResponse::from(quote);
+    Ok(Json(QuoteToBuyUsd {
+        quote_id: response.quote_id,
+        amount_to_sell_in_sats: response.amount_to_sell_in_sats,
+        amount_to_buy_in_cents: response.amount_to_buy_in_cents,
+        expires_at: response.expires_at,
+        executed: response.executed,
+        price_details: response.price_details.map(PriceDetails::from),
+        capacity_limited: response.capacity_limited,
+        indicative: response.indicative,
+    }))
+}
+
+#[utoipa::path(post, path = "/v1/quotes/sell-usd", request_body = GetQuoteToSellUsdBody,
+    params(("Idempotency-Key" = Option<String>, Header,)),
+    responses((status = 200, body = QuoteToSellUsd), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_quote_to_sell_usd(
+    State(app): State<Arc<QuotesApp>>,
+    headers: HeaderMap,
+    Json(body): Json<GetQuoteToSellUsdBody>,
+) -> Result<Json<QuoteToSellUsd>, GatewayError> {
+    let quote_request = QuoteRequest {
+        idempotency_key: idempotency_key_header(&headers)?,
+        account_id: body.account_id.and_then(account_id),
+        lifetime: body.lifetime_in_seconds.and_then(lifetime),
+        indicative: body.indicative,
+    };
+    let quote = match (body.amount_to_buy_in_sats, body.amount_to_sell_in_cents) {
+        (Some(amount), None) => {
+            app.quote_cents_from_sats_for_sell(
+                Decimal::from(amount),
+                body.immediate_execution,
+                quote_request,
+            )
+            .await?
+        }
+        (None, Some(amount)) => {
+            app.quote_sats_from_cents_for_sell(
+                Decimal::from(amount),
+                body.immediate_execution,
+                quote_request,
+            )
+            .await?
+        }
+        _ => return Err(invalid_quote_argument()),
+    };
+    let response = GetQuoteToSellUsdResponse::from(quote);
+    Ok(Json(QuoteToSellUsd {
+        quote_id: response.quote_id,
+        amount_to_buy_in_sats: response.amount_to_buy_in_sats,
+        amount_to_sell_in_cents: response.amount_to_sell_in_cents,
+        expires_at: response.expires_at,
+        executed: response.executed,
+        price_details: response.price_details.map(PriceDetails::from),
+        indicative: response.indicative,
+    }))
+}
+
+#[utoipa::path(post, path = "/v1/quotes/{quote_id}/accept",
+    params(("quote_id" = String, Path,), ("Idempotency-Key" = Option<String>, Header,)),
+    responses((status = 200, body = AcceptedQuote), (status = "default", body = GatewayErrorBody))
+)]
+async fn accept_quote(
+    State(app): State<Arc<QuotesApp>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<AcceptedQuote>, GatewayError> {
+    app.accept_quote(quote_id(&id)?, idempotency_key_header(&headers)?)
+        .await?;
+    Ok(Json(AcceptedQuote {}))
+}
+
+#[utoipa::path(post, path = "/v1/quotes/{quote_id}/cancel",
+    params(("quote_id" = String, Path,)),
+    responses((status = 200, body = CancelledQuote), (status = "default", body = GatewayErrorBody))
+)]
+async fn cancel_quote(
+    State(app): State<Arc<QuotesApp>>,
+    Path(id): Path<String>,
+) -> Result<Json<CancelledQuote>, GatewayError> {
+    app.cancel_quote(quote_id(&id)?).await?;
+    Ok(Json(CancelledQuote {}))
+}
+
+#[utoipa::path(get, path = "/v1/quotes/{quote_id}",
+    params(("quote_id" = String, Path,)),
+    responses((status = 200, body = QuoteDetails), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_quote(
+    State(app): State<Arc<QuotesApp>>,
+    Path(id): Path<String>,
+) -> Result<Json<QuoteDetails>, GatewayError> {
+    let quote = app.get_quote(quote_id(&id)?).await?;
+    Ok(Json(QuoteDetails::from(proto::Quote::from(quote))))
+}
+
+#[utoipa::path(get, path = "/v1/quotes", params(ListQuotesQuery),
+    responses((status = 200, body = QuotesList), (status = "default", body = GatewayErrorBody))
+)]
+async fn list_quotes(
+    State(app): State<Arc<QuotesApp>>,
+    Query(query): Query<ListQuotesQuery>,
+) -> Result<Json<QuotesList>, GatewayError> {
+    let page = app
+        .list_quotes(
+            quotes_filter(&query)?,
+            after_quote_id(query.after_quote_id.as_deref().unwrap_or_default())?,
+            query.page_size,
+        )
+        .await?;
+    Ok(Json(QuotesList {
+        quotes: page
+            .quotes
+            .into_iter()
+            .map(|quote| QuoteDetails::from(proto::Quote::from(quote)))
+            .collect(),
+        next_after_quote_id: page.next_after.map(|id| id.to_string()),
+    }))
+}
+
+#[utoipa::path(get, path = "/v1/capacity",
+    responses((status = 200, body = Capacity), (status = "default", body = GatewayErrorBody))
+)]
+async fn get_capacity(State(app): State<Arc<QuotesApp>>) -> Result<Json<Capacity>, GatewayError> {
+    let capacity = app.hedging_capacity().await?;
+    Ok(Json(Capacity::from(GetCapacityResponse::from(capacity))))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_quote_to_buy_usd,
+        get_quote_to_sell_usd,
+        accept_quote,
+        cancel_quote,
+        get_quote,
+        list_quotes,
+        get_capacity
+    ),
+    components(schemas(
+        GetQuoteToBuyUsdBody,
+        GetQuoteToSellUsdBody,
+        QuoteToBuyUsd,
+        QuoteToSellUsd,
+        PriceDetails,
+        ExchangeWeight,
+        OrderBook,
+        BookLevel,
+        AcceptedQuote,
+        CancelledQuote,
+        QuoteDetails,
+        QuoteEventDetails,
+        QuotesList,
+        Capacity,
+        GatewayErrorBody
+    ))
+)]
+pub struct QuotesGatewayApiDoc;
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(QuotesGatewayApiDoc::openapi())
+}
+
+pub(super) async fn start(
+    config: QuotesGatewayConfig,
+    app: Arc<QuotesApp>,
+) -> Result<(), std::io::Error> {
+    let router = Router::new()
+        .route("/v1/quotes/buy-usd", post(get_quote_to_buy_usd))
+        .route("/v1/quotes/sell-usd", post(get_quote_to_sell_usd))
+        .route("/v1/quotes/:quote_id/accept", post(accept_quote))
+        .route("/v1/quotes/:quote_id/cancel", post(cancel_quote))
+        .route("/v1/quotes/:quote_id", get(get_quote))
+        .route("/v1/quotes", get(list_quotes))
+        .route("/v1/capacity", get(get_capacity))
+        .route("/openapi.json", get(openapi))
+        .with_state(app);
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], config.listen_port));
+    axum::Server::try_bind(&addr)
+        .map_err(std::io::Error::other)?
+        .serve(router.into_make_service())
+        .await
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openapi_covers_every_rpc() {
+        let doc = QuotesGatewayApiDoc::openapi();
+        assert_eq!(doc.paths.paths.len(), 7);
+        assert!(doc.paths.paths.contains_key("/v1/quotes/{quote_id}/accept"));
+        assert!(doc.paths.paths.contains_key("/v1/quotes/{quote_id}/cancel"));
+        assert!(doc.paths.paths.contains_key("/v1/quotes/{quote_id}"));
+        assert!(doc.paths.paths.contains_key("/v1/capacity"));
+    }
+
+    #[test]
+    fn omits_unreported_capacity_timestamp() {
+        let capacity = Capacity::from(GetCapacityResponse {
+            remaining_buy_usd_in_cents: 0,
+            max_in_cents: 0,
+            allocated_in_cents: 0,
+            reserved_in_cents: 0,
+            updated_at: 0,
+            hedging_healthy: false,
+        });
+        assert!(capacity.updated_at.is_none());
+    }
+
+    #[test]
+    fn parses_list_filter() {
+        let query = ListQuotesQuery {
+            direction: Some("sell_cents".to_string()),
+            state: Some("expired".to_string()),
+            created_from: 1_700_000_000,
+            created_to: 0,
+            page_size: 0,
+            after_quote_id: None,
+        };
+        let filter = quotes_filter(&query).unwrap();
+        assert_eq!(filter.direction, Some(Direction::SellCents));
+        assert_eq!(filter.state, Some(QuoteState::Expired));
+        assert!(filter.created_from.is_some());
+        assert!(filter.created_to.is_none());
+
+        let query = ListQuotesQuery {
+            state: Some("open".to_string()),
+            ..query
+        };
+        assert!(matches!(
+            quotes_filter(&query),
+            Err(QuotesAppError::InvalidQuoteFilter(_))
+        ));
+    }
+
+    #[test]
+    fn describes_quote_events() {
+        let details = QuoteEventDetails::from(proto::QuoteEvent {
+            sequence: 2,
+            event: Some(proto::quote_event::Event::Cancelled(
+                proto::QuoteCancelled {
+                    cancelled_at: 1_700_000_000,
+                },
+            )),
+        });
+        assert_eq!(details.event_type, "cancelled");
+        assert_eq!(details.cancelled_at, Some(1_700_000_000));
+        assert!(details.accepted_at.is_none());
+    }
+
+    #[test]
+    fn reads_idempotency_key_header() {
+        let mut headers = HeaderMap::new();
+        assert!(matches!(idempotency_key_header(&headers), Ok(None)));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "retry-1".parse().unwrap());
+        assert_eq!(
+            idempotency_key_header(&headers).ok().flatten().as_deref(),
+            Some("retry-1")
+        );
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "".parse().unwrap());
+        assert!(matches!(idempotency_key_header(&headers), Ok(None)));
+    }
+}
@@ -2,6 +2,7 @@
 mod config;
 mod convert;
 mod error;
+mod gateway;
 
 #[allow(clippy::all)]
 pub mod proto {
@@ -12,17 +13,20 @@ use opentelemetry::propagation::{Extractor, TextMapPropagator};
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use proto::{quote_service_server::QuoteService, *};
 use rust_decimal::Decimal;
+use std::sync::Arc;
 use tonic::{transport::Server, Request, Response, Status};
 use tracing::instrument;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-use crate::{app::*, error::QuotesAppError};
+use crate::{app::*, quote::QuotesFilter};
+use convert::{account_id, after_quote_id, idempotency_key, lifetime, quote_id};
 
 pub use config::*;
 pub use error::*;
+pub use gateway::QuotesGatewayApiDoc;
 
 pub struct Quotes {
-    app: QuotesApp,
+    app: Arc<QuotesApp>,
 }
 
 #[tonic::async_trait]
@@ -38,12 +42,19 @@ impl QuoteService for Quotes {
         shared::tracing::record_error(tracing::Level::ERROR, || async move {
             extract_tracing(&request);
             let req = request.into_inner();
+            let quote_request = QuoteRequest {
+                idempotency_key: idempotency_key(req.idempotency_key),
+                account_id: account_id(req.account_id),
+                lifetime: lifetime(req.lifetime_in_seconds),
+                indicative: req.indicative,
+            };
             let quote = match req.quote_for {
                 Some(get_quote_to_buy_usd_request::QuoteFor::AmountToSellInSats(amount)) => {
                     self.app
                         .quote_cents_from_sats_for_buy(
                             Decimal::from(amount),
                             req.immediate_execution,
+                            quote_request,
                         )
                         .await?
                 }
@@ -52,6 +63,7 @@ impl QuoteService for Quotes {
                         .quote_sats_from_cents_for_buy(
                             Decimal::from(amount),
                             req.immediate_execution,
+                            quote_request,
                         )
                         .await?
                 }
@@ -75,12 +87,19 @@ impl QuoteService for Quotes {
         shared::tracing::record_error(tracing::Level::ERROR, || async move {
             extract_tracing(&request);
             let req = request.into_inner();
+            let quote_request = QuoteRequest {
+                idempotency_key: idempotency_key(req.idempotency_key),
+                account_id: account_id(req.account_id),
+                lifetime: lifetime(req.lifetime_in_seconds),
+                indicative: req.indicative,
+            };
             let quote = match req.quote_for {
                 Some(get_quote_to_sell_usd_request::QuoteFor::AmountToSellInCents(amount)) => {
                     self.app
                         .quote_sats_from_cents_for_sell(
                             Decimal::from(amount),
                             req.immediate_execution,
+                            quote_request,
                         )
                         .await?
                 }
@@ -89,6 +108,7 @@ impl QuoteService for Quotes {
                         .quote_cents_from_sats_for_sell(
                             Decimal::from(amount),
                             req.immediate_execution,
+                            quote_request,
                         )
                         .await?
                 }
@@ -114,26 +134,120 @@ impl QuoteService for Quotes {
             let req = request.into_inner();
             self.app
                 .accept_quote(
-                    req.quote_id
-                        .parse()
-                        .map_err(QuotesAppError::CouldNotParseIncomingUuid)?,
+                    quote_id(&req.quote_id)?,
+                    idempotency_key(req.idempotency_key),
                 )
                 .await?;
             Ok(Response::new(AcceptQuoteResponse {}))
         })
         .await
     }
+
+    #[instrument(name = "quotes_server.cancel_quote", skip_all,
+    fields(error, error.level, error.message),
+    err
+    )]
+    async fn cancel_quote(
+        &self,
+        request: Request<CancelQuoteRequest>,
+    ) -> Result<Response<CancelQuoteResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+            let req = request.into_inner();
+            self.app.cancel_quote(quote_id(&req.quote_id)?).await?;
+            Ok(Response::new(CancelQuoteResponse {}))
+        })
+        .await
+    }
+
+    #[instrument(name = "quotes_server.get_capacity", skip_all,
+    fields(error, error.level, error.message),
+    err
+    )]
+    async fn get_capacity(
+        &self,
+        request: Request<GetCapacityRequest>,
+    ) -> Result<Response<GetCapacityResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+            let capacity = self.app.hedging_capacity().await?;
+            Ok(Response::new(GetCapacityResponse::from(capacity)))
+        })
+        .await
+    }
+
+    #[instrument(name = "quotes_server.get_quote", skip_all,
+    fields(error, error.level, error.message),
+    err
+    )]
+    async fn get_quote(
+        &self,
+        request: Request<GetQuoteRequest>,
+    ) -> Result<Response<GetQuoteResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+            let req = request.into_inner();
+            let quote = self.app.get_quote(quote_id(&req.quote_id)?).await?;
+            Ok(Response::new(GetQuoteResponse {
+                quote: Some(proto::Quote::from(quote)),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "quotes_server.list_quotes", skip_all,
+    fields(error, error.level, error.message),
+    err
+    )]
+    async fn list_quotes(
+        &self,
+        request: Request<ListQuotesRequest>,
+    ) -> Result<Response<ListQuotesResponse>, Status> {
+        shared::tracing::record_error(tracing::Level::ERROR, || async move {
+            extract_tracing(&request);
+            let req = request.into_inner();
+            let page = self
+                .app
+                .list_quotes(
+                    QuotesFilter::try_from(&req)?,
+                    after_quote_id(&req.after_quote_id)?,
+                    req.page_size,
+                )
+                .await?;
+            Ok(Response::new(ListQuotesResponse {
+                quotes: page.quotes.into_iter().map(proto::Quote::from).collect(),
+                next_after_quote_id: page.next_after.map(|id| id.to_string()).unwrap_or_default(),
+            }))
+        })
+        .await
+    }
 }
 
 pub(crate) async fn start(
     server_config: QuotesServerConfig,
     app: QuotesApp,
 ) -> Result<(), QuotesServerError> {
-    let quote_service = Quotes { app };
-    Server::builder()
-        .add_service(quote_service_server::QuoteServiceServer::new(quote_service))
-        .serve(([0, 0, 0, 0], server_config.listen_port).into())
-        .await?;
+    let app = Arc::new(app);
+    let quote_service = Quotes {
+        app: Arc::clone(&app),
+    };
+    let listen_port = server_config.listen_port;
+    let grpc = async move {
+        Server::builder()
+            .add_service(quote_service_server::QuoteServiceServer::new(quote_service))
+            .serve(([0, 0, 0, 0], listen_port).into())
+            .await?;
+        Ok::<_, QuotesServerError>(())
+    };
+    if server_config.gateway.enabled {
+        let gateway = async move {
+            gateway::start(server_config.gateway, app).await?;
+            Ok::<_, QuotesServerError>(())
+        };
+        tokio::try_join!(grpc, gateway)?;
+    } else {
+        grpc.await?;
+    }
     Ok(())
 }
 
@@ -2,10 +2,12 @@ use chrono::Duration;
 use rust_decimal_macros::dec;
 use serial_test::file_serial;
 
+use pricing::OrderBookCacheError;
 use quotes_server::error::QuotesAppError;
+use quotes_server::quote::{Direction, QuoteError, QuoteState, QuotesFilter};
 use quotes_server::{
-    app::*, cache::OrderBookCacheError, ExchangePriceCacheError, QuotesExchangePriceCacheConfig,
-    QuotesFeeCalculatorConfig,
+    app::*, ExchangePriceCacheConfig, ExchangePriceCacheError, ExchangeWeights,
+    FeeCalculatorConfig, PriceMixerConfig,
 };
 
 use shared::{payload::*, pubsub::*, time::*};
@@ -28,22 +30,12 @@ fn load_fixture() -> OrderBookPayload {
     }
 }
 
-#[tokio::test]
-#[file_serial]
-async fn quotes_app() -> anyhow::Result<()> {
-    let (tick_send, tick_recv) =
-        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
-    let publisher = tick_send.clone();
-    let mut subscriber = tick_recv.resubscribe();
-
+async fn init_app(
+    quotes_config: QuotesConfig,
+    tick_recv: memory::Subscriber<PriceStreamPayload>,
+) -> anyhow::Result<(QuotesApp, shared::health::HealthDetailsRequester)> {
     let (_, recv) = futures::channel::mpsc::unbounded();
-    let ex_cfgs = ExchangeWeights {
-        okex: Some(dec!(1.0)),
-    };
-
-    let base_fee_rate = dec!(0.001);
-    let immediate_fee_rate = dec!(0.01);
-    let delayed_fee_rate = dec!(0.1);
+    let (details_send, details_recv) = futures::channel::mpsc::unbounded();
 
     let pg_host = std::env::var("PG_HOST").unwrap_or_else(|_| "localhost".into());
     let pg_port = std::env::var("PG_PORT").unwrap_or_else(|_| "5432".into());
@@ -54,62 +46,334 @@ async fn quotes_app() -> anyhow::Result<()> {
     let app = QuotesApp::run(
         pool,
         recv,
+        details_recv,
         QuotesServerHealthCheckConfig::default(),
-        QuotesFeeCalculatorConfig {
-            base_fee_rate,
-            immediate_fee_rate,
-            delayed_fee_rate,
+        FeeCalculatorConfig {
+            base_fee_rate: dec!(0.001),
+            immediate_fee_rate: dec!(0.01),
+            delayed_fee_rate: dec!(0.1),
+            ..Default::default()
         },
         tick_recv,
-        QuotesExchangePriceCacheConfig::default(),
-        ex_cfgs,
+        ExchangePriceCacheConfig::default(),
+        PriceMixerConfig::default(),
+        ExchangeWeights {
+            okex: Some(dec!(1.0)),
+            bitfinex: None,
+            binance: None,
+            kraken: None,
+        },
         QuotesConfig {
             expiration_interval: Duration::try_seconds(2).expect("valid duration"),
+            ..quotes_config
         },
         ledger,
     )
     .await?;
+    Ok((app, details_send))
+}
 
-    let err = app
-        .quote_cents_from_sats_for_buy(dec!(100_000_000), true)
-        .await;
-    if let Err(QuotesAppError::ExchangePriceCacheError(ExchangePriceCacheError::OrderBookCache(
-        OrderBookCacheError::NoSnapshotAvailable,
-    ))) = err
-    {
-        assert!(true)
-    } else {
-        assert!(false)
-    }
-
+async fn init_app_with_price(
+    quotes_config: QuotesConfig,
+) -> anyhow::Result<(QuotesApp, shared::health::HealthDetailsRequester)> {
+    let (publisher, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let mut subscriber = tick_recv.resubscribe();
+    let (app, details) = init_app(quotes_config, tick_recv).await?;
     let mut payload = load_fixture();
-    tick_send
-        .publish(PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(
-            payload.clone(),
-        ))
-        .await?;
-    subscriber.next().await;
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-
     payload.timestamp = TimeStamp::now();
     publisher
         .publish(PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(payload))
         .await?;
     subscriber.next().await;
     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    Ok((app, details))
+}
 
-    let quote = app
-        .quote_cents_from_sats_for_buy(dec!(100_000_000), false)
+#[tokio::test]
+#[file_serial]
+async fn quote_fails_without_price() -> anyhow::Result<()> {
+    let (_publisher, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (app, _) = init_app(QuotesConfig::default(), tick_recv).await?;
+
+    let err = app
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), true, QuoteRequest::default())
         .await;
-    assert!(quote.is_ok());
-    let accepted = app.accept_quote(quote.unwrap().id).await;
-    assert!(accepted.is_ok());
+    assert!(matches!(
+        err,
+        Err(QuotesAppError::ExchangePriceCacheError(
+            ExchangePriceCacheError::OrderBookCache(OrderBookCacheError::NoSnapshotAvailable)
+        ))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn quote_records_price_details() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
 
     let quote = app
-        .quote_cents_from_sats_for_buy(dec!(100_000_000), true)
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), false, QuoteRequest::default())
+        .await?;
+    assert!(!quote.is_accepted());
+    let books = &quote.price_details.as_ref().unwrap().exchanges[0].books;
+    assert_eq!(books.len(), 1);
+    assert_eq!(books[0].exchange_id, "okex");
+    assert_eq!(books[0].bids.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn immediate_execution_accepts_quote() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let quote = app
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), true, QuoteRequest::default())
+        .await?;
+    assert!(quote.is_accepted());
+
+    let found = app.get_quote(quote.id).await?;
+    assert_eq!(found.state(), QuoteState::Accepted);
+    assert_eq!(found.cent_amount, quote.cent_amount);
+    assert_eq!(found.events().count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn list_quotes_pages_through_filter() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let created_from = chrono::Utc::now();
+    let first = app
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), false, QuoteRequest::default())
+        .await?;
+    app.accept_quote(first.id, None).await?;
+    let second = app
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), true, QuoteRequest::default())
+        .await?;
+    app.quote_cents_from_sats_for_sell(dec!(100_000_000), true, QuoteRequest::default())
+        .await?;
+
+    let filter = QuotesFilter {
+        direction: Some(Direction::BuyCents),
+        state: Some(QuoteState::Accepted),
+        created_from: Some(created_from),
+        ..Default::default()
+    };
+    let page = app.list_quotes(filter.clone(), None, 1).await?;
+    assert_eq!(page.quotes.len(), 1);
+    assert_eq!(page.quotes[0].id, first.id);
+    let page = app.list_quotes(filter, page.next_after, 1).await?;
+    assert_eq!(page.quotes.len(), 1);
+    assert_eq!(page.quotes[0].id, second.id);
+    assert!(page.next_after.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn idempotency_key_returns_existing_quote() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let key = format!("retry-{}", uuid::Uuid::new_v4());
+    let request = QuoteRequest {
+        idempotency_key: Some(key),
+        ..Default::default()
+    };
+    let quote = app
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), true, request.clone())
+        .await?;
+    let retried = app
+        .quote_cents_from_sats_for_buy(dec!(100_000_000), true, request.clone())
+        .await?;
+    assert_eq!(retried.id, quote.id);
+    assert_eq!(retried.events().count(), 2);
+
+    let reused = app
+        .quote_cents_from_sats_for_buy(dec!(50_000_000), true, request)
         .await;
-    assert!(quote.is_ok());
-    assert!(quote.unwrap().is_accepted());
+    assert!(matches!(
+        reused,
+        Err(QuotesAppError::QuoteError(
+            QuoteError::IdempotencyKeyReused(_)
+        ))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn accept_quote_is_idempotent_per_key() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let quote = app
+        .quote_cents_from_sats_for_sell(dec!(100_000_000), false, QuoteRequest::default())
+        .await?;
+    let key = format!("accept-{}", quote.id);
+    app.accept_quote(quote.id, Some(key.clone())).await?;
+    app.accept_quote(quote.id, Some(key)).await?;
+    assert!(matches!(
+        app.accept_quote(quote.id, None).await,
+        Err(QuotesAppError::QuoteError(QuoteError::QuoteAlreadyAccepted))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn cancelled_quote_cannot_be_accepted() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let quote = app
+        .quote_sats_from_cents_for_buy(dec!(1_000), false, QuoteRequest::default())
+        .await?;
+    app.cancel_quote(quote.id).await?;
+    let cancelled = app.get_quote(quote.id).await?;
+    assert_eq!(cancelled.state(), QuoteState::Cancelled);
+    assert!(matches!(
+        app.accept_quote(quote.id, None).await,
+        Err(QuotesAppError::QuoteError(QuoteError::QuoteCancelled))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn account_limits_open_quotes() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig {
+        account_limits: AccountLimitsConfig {
+            max_open_quotes: Some(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
+
+    let request = QuoteRequest {
+        account_id: Some(format!("account-{}", uuid::Uuid::new_v4())),
+        ..Default::default()
+    };
+    app.quote_sats_from_cents_for_buy(dec!(1_000), false, request.clone())
+        .await?;
+    assert!(matches!(
+        app.quote_sats_from_cents_for_buy(dec!(1_000), false, request)
+            .await,
+        Err(QuotesAppError::AccountLimitExceeded(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn indicative_quote_is_not_stored() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let indicative = app
+        .quote_sats_from_cents_for_sell(
+            dec!(1_000),
+            false,
+            QuoteRequest {
+                indicative: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert!(indicative.indicative);
+    assert!(matches!(
+        app.get_quote(indicative.id).await,
+        Err(QuotesAppError::QuoteError(QuoteError::QuoteNotFound(_)))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn requested_lifetime_bounds_expiry() -> anyhow::Result<()> {
+    let (app, _) = init_app_with_price(QuotesConfig::default()).await?;
+
+    let quote = app
+        .quote_sats_from_cents_for_sell(
+            dec!(1_000),
+            false,
+            QuoteRequest {
+                lifetime: Some(Duration::try_seconds(5).expect("valid duration")),
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert!(
+        quote.expires_at <= chrono::Utc::now() + Duration::try_seconds(5).expect("valid duration")
+    );
+    assert!(matches!(
+        app.quote_sats_from_cents_for_sell(
+            dec!(1_000),
+            false,
+            QuoteRequest {
+                lifetime: Some(Duration::try_hours(1).expect("valid duration")),
+                ..Default::default()
+            }
+        )
+        .await,
+        Err(QuotesAppError::InvalidQuoteRequest(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn health_details_report_quote_counts() -> anyhow::Result<()> {
+    let (app, details_send) = init_app_with_price(QuotesConfig::default()).await?;
+
+    app.quote_cents_from_sats_for_buy(dec!(100_000_000), true, QuoteRequest::default())
+        .await?;
+
+    let mut details = shared::health::HealthDetails::new();
+    for _ in 0..20 {
+        let (snd, recv) = futures::channel::oneshot::channel();
+        details_send.unbounded_send(snd)?;
+        details = recv.await?;
+        if !details.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+    assert!(details.contains_key("open_quotes"));
+    assert!(details["accepted_quotes"] > 0);
+    assert!(details.contains_key("expired_quotes"));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[file_serial]
+async fn quotes_app_with_recorded_feed() -> anyhow::Result<()> {
+    let (tick_send, tick_recv) =
+        memory::channel(chrono::Duration::from_std(std::time::Duration::from_secs(2)).unwrap());
+    let (app, _) = init_app(QuotesConfig::default(), tick_recv).await?;
+
+    let replay =
+        okex_price::FeedReplayConfig::new("../okex-price/tests/fixtures/recorded-feed.jsonl");
+    okex_price::replay(tick_send, replay).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let quote = app
+        .quote_cents_from_sats_for_sell(dec!(100_000_000), false, QuoteRequest::default())
+        .await?;
+    assert!(!quote.is_accepted());
 
     Ok(())
 }
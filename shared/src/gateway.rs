@@ -0,0 +1,170 @@
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts,
+    },
+    http::{request::Parts, Request, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tonic::{Code, Status};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct GatewayErrorBody {
+    code: String,
+    message: String,
+}
+
+/// Carries the same `Status` the gRPC service would return so both
+/// transports report errors identically.
+pub struct GatewayError(Box<Status>);
+
+impl GatewayError {
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::from(Status::invalid_argument(message))
+    }
+}
+
+impl From<Status> for GatewayError {
+    fn from(status: Status) -> Self {
+        Self(Box::new(status))
+    }
+}
+
+impl From<QueryRejection> for GatewayError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::invalid_argument(rejection.body_text())
+    }
+}
+
+impl From<JsonRejection> for GatewayError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::invalid_argument(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for GatewayError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::invalid_argument(rejection.body_text())
+    }
+}
+
+fn status_code(code: Code) -> StatusCode {
+    match code {
+        Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl IntoResponse for GatewayError {
+    fn into_response(self) -> Response {
+        let body = GatewayErrorBody {
+            code: format!("{:?}", self.0.code()),
+            message: self.0.message().to_string(),
+        };
+        (status_code(self.0.code()), axum::Json(body)).into_response()
+    }
+}
+
+/// `axum::extract::Query` that rejects with a `GatewayErrorBody`
+pub struct Query<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = GatewayError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::extract::Query(value) =
+            axum::extract::Query::from_request_parts(parts, state).await?;
+        Ok(Self(value))
+    }
+}
+
+/// `axum::extract::Path` that rejects with a `GatewayErrorBody`
+pub struct Path<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = GatewayError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::extract::Path(value) =
+            axum::extract::Path::from_request_parts(parts, state).await?;
+        Ok(Self(value))
+    }
+}
+
+/// `axum::Json` that rejects with a `GatewayErrorBody`
+pub struct Json<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for Json<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = GatewayError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::Json(value) = axum::Json::from_request(req, state).await?;
+        Ok(Self(value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rejections_report_invalid_argument() {
+        let request = Request::builder()
+            .uri("/?amount=abc")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        #[derive(serde::Deserialize)]
+        struct AmountQuery {
+            #[allow(dead_code)]
+            amount: u64,
+        }
+        let Err(err) = Query::<AmountQuery>::from_request_parts(&mut parts, &()).await else {
+            panic!("query should be rejected");
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().data().await.unwrap().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "InvalidArgument");
+    }
+}
@@ -1,5 +1,76 @@
-use futures::channel::{mpsc::*, oneshot};
+use futures::{
+    channel::{mpsc::*, oneshot},
+    StreamExt,
+};
+use tokio::sync::RwLock;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::time::TimeStamp;
 
 pub type HealthCheckResponse = Result<(), String>;
 pub type HealthCheckTrigger = UnboundedReceiver<oneshot::Sender<HealthCheckResponse>>;
 pub type HealthChecker = UnboundedSender<oneshot::Sender<HealthCheckResponse>>;
+
+/// Figures a component reports alongside its health, e.g. how many entities it manages
+pub type HealthDetails = BTreeMap<&'static str, u64>;
+pub type HealthDetailsTrigger = UnboundedReceiver<oneshot::Sender<HealthDetails>>;
+pub type HealthDetailsRequester = UnboundedSender<oneshot::Sender<HealthDetails>>;
+
+/// Reports a price feed as unhealthy when it hasn't produced data for longer than `unhealthy_after`
+#[derive(Clone)]
+pub struct FeedHealthCheck {
+    name: &'static str,
+    unhealthy_after: chrono::Duration,
+    last_update: Arc<RwLock<Option<TimeStamp>>>,
+}
+
+impl FeedHealthCheck {
+    pub fn new(name: &'static str, unhealthy_after: chrono::Duration) -> Self {
+        Self {
+            name,
+            unhealthy_after,
+            last_update: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub async fn record_update(&self) {
+        *self.last_update.write().await = Some(TimeStamp::now());
+    }
+
+    pub async fn healthy(&self) -> HealthCheckResponse {
+        match *self.last_update.read().await {
+            Some(ts) if ts.duration_since() <= self.unhealthy_after => Ok(()),
+            Some(ts) => Err(format!(
+                "No '{}' updates in the last {} seconds",
+                self.name,
+                ts.duration_since().num_seconds()
+            )),
+            None => Err(format!("No '{}' updates received", self.name)),
+        }
+    }
+
+    pub fn respond_to(&self, mut health_check_trigger: HealthCheckTrigger) {
+        let health = self.clone();
+        tokio::spawn(async move {
+            while let Some(check) = health_check_trigger.next().await {
+                let _ = check.send(health.healthy().await);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn feed_health_check() {
+        let health = FeedHealthCheck::new("book", chrono::Duration::try_seconds(5).unwrap());
+        assert!(health.healthy().await.is_err());
+        health.record_update().await;
+        assert!(health.healthy().await.is_ok());
+        *health.last_update.write().await = Some(TimeStamp::from(1_u64));
+        assert!(health.healthy().await.is_err());
+    }
+}
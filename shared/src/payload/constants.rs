@@ -1 +1,4 @@
 pub const OKEX_EXCHANGE_ID: &str = "okex";
+pub const BITFINEX_EXCHANGE_ID: &str = "bitfinex";
+pub const BINANCE_EXCHANGE_ID: &str = "binance";
+pub const KRAKEN_EXCHANGE_ID: &str = "kraken";
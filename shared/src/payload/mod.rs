@@ -20,16 +20,43 @@ pub struct PriceMessagePayload {
     pub bid_price: PriceRatioRaw,
 }
 
+/// A single reference price such as a mark or index price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferencePricePayload {
+    pub timestamp: TimeStamp,
+    pub exchange: ExchangeIdRaw,
+    pub instrument_id: InstrumentIdRaw,
+    pub price: PriceRatioRaw,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum PriceStreamPayload {
     OkexBtcSwapPricePayload(PriceMessagePayload),
     BitfinexBtcUsdSwapPricePayload(PriceMessagePayload),
     OkexBtcUsdSwapOrderBookPayload(OrderBookPayload),
+    OkexBtcUsdSwapMarkPricePayload(ReferencePricePayload),
+    OkexBtcUsdIndexPricePayload(ReferencePricePayload),
+    BitfinexBtcUsdSwapOrderBookPayload(OrderBookPayload),
+    BinanceBtcUsdtSwapOrderBookPayload(OrderBookPayload),
+    KrakenBtcUsdOrderBookPayload(OrderBookPayload),
 }
 
 crate::payload! { PriceStreamPayload, "price.stream" }
 
+impl PriceStreamPayload {
+    pub fn order_book(&self) -> Option<&OrderBookPayload> {
+        match self {
+            PriceStreamPayload::OkexBtcUsdSwapOrderBookPayload(book)
+            | PriceStreamPayload::BitfinexBtcUsdSwapOrderBookPayload(book)
+            | PriceStreamPayload::BinanceBtcUsdtSwapOrderBookPayload(book)
+            | PriceStreamPayload::KrakenBtcUsdOrderBookPayload(book) => Some(book),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SynthUsdLiabilityPayload {
     pub liability: SyntheticCentLiability,
@@ -20,6 +20,10 @@ impl TimeStamp {
     pub fn duration_since(&self) -> Duration {
         &Self::now() - self
     }
+
+    pub fn timestamp(&self) -> i64 {
+        self.0.timestamp()
+    }
 }
 impl PartialOrd for TimeStamp {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
@@ -32,6 +36,12 @@ impl From<u64> for TimeStamp {
     }
 }
 
+impl From<TimeStamp> for DateTime<Utc> {
+    fn from(timestamp: TimeStamp) -> Self {
+        timestamp.0
+    }
+}
+
 impl std::fmt::Display for TimeStamp {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.0.timestamp())